@@ -1,10 +1,15 @@
 use cairo::{Format, ImageSurface, Surface};
 use std::fmt;
-use std::fs::File;
+use std::fs::{self, File};
+use std::path::Path;
 
+use crate::data::DataLoader;
+use crate::expr::Scope;
+use crate::format::InputFormat;
 use crate::loader::SourceLoader;
-use crate::node::Root;
+use crate::node::{json_schema, to_canonical_yaml, Diagnostic, NodeError, Root, Severity};
 use crate::renderer::render;
+use crate::source::SourceMap;
 use crate::{Context, ContextImpl};
 
 #[derive(Debug)]
@@ -13,6 +18,9 @@ pub enum CompileError {
     ParseError(String),
     OutputError(String),
     ExportError,
+    DataError(String),
+    AlreadyExistsError(String),
+    ValidationError(Vec<String>),
 }
 
 impl fmt::Display for CompileError {
@@ -24,6 +32,11 @@ impl fmt::Display for CompileError {
                 f.write_fmt(format_args!("file output error! path: '{}'", x))
             }
             CompileError::ExportError => f.write_str("file export error!"),
+            CompileError::DataError(x) => f.write_str(x),
+            CompileError::AlreadyExistsError(x) => {
+                f.write_fmt(format_args!("{} is already exists.", x))
+            }
+            CompileError::ValidationError(x) => f.write_str(&x.join("\n")),
         }
     }
 }
@@ -32,25 +45,226 @@ pub struct Compiler {}
 
 impl Compiler {
     pub fn compile_to_png(src_path: String, out_path: String) -> Result<u8, CompileError> {
-        let src = SourceLoader::load(src_path.as_str())
+        Self::compile_to_png_with_scope(src_path, out_path, None, &Scope::new())
+    }
+
+    /// Like `compile_to_png`, with variables that take precedence over the
+    /// `vars` of the source. The source is read as `format`, or as its
+    /// extension tells when `None`.
+    pub fn compile_to_png_with_scope(
+        src_path: String,
+        out_path: String,
+        format: Option<InputFormat>,
+        scope: &Scope,
+    ) -> Result<u8, CompileError> {
+        let (src, map) = SourceLoader::load(src_path.as_str(), format)
+            .map_err(|x| -> CompileError { CompileError::LoadError(x.to_string()) })?;
+        let node = Root::parse(&src, scope).map_err(|x| -> CompileError {
+            let diagnostics = Root::validate(&src, scope);
+            if diagnostics.iter().any(Diagnostic::is_error) {
+                CompileError::ValidationError(Self::describe(&map, &diagnostics))
+            } else {
+                CompileError::ParseError(Self::locate(&map, &x))
+            }
+        })?;
+        Self::export_png(node, out_path)
+    }
+
+    /// Checks every layer of `src_path` without rendering. Returns the
+    /// warnings, or every error and warning when there is an error.
+    pub fn validate(
+        src_path: String,
+        format: Option<InputFormat>,
+        scope: &Scope,
+    ) -> Result<Vec<String>, CompileError> {
+        let (src, map) = SourceLoader::load(src_path.as_str(), format)
+            .map_err(|x| -> CompileError { CompileError::LoadError(x.to_string()) })?;
+        let diagnostics = Root::validate(&src, scope);
+        let ret = Self::describe(&map, &diagnostics);
+        if diagnostics.iter().any(Diagnostic::is_error) {
+            return Err(CompileError::ValidationError(ret));
+        }
+        Ok(ret)
+    }
+
+    /// Returns the names of the `themes:` of `src_path` in declared order.
+    pub fn themes(
+        src_path: String,
+        format: Option<InputFormat>,
+    ) -> Result<Vec<String>, CompileError> {
+        let (src, _) = SourceLoader::load(src_path.as_str(), format)
+            .map_err(|x| -> CompileError { CompileError::LoadError(x.to_string()) })?;
+        Ok(src["themes"]
+            .as_hash()
+            .map(|x| {
+                x.keys()
+                    .filter_map(|x| x.as_str())
+                    .map(|x| x.to_string())
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    /// Returns `src_path` and every file it includes or imports, the files
+    /// whose changes change its output.
+    pub fn dependencies(
+        src_path: String,
+        format: Option<InputFormat>,
+    ) -> Result<Vec<String>, CompileError> {
+        let (_, map) = SourceLoader::load(src_path.as_str(), format)
+            .map_err(|x| -> CompileError { CompileError::LoadError(x.to_string()) })?;
+        Ok(map.files().into_iter().map(|x| x.to_string()).collect())
+    }
+
+    /// Returns `src_path` rewritten as canonical YAML, with its comments.
+    /// Its `include:` and `imports:` files are left as they are.
+    pub fn format(src_path: String) -> Result<String, CompileError> {
+        if InputFormat::from_path(Path::new(&src_path)) != InputFormat::Yaml {
+            return Err(CompileError::LoadError(format!(
+                "{} is not a yaml file.",
+                src_path
+            )));
+        }
+        let (src, text, entries) = SourceLoader::load_source(src_path.as_str())
             .map_err(|x| -> CompileError { CompileError::LoadError(x.to_string()) })?;
-        let node = Root::parse(&src)
-            .map_err(|x| -> CompileError { CompileError::ParseError(x.to_string()) })?;
+        Ok(to_canonical_yaml(&src, &text, &entries))
+    }
+
+    /// Returns a JSON Schema of documents for editor completion and
+    /// validation.
+    pub fn schema() -> String {
+        serde_json::to_string_pretty(&json_schema()).unwrap_or_default()
+    }
+
+    /// Reads variables from a mapping in a YAML, JSON or TOML file. Entries
+    /// may be expressions of earlier entries, as in `vars`.
+    pub fn load_vars(path: String) -> Result<Scope, CompileError> {
+        let (src, map) = SourceLoader::load(path.as_str(), None)
+            .map_err(|x| -> CompileError { CompileError::LoadError(x.to_string()) })?;
+        let vars = src.as_hash().ok_or_else(|| -> CompileError {
+            CompileError::ParseError(format!("invalid vars file! path: '{}'", path))
+        })?;
+        let mut scope = Scope::new();
+        scope
+            .define(vars)
+            .map_err(|x| -> CompileError { CompileError::ParseError(Self::locate(&map, &x)) })?;
+        Ok(scope)
+    }
+
+    /// Renders `src_path` once per row of the CSV or JSON file at
+    /// `data_path`, with the row's fields as variables. Each output is named
+    /// after the `name_field` of its row, or numbered after the source file
+    /// when no field is given, followed by the theme of `scope` if any. Row
+    /// fields take precedence over `scope`. Returns the paths written.
+    pub fn compile_batch_to_png(
+        src_path: String,
+        data_path: String,
+        out_dir: String,
+        name_field: Option<String>,
+        overwrite: bool,
+        format: Option<InputFormat>,
+        scope: &Scope,
+    ) -> Result<Vec<String>, CompileError> {
+        let (src, map) = SourceLoader::load(src_path.as_str(), format)
+            .map_err(|x| -> CompileError { CompileError::LoadError(x.to_string()) })?;
+        let rows = DataLoader::load(data_path.as_str())
+            .map_err(|x| -> CompileError { CompileError::LoadError(x.to_string()) })?;
+        let stem = Path::new(&src_path)
+            .file_stem()
+            .map(|x| x.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let mut ret = Vec::new();
+        for (i, row) in rows.iter().enumerate() {
+            let mut name = match &name_field {
+                Some(field) => Self::output_name(row, field, i + 1)?,
+                None => format!("{}-{}", stem, i + 1),
+            };
+            if let Some(theme) = scope.theme() {
+                name = format!("{}.{}", name, theme);
+            }
+            let out_path = Path::new(&out_dir)
+                .join(format!("{}.png", name))
+                .to_string_lossy()
+                .to_string();
+            if !overwrite && Path::new(&out_path).exists() {
+                return Err(CompileError::AlreadyExistsError(out_path));
+            }
+
+            let mut row_scope = scope.clone();
+            row_scope.merge(row);
+            let node = Root::parse(&src, &row_scope).map_err(|x| -> CompileError {
+                CompileError::ParseError(format!("row {}: {}", i + 1, Self::locate(&map, &x)))
+            })?;
+            Self::export_png(node, out_path.clone())?;
+            ret.push(out_path);
+        }
+        Ok(ret)
+    }
+
+    // Prefixes the message with the position of the failed node and appends
+    // the source line.
+    fn locate(map: &SourceMap, e: &NodeError) -> String {
+        Self::annotate(map, e, e.to_string())
+    }
+
+    fn annotate(map: &SourceMap, e: &NodeError, message: String) -> String {
+        match map.locate(e.location().0, e.subject()) {
+            Some(x) => x.render(&message),
+            None => message,
+        }
+    }
+
+    fn describe(map: &SourceMap, diagnostics: &[Diagnostic]) -> Vec<String> {
+        diagnostics
+            .iter()
+            .map(|x| match x.severity {
+                Severity::Error => Self::locate(map, &x.error),
+                Severity::Warning => Self::annotate(map, &x.error, format!("warning: {}", x.error)),
+            })
+            .collect()
+    }
+
+    fn output_name(row: &Scope, field: &str, line: usize) -> Result<String, CompileError> {
+        let name = row
+            .get(field)
+            .map(|x| x.to_string())
+            .ok_or_else(|| -> CompileError {
+                CompileError::DataError(format!("row {} has no '{}' field", line, field))
+            })?;
+        if name.is_empty() || name == "." || name == ".." || name.contains(&['/', '\\'][..]) {
+            return Err(CompileError::DataError(format!(
+                "row {} has invalid output name '{}'",
+                line, name
+            )));
+        }
+        Ok(name)
+    }
+
+    fn export_png(node: Root, out_path: String) -> Result<u8, CompileError> {
         let surface = ImageSurface::create(Format::ARgb32, node.width, node.height).unwrap();
 
         Self::render_context(&surface, node)?;
 
-        let mut f = File::create(out_path.as_str())
-            .map_err(|_| -> CompileError { CompileError::OutputError(out_path) })?;
-        surface
-            .write_to_png(&mut f)
-            .map_err(|_| -> CompileError { CompileError::ExportError })?;
+        // Written aside and moved over the output, so that a failed export
+        // keeps the previous image.
+        let part_path = format!("{}.part", out_path);
+        let mut f = File::create(part_path.as_str())
+            .map_err(|_| -> CompileError { CompileError::OutputError(out_path.clone()) })?;
+        let written = surface.write_to_png(&mut f);
         surface.finish();
+        drop(f);
+        if written.is_err() {
+            let _ = fs::remove_file(part_path.as_str());
+            return Err(CompileError::ExportError);
+        }
+        fs::rename(part_path.as_str(), out_path.as_str())
+            .map_err(|_| -> CompileError { CompileError::OutputError(out_path) })?;
         Ok(0)
     }
 
     fn render_context(surface: &Surface, node: Root) -> Result<u8, CompileError> {
-        let mut context = ContextImpl::new(&surface);
+        let mut context = ContextImpl::new(surface);
         context.save();
         context.set_source_rgba(
             node.color.r as f64 / 255.0,
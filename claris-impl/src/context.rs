@@ -1,24 +1,29 @@
-use cairo::{Context as RealContext, FontSlant, FontWeight, LineCap, Surface};
+use cairo::{Context as RealContext, FontSlant, FontWeight, LineCap, Surface, TextExtents};
 
 pub trait Context {
     fn move_to(&mut self, x: f64, y: f64);
     fn save(&mut self);
     fn restore(&mut self);
     fn fill(&mut self);
+    fn stroke_preserve(&mut self);
     fn stroke(&mut self);
     fn set_source_rgba(&mut self, r: f64, g: f64, b: f64, a: f64);
     fn rectangle(&mut self, x: f64, y: f64, width: f64, height: f64);
     fn translate(&mut self, x: f64, y: f64);
     fn scale(&mut self, x: f64, y: f64);
+    fn rotate(&mut self, angle: f64);
     fn set_line_width(&mut self, width: f64);
     fn set_line_cap(&mut self, cap: LineCap);
     fn arc(&mut self, xc: f64, yc: f64, radius: f64, angle1: f64, angle2: f64);
+    fn arc_negative(&mut self, xc: f64, yc: f64, radius: f64, angle1: f64, angle2: f64);
     fn curve_to(&mut self, x1: f64, y1: f64, x2: f64, y2: f64, x3: f64, y3: f64);
     fn line_to(&mut self, x: f64, y: f64);
     fn close_path(&mut self);
     fn select_font_face(&mut self, family: &str, slant: FontSlant, weight: FontWeight);
     fn set_font_size(&mut self, size: f64);
     fn show_text(&mut self, text: &str);
+    fn text_path(&mut self, text: &str);
+    fn text_extents(&mut self, text: &str) -> TextExtents;
 }
 
 pub struct ContextImpl {
@@ -50,6 +55,10 @@ impl Context for ContextImpl {
         self.context.fill();
     }
 
+    fn stroke_preserve(&mut self) {
+        self.context.stroke_preserve();
+    }
+
     fn stroke(&mut self) {
         self.context.stroke();
     }
@@ -70,6 +79,10 @@ impl Context for ContextImpl {
         self.context.scale(x, y);
     }
 
+    fn rotate(&mut self, angle: f64) {
+        self.context.rotate(angle);
+    }
+
     fn set_line_width(&mut self, width: f64) {
         self.context.set_line_width(width);
     }
@@ -82,6 +95,10 @@ impl Context for ContextImpl {
         self.context.arc(xc, yc, radius, angle1, angle2);
     }
 
+    fn arc_negative(&mut self, xc: f64, yc: f64, radius: f64, angle1: f64, angle2: f64) {
+        self.context.arc_negative(xc, yc, radius, angle1, angle2);
+    }
+
     fn curve_to(&mut self, x1: f64, y1: f64, x2: f64, y2: f64, x3: f64, y3: f64) {
         self.context.curve_to(x1, y1, x2, y2, x3, y3);
     }
@@ -105,4 +122,12 @@ impl Context for ContextImpl {
     fn show_text(&mut self, text: &str) {
         self.context.show_text(text);
     }
+
+    fn text_path(&mut self, text: &str) {
+        self.context.text_path(text);
+    }
+
+    fn text_extents(&mut self, text: &str) -> TextExtents {
+        self.context.text_extents(text)
+    }
 }
@@ -0,0 +1,157 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use serde_json::Value as Json;
+
+use crate::expr::{Scope, Value};
+use crate::loader::LoadError;
+
+/// Loads the rows of a CSV or JSON file for batch rendering. Each row
+/// becomes the external scope of one render.
+pub struct DataLoader {}
+
+impl DataLoader {
+    pub fn load(path: &str) -> Result<Vec<Scope>, LoadError> {
+        let mut file = File::open(path).map_err(|_| LoadError::OpenError(path.to_string()))?;
+        let mut data = String::new();
+        file.read_to_string(&mut data)
+            .map_err(|_| LoadError::ReadError(path.to_string()))?;
+
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|x| x.to_str())
+            .map(|x| x.to_lowercase());
+        match extension.as_deref() {
+            Some("csv") => Self::parse_csv(&data),
+            Some("json") => Self::parse_json(&data),
+            _ => return Err(LoadError::UnknownDataFormatError(path.to_string())),
+        }
+        .ok_or_else(|| LoadError::InvalidDataError(path.to_string()))
+    }
+
+    fn parse_csv(data: &str) -> Option<Vec<Scope>> {
+        let mut reader = csv::Reader::from_reader(data.as_bytes());
+        let headers = reader.headers().ok()?.clone();
+        let mut ret = Vec::new();
+        for record in reader.records() {
+            let record = record.ok()?;
+            let mut scope = Scope::new();
+            for (key, value) in headers.iter().zip(record.iter()) {
+                scope.set(key, Value::parse(value));
+            }
+            ret.push(scope);
+        }
+        Some(ret)
+    }
+
+    fn parse_json(data: &str) -> Option<Vec<Scope>> {
+        let rows = match serde_json::from_str(data).ok()? {
+            Json::Array(x) => x,
+            _ => return None,
+        };
+        let mut ret = Vec::new();
+        for row in rows {
+            let mut scope = Scope::new();
+            for (key, value) in row.as_object()? {
+                let value = match value {
+                    Json::Number(x) => Value::Number(x.as_f64()?),
+                    Json::String(x) => Value::Str(x.clone()),
+                    Json::Bool(x) => Value::Str(x.to_string()),
+                    Json::Null => continue,
+                    _ => return None,
+                };
+                scope.set(key, value);
+            }
+            ret.push(scope);
+        }
+        Some(ret)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DataLoader;
+    use crate::expr::Value;
+    use crate::node::{Layer, Root};
+    use yaml_rust::YamlLoader;
+
+    #[test]
+    fn parse_csv() {
+        let s = "name,x,code,title
+alice,10,007,\"Dr, PhD\"
+bob,2.5,8,
+";
+        let subject = DataLoader::parse_csv(s).unwrap();
+        assert_eq!(subject.len(), 2);
+        assert_eq!(
+            subject[0].get("name"),
+            Some(&Value::Str("alice".to_string()))
+        );
+        assert_eq!(subject[0].get("x"), Some(&Value::Number(10.0)));
+        assert_eq!(subject[0].get("code"), Some(&Value::Str("007".to_string())));
+        assert_eq!(
+            subject[0].get("title"),
+            Some(&Value::Str("Dr, PhD".to_string()))
+        );
+        assert_eq!(subject[1].get("x"), Some(&Value::Number(2.5)));
+        assert_eq!(subject[1].get("title"), Some(&Value::Str("".to_string())));
+    }
+
+    #[test]
+    fn parse_csv_decimal_option() {
+        let rows = DataLoader::parse_csv("size,scale\n14.50,1e1\n").unwrap();
+        assert_eq!(rows[0].get("size"), Some(&Value::Number(14.5)));
+        assert_eq!(rows[0].get("scale"), Some(&Value::Number(10.0)));
+        let s = "---
+width: 400
+height: 300
+layers:
+  - text:
+      x: 10
+      y: 10
+      text: test
+      size: $size
+      color: '#AABBCC'
+";
+        let src = &YamlLoader::load_from_str(s).unwrap()[0];
+        let subject = Root::parse(src, &rows[0]).unwrap_or_else(|e| panic!(e.to_string()));
+        match &subject.layers[0] {
+            Layer::Text(x) => assert!((x.size - 14.5).abs() < f64::EPSILON),
+            _ => panic!("not a text"),
+        }
+    }
+
+    #[test]
+    fn parse_csv_with_uneven_rows() {
+        assert!(DataLoader::parse_csv("a,b\n1,2,3\n").is_none());
+    }
+
+    #[test]
+    fn parse_json() {
+        let s = r#"[
+  {"name": "alice", "x": 10, "vip": true, "note": null},
+  {"name": "bob", "x": 2.5}
+]"#;
+        let subject = DataLoader::parse_json(s).unwrap();
+        assert_eq!(subject.len(), 2);
+        assert_eq!(
+            subject[0].get("name"),
+            Some(&Value::Str("alice".to_string()))
+        );
+        assert_eq!(subject[0].get("x"), Some(&Value::Number(10.0)));
+        assert_eq!(subject[0].get("vip"), Some(&Value::Str("true".to_string())));
+        assert_eq!(subject[0].get("note"), None);
+        assert_eq!(subject[1].get("x"), Some(&Value::Number(2.5)));
+    }
+
+    #[test]
+    fn parse_json_not_array() {
+        assert!(DataLoader::parse_json(r#"{"name": "alice"}"#).is_none());
+    }
+
+    #[test]
+    fn parse_json_nested_value() {
+        assert!(DataLoader::parse_json(r#"[{"name": ["alice"]}]"#).is_none());
+    }
+}
@@ -0,0 +1,430 @@
+use std::path::Path;
+use yaml_rust::{Yaml, YamlLoader};
+
+use crate::expr::Scope;
+use crate::format::InputFormat;
+use crate::loader::{LoadError, SourceLoader};
+use crate::node::{self, Color, Root, Severity, Step, Suggestion};
+use crate::source::{Location, Segment, SourceMap};
+
+/// A range on one line of a source. `line` and `col` start at 1, `end` is
+/// the column after the last character.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Span {
+    pub line: usize,
+    pub col: usize,
+    pub end: usize,
+}
+
+/// An error or warning found in an open document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+    pub span: Span,
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// A color value written in an open document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Swatch {
+    pub span: Span,
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: f32,
+}
+
+/// A source open in an editor, with its text as edited rather than as saved.
+/// Positions are 1-based lines and columns counted in characters.
+pub struct OpenDocument {
+    path: String,
+    text: String,
+}
+
+// Where the cursor is in the outline of a document.
+struct Cursor {
+    steps: Vec<Step>,
+    // The key whose value is being written.
+    key: Option<String>,
+    // Keys already written in the mapping of the cursor.
+    present: Vec<String>,
+}
+
+impl OpenDocument {
+    pub fn new(path: &str, text: &str) -> OpenDocument {
+        OpenDocument {
+            path: path.to_string(),
+            text: text.to_string(),
+        }
+    }
+
+    /// Returns every error and warning `Root::validate` reports, or the
+    /// error that keeps the document from loading. Problems inside included
+    /// files are shown on the first line.
+    pub fn problems(&self) -> Vec<Problem> {
+        let format = InputFormat::from_path(Path::new(&self.path));
+        let (src, map) = match SourceLoader::load_text(&self.path, &self.text, Some(format)) {
+            Ok(x) => x,
+            Err(LoadError::ParseError(location, message)) => {
+                return vec![self.problem(Some(location), Severity::Error, message)]
+            }
+            Err(e) => return vec![self.problem(None, Severity::Error, e.to_string())],
+        };
+
+        Root::validate(&src, &Scope::new())
+            .into_iter()
+            .map(|x| {
+                let (path, e) = x.error.location();
+                let location = map.locate(path, e.subject());
+                self.problem(location, x.severity, x.error.to_string())
+            })
+            .collect()
+    }
+
+    fn problem(&self, location: Option<Location>, severity: Severity, message: String) -> Problem {
+        match location {
+            Some(x) if x.file == self.path => Problem {
+                span: self.span(x.line, x.col),
+                severity,
+                message,
+            },
+            Some(x) => Problem {
+                span: self.span(1, 1),
+                severity,
+                message: format!("{}: {}", x, message),
+            },
+            None => Problem {
+                span: self.span(1, 1),
+                severity,
+                message,
+            },
+        }
+    }
+
+    // The token starting at `col`: a quoted string, or up to the next space
+    // or flow indicator.
+    fn span(&self, line: usize, col: usize) -> Span {
+        let chars: Vec<char> = self
+            .text
+            .lines()
+            .nth(line.saturating_sub(1))
+            .unwrap_or("")
+            .chars()
+            .collect();
+        let start = col.saturating_sub(1);
+        let len = match chars.get(start) {
+            Some(q @ '"') | Some(q @ '\'') => chars[start + 1..]
+                .iter()
+                .position(|x| x == q)
+                .map_or(chars.len() - start, |x| x + 2),
+            Some(_) => chars[start..]
+                .iter()
+                .position(|x| x.is_whitespace() || ",:]}".contains(*x))
+                .unwrap_or(chars.len() - start),
+            None => 0,
+        };
+        Span {
+            line,
+            col,
+            end: col + len.max(1),
+        }
+    }
+
+    fn quoted(&self, span: Span) -> bool {
+        self.text
+            .lines()
+            .nth(span.line.saturating_sub(1))
+            .and_then(|x| x.chars().nth(span.col.saturating_sub(1)))
+            .is_some_and(|x| x == '"' || x == '\'')
+    }
+
+    /// Returns the layer types, option keys or option values that may be
+    /// written at the cursor.
+    pub fn completions(&self, line: usize, col: usize) -> Vec<Suggestion> {
+        let cursor = self.cursor(line, col);
+        match &cursor.key {
+            Some(key) => node::values(&cursor.steps, key),
+            None => node::keys(&cursor.steps, &cursor.present),
+        }
+    }
+
+    /// Returns the docs of the layer type or option key under the cursor.
+    pub fn hover(&self, line: usize, col: usize) -> Option<String> {
+        let text = self.text.lines().nth(line.saturating_sub(1))?;
+        let (start, key) = outline_line(text).1?;
+        if col <= start || col > start + key.chars().count() {
+            return None;
+        }
+        let cursor = self.cursor(line, start + 1);
+        node::describe(&cursor.steps, &key)
+    }
+
+    // Reads the keys and `-` of the lines above the cursor by their
+    // indentation, since the text being edited is often not valid YAML.
+    fn cursor(&self, line: usize, col: usize) -> Cursor {
+        let lines: Vec<&str> = self.text.lines().collect();
+        let mut stack: Vec<(usize, Step)> = Vec::new();
+        let mut siblings: Vec<(usize, String)> = Vec::new();
+        let push = |stack: &mut Vec<(usize, Step)>, at: usize, step: Step| {
+            while stack.last().is_some_and(|(x, _)| *x >= at) {
+                stack.pop();
+            }
+            stack.push((at, step));
+        };
+
+        for text in lines.iter().take(line.saturating_sub(1)) {
+            let (items, key) = outline_line(text);
+            for at in items {
+                push(&mut stack, at, Step::Item);
+            }
+            if let Some((at, key)) = key {
+                push(&mut stack, at, Step::Key(key.clone()));
+                siblings.push((at, key));
+            }
+        }
+
+        let current: String = lines
+            .get(line.saturating_sub(1))
+            .unwrap_or(&"")
+            .chars()
+            .take(col.saturating_sub(1))
+            .collect();
+        let (items, key) = outline_line(&current);
+        for at in &items {
+            push(&mut stack, *at, Step::Item);
+        }
+        let (at, key) = match key {
+            Some((at, key)) => (at, Some(key)),
+            None => {
+                let indent = current.len() - current.trim_start_matches([' ', '-']).len();
+                let word = current.trim_end().len() - current.trim_end().trim_start().len();
+                (indent.max(word), None)
+            }
+        };
+        while stack.last().is_some_and(|(x, _)| *x >= at) {
+            stack.pop();
+        }
+
+        // Keys of the same mapping: those at its column up to its parent.
+        let mut present = Vec::new();
+        for (col, key) in siblings.iter().rev() {
+            if *col < at {
+                break;
+            }
+            if *col == at {
+                present.push(key.clone());
+            }
+        }
+
+        Cursor {
+            steps: stack.into_iter().map(|(_, x)| x).collect(),
+            key,
+            present,
+        }
+    }
+
+    /// Returns the colors written as `color` options and theme palettes.
+    /// Palette references are shown with the color of the first theme.
+    pub fn swatches(&self) -> Vec<Swatch> {
+        let entries = SourceMap::new().add_file(&self.path, &self.text);
+        let doc = YamlLoader::load_from_str(&self.text)
+            .ok()
+            .and_then(|mut x| {
+                if x.is_empty() {
+                    None
+                } else {
+                    Some(x.remove(0))
+                }
+            });
+        let palette = doc
+            .as_ref()
+            .and_then(|x| x["themes"].as_hash())
+            .and_then(|x| x.values().next())
+            .and_then(Yaml::as_hash);
+
+        let mut ret = Vec::new();
+        for entry in &entries {
+            let value = match &entry.scalar {
+                Some(x) => x,
+                None => continue,
+            };
+            let is_color = match entry.path.as_slice() {
+                [Segment::Key(x), _, _] if x == "themes" => true,
+                [.., Segment::Key(x)] => x == "color",
+                _ => false,
+            };
+            if !is_color {
+                continue;
+            }
+            // Plain scalars may hold spaces and commas, as in `rgb(1, 2, 3)`.
+            let mut span = self.span(entry.value.line, entry.value.col);
+            if !self.quoted(span) {
+                span.end = span.col + value.chars().count();
+            }
+            let value = match value.strip_prefix('@') {
+                Some(name) => match palette.and_then(|x| x.get(&Yaml::from_str(name))) {
+                    Some(Yaml::String(x)) => x.clone(),
+                    _ => continue,
+                },
+                None => value.clone(),
+            };
+            if let Ok(Color { r, g, b, a }) = Color::parse(value) {
+                ret.push(Swatch { span, r, g, b, a });
+            }
+        }
+        ret
+    }
+}
+
+// Returns the columns, starting at 0, of the `-` of a line and its key with
+// the key's column.
+fn outline_line(text: &str) -> (Vec<usize>, Option<(usize, String)>) {
+    let chars: Vec<char> = text.chars().collect();
+    let mut at = chars.iter().take_while(|x| **x == ' ').count();
+    let mut items = Vec::new();
+    while chars.get(at) == Some(&'-') && chars.get(at + 1).is_none_or(|x| *x == ' ') {
+        items.push(at);
+        at += 1;
+        while chars.get(at) == Some(&' ') {
+            at += 1;
+        }
+    }
+
+    let rest: String = chars[at.min(chars.len())..].iter().collect();
+    if rest.starts_with(['#', '{', '[', '"', '\'', '|', '>']) {
+        return (items, None);
+    }
+    let key = rest.find(':').and_then(|i| {
+        let after = &rest[i + 1..];
+        if after.is_empty() || after.starts_with(' ') {
+            Some(rest[..i].trim_end().to_string())
+        } else {
+            None
+        }
+    });
+    (items, key.filter(|x| !x.is_empty()).map(|x| (at, x)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{OpenDocument, Span};
+    use crate::node::Severity;
+
+    const SRC: &str = "---
+width: 100
+height: 100
+themes:
+  light: {primary: '#336699'}
+layers:
+  - circle:
+      x: 10
+      y: 10
+      radius: 5
+      color: '@primary'
+      stroke:
+        cap: round
+  - rectangle:
+      x: 1
+      colr: red
+";
+
+    fn labels(subject: &OpenDocument, line: usize, col: usize) -> Vec<String> {
+        subject
+            .completions(line, col)
+            .into_iter()
+            .map(|x| x.label)
+            .collect()
+    }
+
+    #[test]
+    fn problems() {
+        let subject = OpenDocument::new("main.yml", SRC);
+        let problems = subject.problems();
+        assert_eq!(problems.len(), 2);
+        assert_eq!(problems[0].severity, Severity::Warning);
+        assert_eq!(
+            problems[0].message,
+            "'rectangle' has no option 'colr', did you mean 'color'?"
+        );
+        assert_eq!(
+            problems[0].span,
+            Span {
+                line: 16,
+                col: 7,
+                end: 11
+            }
+        );
+        assert_eq!(problems[1].severity, Severity::Error);
+        assert_eq!(problems[1].span.line, 14);
+        assert_eq!(problems[1].message, "'rectangle' is required 'y' option");
+    }
+
+    #[test]
+    fn parse_error() {
+        let subject = OpenDocument::new("main.yml", "---\nwidth: [1\n");
+        let problems = subject.problems();
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].span.line, 3);
+        assert!(problems[0].message.starts_with("invalid yaml format!"));
+    }
+
+    #[test]
+    fn completions() {
+        let src = format!("{}  - \n  - text:\n      x: 1\n      \n", SRC);
+        let subject = OpenDocument::new("main.yml", &src);
+        // A new layer.
+        assert_eq!(labels(&subject, 17, 5)[0], "rectangle");
+        // Options of a text layer, without those written.
+        let options = labels(&subject, 20, 7);
+        assert_eq!(options[0], "class");
+        assert!(!options.contains(&"x".to_string()));
+        assert!(options.contains(&"spans".to_string()));
+        // Options of a stroke and the values of its cap.
+        assert_eq!(labels(&subject, 13, 9), vec!["width", "cap"]);
+        assert_eq!(labels(&subject, 13, 13), vec!["butt", "round", "square"]);
+        // Root keys.
+        assert!(labels(&subject, 2, 1).contains(&"layers".to_string()));
+    }
+
+    #[test]
+    fn hover() {
+        let subject = OpenDocument::new("main.yml", SRC);
+        assert!(subject
+            .hover(7, 6)
+            .unwrap()
+            .starts_with("**circle** layer."));
+        assert_eq!(
+            subject.hover(13, 10).unwrap(),
+            "**cap**: one of butt, round, square.\n\nDefault: `butt`"
+        );
+        assert_eq!(subject.hover(13, 14), None);
+        assert_eq!(subject.hover(16, 8), None);
+    }
+
+    #[test]
+    fn swatches() {
+        let subject = OpenDocument::new("main.yml", SRC);
+        let swatches = subject.swatches();
+        assert_eq!(swatches.len(), 2);
+        assert_eq!(
+            (swatches[0].r, swatches[0].g, swatches[0].b),
+            (0x33, 0x66, 0x99)
+        );
+        assert_eq!(
+            swatches[0].span,
+            Span {
+                line: 5,
+                col: 20,
+                end: 29
+            }
+        );
+        // `@primary` is shown with the light theme.
+        assert_eq!(swatches[1].span.line, 11);
+        assert_eq!(swatches[1].b, 0x99);
+
+        let subject = OpenDocument::new("main.yml", "color: rgba(255, 0, 0, 0.5)\n");
+        let swatches = subject.swatches();
+        assert_eq!(swatches[0].span.end, 28);
+        assert_eq!((swatches[0].r, swatches[0].a), (255, 0.5));
+    }
+}
@@ -0,0 +1,365 @@
+use std::fmt;
+
+use yaml_rust::Yaml;
+
+use crate::expr::scope::Scope;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Number(f64),
+    Str(String),
+}
+
+impl Value {
+    /// Converts an already resolved scalar without evaluating it again.
+    /// Booleans become the strings "true" and "false".
+    pub fn from_yaml(src: &Yaml) -> Option<Value> {
+        match src {
+            Yaml::Integer(x) => Some(Value::Number(*x as f64)),
+            Yaml::Real(_) => src.as_f64().map(Value::Number),
+            Yaml::String(x) => Some(Value::Str(x.clone())),
+            Yaml::Boolean(x) => Some(Value::Str(x.to_string())),
+            _ => None,
+        }
+    }
+
+    /// Converts untyped text such as a CSV field or a command-line value.
+    /// Numbers such as "2.50" or "1e3" become numbers; values with leading
+    /// zeros such as "007" keep their spelling as strings.
+    pub fn parse(src: &str) -> Value {
+        let digits = src.trim_start_matches(['+', '-']);
+        let padded = digits.len() > 1
+            && digits.starts_with('0')
+            && digits[1..].starts_with(|c: char| c.is_ascii_digit());
+        match src.parse::<f64>() {
+            Ok(x) if x.is_finite() && !padded => Value::Number(x),
+            _ => Value::Str(src.to_string()),
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Number(x) => Some(*x),
+            Value::Str(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Number(x) => f.write_fmt(format_args!("{}", x)),
+            Value::Str(x) => f.write_str(x),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Op(char),
+}
+
+/// Evaluates an arithmetic expression such as `$width / 2 - sin(30) * r`.
+/// Variables may be written with or without a leading `$`.
+pub fn eval(src: &str, scope: &Scope) -> Result<Value, String> {
+    let tokens = tokenize(src)?;
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        scope,
+    };
+    let value = parser.expr()?;
+    match parser.peek() {
+        None => Ok(value),
+        Some(x) => Err(format!("unexpected token '{}'", token_str(x))),
+    }
+}
+
+fn token_str(token: &Token) -> String {
+    match token {
+        Token::Number(x) => x.to_string(),
+        Token::Ident(x) => x.clone(),
+        Token::Op(x) => x.to_string(),
+    }
+}
+
+fn tokenize(src: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = src.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || c == '.' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let s: String = chars[start..i].iter().collect();
+            let n = s
+                .parse::<f64>()
+                .map_err(|_| format!("invalid number '{}'", s))?;
+            tokens.push(Token::Number(n));
+        } else if c == '$' || c.is_alphabetic() || c == '_' {
+            let start = if c == '$' { i + 1 } else { i };
+            i = start;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            if i == start {
+                return Err("'$' must be followed by a name".to_string());
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else if "+-*/%(),".contains(c) {
+            tokens.push(Token::Op(c));
+            i += 1;
+        } else {
+            return Err(format!("unexpected character '{}'", c));
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    pos: usize,
+    scope: &'a Scope,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn expect(&mut self, op: char) -> Result<(), String> {
+        match self.next() {
+            Some(Token::Op(x)) if x == op => Ok(()),
+            Some(x) => Err(format!("expected '{}' but found '{}'", op, token_str(&x))),
+            None => Err(format!("expected '{}'", op)),
+        }
+    }
+
+    // expr := term (('+' | '-') term)*
+    fn expr(&mut self) -> Result<Value, String> {
+        let mut lhs = self.term()?;
+        while let Some(Token::Op(op)) = self.peek() {
+            let op = *op;
+            if op != '+' && op != '-' {
+                break;
+            }
+            self.pos += 1;
+            let rhs = self.term()?;
+            lhs = match (op, lhs, rhs) {
+                ('+', Value::Number(a), Value::Number(b)) => Value::Number(a + b),
+                ('-', Value::Number(a), Value::Number(b)) => Value::Number(a - b),
+                ('+', a, b) => Value::Str(format!("{}{}", a, b)),
+                _ => return Err("cannot subtract from a string".to_string()),
+            };
+        }
+        Ok(lhs)
+    }
+
+    // term := unary (('*' | '/' | '%') unary)*
+    fn term(&mut self) -> Result<Value, String> {
+        let mut lhs = self.unary()?;
+        while let Some(Token::Op(op)) = self.peek() {
+            let op = *op;
+            if op != '*' && op != '/' && op != '%' {
+                break;
+            }
+            self.pos += 1;
+            let a = Self::number(lhs)?;
+            let b = Self::number(self.unary()?)?;
+            lhs = Value::Number(match op {
+                '*' => a * b,
+                '/' => a / b,
+                _ => a % b,
+            });
+        }
+        Ok(lhs)
+    }
+
+    // unary := '-' unary | primary
+    fn unary(&mut self) -> Result<Value, String> {
+        if let Some(Token::Op('-')) = self.peek() {
+            self.pos += 1;
+            return Ok(Value::Number(-Self::number(self.unary()?)?));
+        }
+        self.primary()
+    }
+
+    // primary := number | name | name '(' args ')' | '(' expr ')'
+    fn primary(&mut self) -> Result<Value, String> {
+        match self.next() {
+            Some(Token::Number(x)) => Ok(Value::Number(x)),
+            Some(Token::Op('(')) => {
+                let value = self.expr()?;
+                self.expect(')')?;
+                Ok(value)
+            }
+            Some(Token::Ident(name)) => {
+                if let Some(Token::Op('(')) = self.peek() {
+                    self.pos += 1;
+                    let mut args = Vec::new();
+                    if let Some(Token::Op(')')) = self.peek() {
+                        self.pos += 1;
+                    } else {
+                        loop {
+                            args.push(Self::number(self.expr()?)?);
+                            match self.next() {
+                                Some(Token::Op(',')) => continue,
+                                Some(Token::Op(')')) => break,
+                                _ => return Err(format!("unclosed call to '{}'", name)),
+                            }
+                        }
+                    }
+                    return call(name.as_str(), &args).map(Value::Number);
+                }
+                self.scope
+                    .get(name.as_str())
+                    .cloned()
+                    .ok_or_else(|| format!("undefined variable '{}'", name))
+            }
+            Some(x) => Err(format!("unexpected token '{}'", token_str(&x))),
+            None => Err("unexpected end of expression".to_string()),
+        }
+    }
+
+    fn number(value: Value) -> Result<f64, String> {
+        value
+            .as_f64()
+            .ok_or_else(|| format!("'{}' is not a number", value))
+    }
+}
+
+// Trigonometric functions take degrees, like the angle options of layers.
+fn call(name: &str, args: &[f64]) -> Result<f64, String> {
+    let arity = |n: usize| -> Result<(), String> {
+        if args.len() == n {
+            Ok(())
+        } else {
+            Err(format!("'{}' takes {} argument(s)", name, n))
+        }
+    };
+    match name {
+        "sin" => arity(1).map(|_| args[0].to_radians().sin()),
+        "cos" => arity(1).map(|_| args[0].to_radians().cos()),
+        "tan" => arity(1).map(|_| args[0].to_radians().tan()),
+        "sqrt" => arity(1).map(|_| args[0].sqrt()),
+        "abs" => arity(1).map(|_| args[0].abs()),
+        "floor" => arity(1).map(|_| args[0].floor()),
+        "ceil" => arity(1).map(|_| args[0].ceil()),
+        "round" => arity(1).map(|_| args[0].round()),
+        "min" => arity(2).map(|_| args[0].min(args[1])),
+        "max" => arity(2).map(|_| args[0].max(args[1])),
+        _ => Err(format!("unknown function '{}'", name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{eval, Value};
+    use crate::expr::Scope;
+    use float_cmp::approx_eq;
+
+    fn number(src: &str) -> f64 {
+        let mut scope = Scope::new();
+        scope.set("width", Value::Number(400.0));
+        scope.set("r", Value::Number(10.0));
+        match eval(src, &scope) {
+            Ok(Value::Number(x)) => x,
+            Ok(x) => panic!("not a number: {}", x),
+            Err(e) => panic!(e),
+        }
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert!(approx_eq!(f64, number("1 + 2 * 3"), 7.0));
+        assert!(approx_eq!(f64, number("(1 + 2) * 3"), 9.0));
+        assert!(approx_eq!(f64, number("-2 - -3"), 1.0));
+        assert!(approx_eq!(f64, number("7 % 4 / 2"), 1.5));
+    }
+
+    #[test]
+    fn variables() {
+        assert!(approx_eq!(f64, number("$width / 2 - 20"), 180.0));
+        assert!(approx_eq!(f64, number("r * 1.5"), 15.0));
+    }
+
+    #[test]
+    fn functions() {
+        assert!(approx_eq!(f64, number("sin(30)"), 0.5, epsilon = 1e-9));
+        assert!(approx_eq!(f64, number("cos(60) * $r"), 5.0, epsilon = 1e-9));
+        assert!(approx_eq!(f64, number("max(r, 20) + min(1, 2)"), 21.0));
+        assert!(approx_eq!(
+            f64,
+            number("sqrt(16) + abs(-1) + round(1.6)"),
+            7.0
+        ));
+    }
+
+    #[test]
+    fn parse_text() {
+        assert_eq!(Value::parse("12"), Value::Number(12.0));
+        assert_eq!(Value::parse("-1.5"), Value::Number(-1.5));
+        assert_eq!(Value::parse("2.50"), Value::Number(2.5));
+        assert_eq!(Value::parse("1e3"), Value::Number(1000.0));
+        assert_eq!(Value::parse("+5"), Value::Number(5.0));
+        assert_eq!(Value::parse("0.5"), Value::Number(0.5));
+        assert_eq!(Value::parse("007"), Value::Str("007".to_string()));
+        assert_eq!(Value::parse("-007"), Value::Str("-007".to_string()));
+        assert_eq!(Value::parse("1.0.3"), Value::Str("1.0.3".to_string()));
+        assert_eq!(Value::parse("inf"), Value::Str("inf".to_string()));
+    }
+
+    #[test]
+    fn parsed_text_in_arithmetic() {
+        let mut scope = Scope::new();
+        scope.set("r", Value::parse("2.50"));
+        scope.set("n", Value::parse("1e3"));
+        assert_eq!(eval("$r * 2", &scope), Ok(Value::Number(5.0)));
+        assert_eq!(eval("$n + 1", &scope), Ok(Value::Number(1001.0)));
+    }
+
+    #[test]
+    fn concatenation() {
+        let mut scope = Scope::new();
+        scope.set("name", Value::Str("claris".to_string()));
+        let subject = eval("$name + 1", &scope).unwrap();
+        assert_eq!(subject, Value::Str("claris1".to_string()));
+    }
+
+    #[test]
+    #[should_panic(expected = "undefined variable 'x'")]
+    fn undefined_variable() {
+        number("$x + 1");
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown function 'foo'")]
+    fn unknown_function() {
+        number("foo(1)");
+    }
+
+    #[test]
+    #[should_panic(expected = "expected ')'")]
+    fn unclosed_paren() {
+        number("(1 + 2");
+    }
+
+    #[test]
+    #[should_panic(expected = "unexpected character '#'")]
+    fn invalid_character() {
+        number("1 # 2");
+    }
+}
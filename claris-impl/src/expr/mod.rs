@@ -0,0 +1,5 @@
+mod eval;
+mod scope;
+
+pub use eval::Value;
+pub use scope::Scope;
@@ -0,0 +1,407 @@
+use std::collections::HashMap;
+
+use yaml_rust::yaml::Hash;
+use yaml_rust::Yaml;
+
+use crate::expr::eval::{eval, Value};
+use crate::node::NodeError;
+
+/// Variables visible to the expressions of a document, the palette of its
+/// selected theme and whether the document is parsed strictly.
+#[derive(Debug, Clone, Default)]
+pub struct Scope {
+    vars: HashMap<String, Value>,
+    palette: HashMap<String, String>,
+    theme: Option<String>,
+    strict: bool,
+}
+
+impl Scope {
+    pub fn new() -> Scope {
+        Scope {
+            vars: HashMap::new(),
+            palette: HashMap::new(),
+            theme: None,
+            strict: false,
+        }
+    }
+
+    /// Selects the entry of `themes:` whose palette `@name` refers to.
+    pub fn set_theme(&mut self, name: &str) {
+        self.theme = Some(name.to_string());
+    }
+
+    pub fn theme(&self) -> Option<&str> {
+        self.theme.as_deref()
+    }
+
+    /// Makes unknown options, invalid option values and invalid colors
+    /// errors instead of being ignored.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    pub fn strict(&self) -> bool {
+        self.strict
+    }
+
+    pub fn set_color(&mut self, name: &str, color: &str) {
+        self.palette.insert(name.to_string(), color.to_string());
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.vars.get(name)
+    }
+
+    pub fn set(&mut self, name: &str, value: Value) {
+        self.vars.insert(name.to_string(), value);
+    }
+
+    /// Copies every variable of `other` over this scope.
+    pub fn merge(&mut self, other: &Scope) {
+        for (name, value) in &other.vars {
+            self.vars.insert(name.clone(), value.clone());
+        }
+        for (name, color) in &other.palette {
+            self.palette.insert(name.clone(), color.clone());
+        }
+        if other.theme.is_some() {
+            self.theme = other.theme.clone();
+        }
+        self.strict |= other.strict;
+    }
+
+    /// Evaluates each entry of a `vars:` block in order, so later entries
+    /// may refer to earlier ones.
+    pub fn define(&mut self, src: &Hash) -> Result<(), NodeError> {
+        self.define_entries(src, true)
+    }
+
+    /// Like `define`, but keeps the variables that are already set, so
+    /// values given from outside the document win over its defaults.
+    pub fn define_defaults(&mut self, src: &Hash) -> Result<(), NodeError> {
+        self.define_entries(src, false)
+    }
+
+    fn define_entries(&mut self, src: &Hash, overwrite: bool) -> Result<(), NodeError> {
+        for (key, value) in src {
+            let name = key.as_str().ok_or(NodeError::InvalidVariable)?;
+            if !overwrite && self.vars.contains_key(name) {
+                continue;
+            }
+            let value = self.value(value)?;
+            self.set(name, value);
+        }
+        Ok(())
+    }
+
+    /// Converts a scalar into a value, evaluating it when it is an expression.
+    pub fn value(&self, src: &Yaml) -> Result<Value, NodeError> {
+        match src {
+            Yaml::String(x) => self.string(x),
+            _ => Value::from_yaml(src).ok_or(NodeError::InvalidVariable),
+        }
+    }
+
+    /// Returns a copy of `src` where every expression string is replaced by
+    /// its value.
+    pub fn resolve(&self, src: &Yaml) -> Result<Yaml, NodeError> {
+        match src {
+            Yaml::Hash(x) => {
+                let mut ret = Hash::new();
+                for (key, value) in x {
+                    ret.insert(key.clone(), self.resolve(value)?);
+                }
+                Ok(Yaml::Hash(ret))
+            }
+            Yaml::Array(x) => x
+                .iter()
+                .map(|x| self.resolve(x))
+                .collect::<Result<Vec<Yaml>, NodeError>>()
+                .map(Yaml::Array),
+            Yaml::String(x) => self.string(x).map(to_yaml),
+            _ => Ok(src.clone()),
+        }
+    }
+
+    // `${expr}` and `$name ...` are whole expressions and may yield numbers;
+    // `${expr}` embedded in other text is interpolated into the string. `$$`
+    // writes a literal `$`, so `$$HOME` and `$${` stay text.
+    fn string(&self, src: &str) -> Result<Value, NodeError> {
+        let trimmed = src.trim();
+        if let Some(x) = trimmed.strip_prefix('@').and_then(|x| self.palette.get(x)) {
+            return Ok(Value::Str(x.clone()));
+        }
+        if is_call(trimmed) && trimmed.contains('@') {
+            return Ok(Value::Str(self.palette_refs(trimmed)));
+        }
+        if trimmed.starts_with("${") && trimmed.ends_with('}') && trimmed.matches("${").count() == 1
+        {
+            return self.eval(src, &trimmed[2..trimmed.len() - 1]);
+        }
+        if is_reference(trimmed) {
+            return self.eval(src, trimmed);
+        }
+        self.interpolate(src).map(Value::Str)
+    }
+
+    /// Returns `src` with each `${expr}` replaced by its value and each `$$`
+    /// by `$`. Free text is only resolved this way, so `$word` and `@name`
+    /// in it stay as written.
+    pub fn interpolate(&self, src: &str) -> Result<String, NodeError> {
+        if !src.contains("${") && !src.contains("$$") {
+            return Ok(src.to_string());
+        }
+
+        let mut ret = String::new();
+        let mut rest = src;
+        while let Some(start) = rest.find('$') {
+            ret.push_str(&rest[..start]);
+            rest = &rest[start..];
+            if let Some(x) = rest.strip_prefix("$$") {
+                ret.push('$');
+                rest = x;
+            } else if rest.starts_with("${") {
+                let end = rest.find('}').ok_or_else(|| {
+                    NodeError::InvalidExpression(src.to_string(), "unclosed '${'".to_string())
+                })?;
+                let value = self.eval(src, &rest[2..end])?;
+                ret.push_str(&value.to_string());
+                rest = &rest[end + 1..];
+            } else {
+                ret.push('$');
+                rest = &rest[1..];
+            }
+        }
+        ret.push_str(rest);
+        Ok(ret)
+    }
+
+    // Replaces the known `@name` arguments of a call such as
+    // `alpha(@primary, 0.5)`. Unknown names are left for the caller to
+    // report.
+    fn palette_refs(&self, src: &str) -> String {
+        let mut ret = String::new();
+        let mut rest = src;
+        while let Some(start) = rest.find('@') {
+            ret.push_str(&rest[..start]);
+            let name_len = rest[start + 1..]
+                .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-'))
+                .unwrap_or_else(|| rest.len() - start - 1);
+            let name = &rest[start + 1..start + 1 + name_len];
+            match self.palette.get(name) {
+                Some(x) => ret.push_str(x),
+                None => ret.push_str(&rest[start..start + 1 + name_len]),
+            }
+            rest = &rest[start + 1 + name_len..];
+        }
+        ret.push_str(rest);
+        ret
+    }
+
+    fn eval(&self, src: &str, expr: &str) -> Result<Value, NodeError> {
+        eval(expr, self).map_err(|e| NodeError::InvalidExpression(src.to_string(), e))
+    }
+}
+
+fn is_reference(src: &str) -> bool {
+    let mut chars = src.chars();
+    chars.next() == Some('$')
+        && chars
+            .next()
+            .map(|x| x.is_alphabetic() || x == '_')
+            .unwrap_or(false)
+}
+
+fn is_call(src: &str) -> bool {
+    match src.find('(') {
+        Some(i) => {
+            i > 0
+                && src.ends_with(')')
+                && src[..i]
+                    .chars()
+                    .all(|x| x.is_ascii_alphabetic() || x == '_')
+        }
+        None => false,
+    }
+}
+
+fn to_yaml(value: Value) -> Yaml {
+    match value {
+        Value::Number(x) if x.fract() == 0.0 && x.abs() < i64::MAX as f64 => {
+            Yaml::Integer(x as i64)
+        }
+        Value::Number(x) => Yaml::Real(x.to_string()),
+        Value::Str(x) if x == "true" || x == "false" => Yaml::Boolean(x == "true"),
+        Value::Str(x) => Yaml::String(x),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Scope;
+    use crate::expr::Value;
+    use crate::parse_yaml;
+    use yaml_rust::{Yaml, YamlLoader};
+
+    fn scope() -> Scope {
+        let mut scope = Scope::new();
+        scope.set("width", Value::Number(400.0));
+        scope.set("r", Value::Number(10.0));
+        scope.set("name", Value::Str("claris".to_string()));
+        scope
+    }
+
+    #[test]
+    fn resolve_expressions() {
+        let src = parse_yaml!(
+            "---
+x: ${width / 2 - 20}
+y: $r * 1.25
+points:
+  - [$r, '${r + 1}']
+text: Hello ${name} v${r}!
+color: '#AABBCC'
+size: 12
+"
+        );
+        let subject = scope().resolve(&src).unwrap();
+        assert_eq!(subject["x"], Yaml::Integer(180));
+        assert_eq!(subject["y"], Yaml::Real("12.5".to_string()));
+        assert_eq!(subject["points"][0][0], Yaml::Integer(10));
+        assert_eq!(subject["points"][0][1], Yaml::Integer(11));
+        assert_eq!(
+            subject["text"],
+            Yaml::String("Hello claris v10!".to_string())
+        );
+        assert_eq!(subject["color"], Yaml::String("#AABBCC".to_string()));
+        assert_eq!(subject["size"], Yaml::Integer(12));
+    }
+
+    #[test]
+    fn plain_dollar_is_text() {
+        let src = parse_yaml!("---\ntext: $5 off\n");
+        let subject = scope().resolve(&src).unwrap();
+        assert_eq!(subject["text"], Yaml::String("$5 off".to_string()));
+    }
+
+    #[test]
+    fn free_text_keeps_words() {
+        let mut subject = scope();
+        subject.set_color("primary", "#3366FF");
+        assert_eq!(subject.interpolate("$HOME is set").unwrap(), "$HOME is set");
+        assert_eq!(subject.interpolate("@primary").unwrap(), "@primary");
+        assert_eq!(
+            subject.interpolate("$name ${name}").unwrap(),
+            "$name claris"
+        );
+    }
+
+    #[test]
+    fn escaped_dollar_is_text() {
+        let src = parse_yaml!(
+            "---
+home: $$HOME price
+brace: cost $${r} is ${r}
+price: ${r}$$
+"
+        );
+        let subject = scope().resolve(&src).unwrap();
+        assert_eq!(subject["home"], Yaml::String("$HOME price".to_string()));
+        assert_eq!(
+            subject["brace"],
+            Yaml::String("cost ${r} is 10".to_string())
+        );
+        assert_eq!(subject["price"], Yaml::String("10$".to_string()));
+    }
+
+    #[test]
+    fn define_in_order() {
+        let src = parse_yaml!(
+            "---
+gap: 8
+double: $gap * 2
+label: ${name}-${double}
+"
+        );
+        let mut subject = scope();
+        subject.define(src.as_hash().unwrap()).unwrap();
+        assert_eq!(subject.get("double"), Some(&Value::Number(16.0)));
+        assert_eq!(
+            subject.get("label"),
+            Some(&Value::Str("claris-16".to_string()))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid expression '$x * 2': undefined variable 'x'")]
+    fn undefined_variable() {
+        let src = parse_yaml!("---\nx: $x * 2\n");
+        if let Err(e) = scope().resolve(&src) {
+            panic!(e.to_string())
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid variable")]
+    fn invalid_variable() {
+        let src = parse_yaml!("---\nx: [1, 2]\n");
+        if let Err(e) = scope().define(src.as_hash().unwrap()) {
+            panic!(e.to_string())
+        }
+    }
+
+    #[test]
+    fn define_defaults_keeps_existing() {
+        let src = parse_yaml!(
+            "---
+r: 1
+label: r=${r}
+"
+        );
+        let mut subject = scope();
+        subject.define_defaults(src.as_hash().unwrap()).unwrap();
+        assert_eq!(subject.get("r"), Some(&Value::Number(10.0)));
+        assert_eq!(subject.get("label"), Some(&Value::Str("r=10".to_string())));
+    }
+
+    #[test]
+    fn merge_overwrites() {
+        let mut other = Scope::new();
+        other.set("r", Value::Number(1.0));
+        other.set("g", Value::Number(2.0));
+        let mut subject = scope();
+        subject.merge(&other);
+        assert_eq!(subject.get("r"), Some(&Value::Number(1.0)));
+        assert_eq!(subject.get("g"), Some(&Value::Number(2.0)));
+        assert_eq!(subject.get("width"), Some(&Value::Number(400.0)));
+    }
+
+    #[test]
+    fn resolve_palette() {
+        let src = parse_yaml!(
+            "---
+color: '@primary'
+shade: alpha(@primary, 0.5)
+mixed: mix(@primary,@accent-2, 0.5)
+text: '@home'
+note: follow @primary
+"
+        );
+        let mut subject = scope();
+        subject.set_color("primary", "#3366FF");
+        subject.set_color("accent-2", "#FF0000");
+        let subject = subject.resolve(&src).unwrap();
+        assert_eq!(subject["color"], Yaml::String("#3366FF".to_string()));
+        assert_eq!(
+            subject["shade"],
+            Yaml::String("alpha(#3366FF, 0.5)".to_string())
+        );
+        assert_eq!(
+            subject["mixed"],
+            Yaml::String("mix(#3366FF,#FF0000, 0.5)".to_string())
+        );
+        assert_eq!(subject["text"], Yaml::String("@home".to_string()));
+        assert_eq!(subject["note"], Yaml::String("follow @primary".to_string()));
+    }
+}
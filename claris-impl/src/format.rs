@@ -0,0 +1,338 @@
+use std::fmt;
+use std::path::Path;
+
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use toml::Spanned;
+use yaml_rust::yaml::Hash;
+use yaml_rust::Yaml;
+
+use crate::source::{Entry, Mark, Segment};
+
+/// The syntax of a source document.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InputFormat {
+    Yaml,
+    Json,
+    Toml,
+}
+
+impl InputFormat {
+    /// Parses a format name as given to `--input-format`.
+    pub fn parse(name: &str) -> Option<InputFormat> {
+        match name.to_lowercase().as_str() {
+            "yaml" | "yml" => Some(InputFormat::Yaml),
+            "json" => Some(InputFormat::Json),
+            "toml" => Some(InputFormat::Toml),
+            _ => None,
+        }
+    }
+
+    /// Picks the format from the extension of `path`. Unknown extensions
+    /// are read as YAML.
+    pub fn from_path(path: &Path) -> InputFormat {
+        path.extension()
+            .and_then(|x| x.to_str())
+            .and_then(InputFormat::parse)
+            .unwrap_or(InputFormat::Yaml)
+    }
+}
+
+impl fmt::Display for InputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InputFormat::Yaml => f.write_str("yaml"),
+            InputFormat::Json => f.write_str("json"),
+            InputFormat::Toml => f.write_str("toml"),
+        }
+    }
+}
+
+/// Any serde value rebuilt as a `yaml_rust` tree. JSON and TOML sources are
+/// read through it so that they go through the same expressions, styles and
+/// includes as YAML ones, and the nodes' `Deserialize` impls read their
+/// options through it before handing them to the YAML parsers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct YamlTree(pub Yaml);
+
+impl<'de> Deserialize<'de> for YamlTree {
+    fn deserialize<D>(deserializer: D) -> Result<YamlTree, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(YamlTreeVisitor).map(YamlTree)
+    }
+}
+
+struct YamlTreeVisitor;
+
+impl<'de> Visitor<'de> for YamlTreeVisitor {
+    type Value = Yaml;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a document")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Yaml, E> {
+        Ok(Yaml::Boolean(v))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Yaml, E> {
+        Ok(Yaml::Integer(v))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Yaml, E> {
+        if v > i64::MAX as u64 {
+            return Ok(Yaml::Real(v.to_string()));
+        }
+        Ok(Yaml::Integer(v as i64))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Yaml, E> {
+        Ok(Yaml::Real(format!("{:?}", v)))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Yaml, E> {
+        Ok(Yaml::String(v.to_string()))
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Yaml, E> {
+        Ok(Yaml::Null)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Yaml, E> {
+        Ok(Yaml::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Yaml, D::Error> {
+        YamlTree::deserialize(deserializer).map(|x| x.0)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Yaml, A::Error> {
+        let mut ret = Vec::new();
+        while let Some(YamlTree(x)) = seq.next_element()? {
+            ret.push(x);
+        }
+        Ok(Yaml::Array(ret))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Yaml, A::Error> {
+        let mut ret = Hash::new();
+        while let Some((YamlTree(key), YamlTree(value))) = map.next_entry()? {
+            ret.insert(key, value);
+        }
+        Ok(Yaml::Hash(ret))
+    }
+}
+
+/// Returns where each node of the TOML `text`, loaded as file `file`, was
+/// written. Tables under a `[name]` header start at their first key.
+pub fn toml_entries(file: usize, text: &str) -> Vec<Entry> {
+    let mut ret = Vec::new();
+    // Syntax errors are reported when the document itself is read.
+    if let Ok(tree) = toml::from_str::<Spanned<Tree>>(text) {
+        let mark = |offset: usize| -> Mark {
+            let before = &text[..offset.min(text.len())];
+            let line_start = before.rfind('\n').map_or(0, |x| x + 1);
+            Mark {
+                file,
+                line: before.matches('\n').count() + 1,
+                col: before[line_start..].chars().count() + 1,
+            }
+        };
+        record(&tree, &mut Vec::new(), None, &mark, &mut ret);
+    }
+    ret
+}
+
+// A TOML value with where it and its keys were written.
+enum Tree {
+    Scalar(String),
+    Seq(Vec<Spanned<Tree>>),
+    Map(Vec<(Spanned<String>, Spanned<Tree>)>),
+}
+
+fn record(
+    tree: &Spanned<Tree>,
+    path: &mut Vec<Segment>,
+    key: Option<Mark>,
+    mark: &dyn Fn(usize) -> Mark,
+    entries: &mut Vec<Entry>,
+) {
+    // Tables and arrays of tables are given no span.
+    let start = match (tree.span(), tree.get_ref()) {
+        ((0, 0), Tree::Map(x)) => x.first().map(|(k, _)| k.start()),
+        ((start, _), _) => Some(start),
+    };
+    entries.push(Entry {
+        path: path.clone(),
+        key,
+        value: start.map(mark).or(key).unwrap_or_else(|| mark(0)),
+        scalar: match tree.get_ref() {
+            Tree::Scalar(x) => Some(x.clone()),
+            _ => None,
+        },
+    });
+    match tree.get_ref() {
+        Tree::Scalar(_) => {}
+        Tree::Seq(x) => {
+            for (i, item) in x.iter().enumerate() {
+                path.push(Segment::Index(i));
+                record(item, path, None, mark, entries);
+                path.pop();
+            }
+        }
+        Tree::Map(x) => {
+            for (k, value) in x {
+                path.push(Segment::Key(k.get_ref().clone()));
+                record(value, path, Some(mark(k.start())), mark, entries);
+                path.pop();
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Tree {
+    fn deserialize<D>(deserializer: D) -> Result<Tree, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(TreeVisitor)
+    }
+}
+
+struct TreeVisitor;
+
+impl<'de> Visitor<'de> for TreeVisitor {
+    type Value = Tree;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a TOML value")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Tree, E> {
+        Ok(Tree::Scalar(v.to_string()))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Tree, E> {
+        Ok(Tree::Scalar(v.to_string()))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Tree, E> {
+        Ok(Tree::Scalar(v.to_string()))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Tree, E> {
+        Ok(Tree::Scalar(v.to_string()))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Tree, E> {
+        Ok(Tree::Scalar(v.to_string()))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Tree, A::Error> {
+        let mut ret = Vec::new();
+        while let Some(x) = seq.next_element()? {
+            ret.push(x);
+        }
+        Ok(Tree::Seq(ret))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Tree, A::Error> {
+        let mut ret = Vec::new();
+        while let Some(x) = map.next_entry()? {
+            ret.push(x);
+        }
+        Ok(Tree::Map(ret))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{toml_entries, InputFormat, YamlTree};
+    use crate::parse_yaml;
+    use crate::source::Segment;
+    use std::path::Path;
+    use yaml_rust::YamlLoader;
+
+    const YAML: &str = "---
+width: 400
+alpha: 0.5
+fill: true
+name: ~
+layers:
+  - circle: {x: 1, y: 2, color: '#AABBCC'}
+";
+
+    #[test]
+    fn from_path() {
+        assert_eq!(
+            InputFormat::from_path(Path::new("a.json")),
+            InputFormat::Json
+        );
+        assert_eq!(
+            InputFormat::from_path(Path::new("a.TOML")),
+            InputFormat::Toml
+        );
+        assert_eq!(
+            InputFormat::from_path(Path::new("a.yaml")),
+            InputFormat::Yaml
+        );
+        assert_eq!(InputFormat::from_path(Path::new("a")), InputFormat::Yaml);
+    }
+
+    #[test]
+    fn json() {
+        let src = r##"{
+  "width": 400,
+  "alpha": 0.5,
+  "fill": true,
+  "name": null,
+  "layers": [{"circle": {"x": 1, "y": 2, "color": "#AABBCC"}}]
+}"##;
+        let subject: YamlTree = serde_json::from_str(src).unwrap();
+        assert_eq!(subject.0, parse_yaml!(YAML));
+    }
+
+    #[test]
+    fn toml() {
+        let src = r##"
+width = 400
+alpha = 0.5
+fill = true
+
+[[layers]]
+circle = { x = 1, y = 2, color = "#AABBCC" }
+"##;
+        let subject: YamlTree = toml::from_str(src).unwrap();
+        let mut expected = parse_yaml!(YAML);
+        if let yaml_rust::Yaml::Hash(x) = &mut expected {
+            x.remove(&yaml_rust::Yaml::from_str("name"));
+        }
+        assert_eq!(subject.0, expected);
+    }
+
+    #[test]
+    fn toml_positions() {
+        let src = "width = 10\n\n[[layers]]\n[layers.circle]\nx = 1\ncolor = 'red'\n";
+        let subject = toml_entries(0, src);
+        let at = |path: &[Segment]| {
+            let x = subject.iter().find(|x| x.path == path).unwrap();
+            (x.key.map(|x| (x.line, x.col)), (x.value.line, x.value.col))
+        };
+        let key = |x: &str| Segment::Key(x.to_string());
+        assert_eq!(at(&[key("width")]), (Some((1, 1)), (1, 9)));
+        assert_eq!(at(&[key("layers"), Segment::Index(0)]), (None, (4, 9)));
+        assert_eq!(
+            at(&[
+                key("layers"),
+                Segment::Index(0),
+                key("circle"),
+                key("color")
+            ]),
+            (Some((6, 1)), (6, 9))
+        );
+        let color = subject.iter().find(|x| x.scalar.as_deref() == Some("red"));
+        assert!(color.is_some());
+        assert!(toml_entries(0, "width = ").is_empty());
+    }
+}
@@ -1,11 +1,20 @@
 mod compiler;
 mod context;
+mod data;
+mod editor;
+mod expr;
 mod ext;
+mod format;
 mod loader;
 mod node;
 mod renderer;
+mod source;
 #[cfg(test)]
 mod testing_helpers;
 
 pub use compiler::Compiler;
 pub use context::{Context, ContextImpl};
+pub use editor::{OpenDocument, Problem, Span, Swatch};
+pub use expr::{Scope, Value};
+pub use format::InputFormat;
+pub use node::{Severity, Suggestion, SuggestionKind};
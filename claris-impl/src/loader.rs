@@ -1,15 +1,27 @@
 use std::fmt;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::Read;
+use std::path::{Path, PathBuf};
+use yaml_rust::yaml::Hash;
 use yaml_rust::{Yaml, YamlLoader};
 
+use crate::format::{toml_entries, InputFormat, YamlTree};
+use crate::source::{splice, Entry, Location, Segment, SourceMap};
+
 #[derive(Debug)]
 pub enum LoadError {
     OpenError(String),
     ReadError(String),
-    ParseError(String),
+    EncodingError(String, usize),
+    ParseError(Location, String),
     NoEntryError,
     TooManyEntryError,
+    UnknownDataFormatError(String),
+    InvalidDataError(String),
+    InvalidIncludeError(String),
+    IgnoredIncludeError(String, Vec<String>),
+    CyclicIncludeError(String),
+    IncludeError(String, Box<LoadError>),
 }
 
 impl fmt::Display for LoadError {
@@ -17,34 +29,781 @@ impl fmt::Display for LoadError {
         match self {
             LoadError::OpenError(x) => f.write_fmt(format_args!("file open error! path: '{}'", x)),
             LoadError::ReadError(x) => f.write_fmt(format_args!("file read error! path: '{}'", x)),
-            LoadError::ParseError(x) => {
-                f.write_fmt(format_args!("invalid yaml format! path: '{}'", x))
-            }
+            LoadError::EncodingError(x, y) => f.write_fmt(format_args!(
+                "file is not valid UTF-8 at byte {}! path: '{}'",
+                y, x
+            )),
+            LoadError::ParseError(x, e) => f.write_str(&x.render(e)),
             LoadError::NoEntryError => f.write_str("yaml has no entry!"),
             LoadError::TooManyEntryError => f.write_str("yaml has too many entry!"),
+            LoadError::UnknownDataFormatError(x) => f.write_fmt(format_args!(
+                "unknown data format! expected .csv or .json path: '{}'",
+                x
+            )),
+            LoadError::InvalidDataError(x) => {
+                f.write_fmt(format_args!("invalid data format! path: '{}'", x))
+            }
+            LoadError::InvalidIncludeError(x) => {
+                f.write_fmt(format_args!("invalid include! path: '{}'", x))
+            }
+            LoadError::IgnoredIncludeError(x, y) => f.write_fmt(format_args!(
+                "included file can't set '{}', import it instead! path: '{}'",
+                y.join("', '"),
+                x
+            )),
+            LoadError::CyclicIncludeError(x) => {
+                f.write_fmt(format_args!("cyclic include! chain: {}", x))
+            }
+            LoadError::IncludeError(x, e) => f.write_fmt(format_args!("{} included from {}", e, x)),
         }
     }
 }
 
 pub struct SourceLoader {}
 
+// Files being expanded, outermost first, as (canonical path, path as written).
+type Chain = Vec<(PathBuf, String)>;
+
+// State shared by a document and the files it includes or imports.
+struct Loading {
+    chain: Chain,
+    // Components of included files, merged under the root's own definitions.
+    defs: Hash,
+    def_entries: Vec<Entry>,
+    map: SourceMap,
+    // Unsaved text read in place of the outermost file.
+    text: Option<String>,
+}
+
 impl SourceLoader {
-    pub fn load(path: &str) -> Result<Yaml, LoadError> {
+    /// Loads `path` and splices in every `include:` layer entry and the
+    /// `defs`, `styles`, `themes` and `vars` of every `imports:` file. Paths are relative to the
+    /// file that names them. Returns the document with where each of its
+    /// nodes was written.
+    ///
+    /// `path` is read as `format`, or as its extension tells when `None`.
+    /// Included and imported files are always read as their extension tells.
+    pub fn load(path: &str, format: Option<InputFormat>) -> Result<(Yaml, SourceMap), LoadError> {
+        Self::load_with(path, format, None)
+    }
+
+    /// Like `load`, reading `text` in place of the file at `path`, which
+    /// need not exist.
+    pub fn load_text(
+        path: &str,
+        text: &str,
+        format: Option<InputFormat>,
+    ) -> Result<(Yaml, SourceMap), LoadError> {
+        Self::load_with(path, format, Some(text.to_string()))
+    }
+
+    fn load_with(
+        path: &str,
+        format: Option<InputFormat>,
+        text: Option<String>,
+    ) -> Result<(Yaml, SourceMap), LoadError> {
+        let mut state = Loading {
+            chain: Vec::new(),
+            defs: Hash::new(),
+            def_entries: Vec::new(),
+            map: SourceMap::new(),
+            text,
+        };
+        let (mut doc, entries) = Self::load_file(Path::new(path), format, &mut state)?;
+        let mut map = state.map;
+        map.entries = entries;
+
+        if let Yaml::Hash(root) = &mut doc {
+            let key = Yaml::from_str("defs");
+            let own = match root.get(&key) {
+                Some(Yaml::Hash(x)) => x.clone(),
+                _ => Hash::new(),
+            };
+            let mut defs = state.defs;
+            for (name, _) in defs.iter() {
+                if !own.contains_key(name) {
+                    let path = vec![Self::key(&key), Self::key(name)];
+                    splice(&mut map.entries, &state.def_entries, &path, &path);
+                }
+            }
+            defs.extend(own);
+            if !defs.is_empty() {
+                root.insert(key, Yaml::Hash(defs));
+            }
+        }
+
+        Ok((doc, map))
+    }
+
+    /// Reads `path` as a single YAML document, without following its
+    /// `include:` and `imports:`. Returns it with its text and where each of
+    /// its nodes was written.
+    pub fn load_source(path: &str) -> Result<(Yaml, String, Vec<Entry>), LoadError> {
+        let data = Self::read(path)?;
+        let (doc, entries) =
+            Self::parse_document(path, &data, InputFormat::Yaml, &mut SourceMap::new())?;
+        Ok((doc, data, entries))
+    }
+
+    fn read(path: &str) -> Result<String, LoadError> {
         let mut file = File::open(path).map_err(|_| LoadError::OpenError(path.to_string()))?;
-        let mut data = String::new();
-        file.read_to_string(&mut data)
-            .or_else(|_| Err(LoadError::ReadError(path.to_string())))?;
-        let docs = YamlLoader::load_from_str(&data)
-            .map_err(|_| LoadError::ParseError(path.to_string()))?;
+        let mut data = Vec::new();
+        file.read_to_end(&mut data)
+            .map_err(|_| LoadError::ReadError(path.to_string()))?;
+        String::from_utf8(data)
+            .map_err(|e| LoadError::EncodingError(path.to_string(), e.utf8_error().valid_up_to()))
+    }
+
+    fn load_document(
+        path: &str,
+        format: InputFormat,
+        map: &mut SourceMap,
+    ) -> Result<(Yaml, Vec<Entry>), LoadError> {
+        let data = Self::read(path)?;
+        Self::parse_document(path, &data, format, map)
+    }
+
+    fn parse_document(
+        path: &str,
+        data: &str,
+        format: InputFormat,
+        map: &mut SourceMap,
+    ) -> Result<(Yaml, Vec<Entry>), LoadError> {
+        // Errors end with their position, which is shown separately.
+        let parse_error = |line: usize, col: usize, message: String| {
+            let location = Location::new(path, data, line, col);
+            let info = message.rsplitn(2, " at line ").last().unwrap_or("");
+            LoadError::ParseError(location, format!("invalid {} format! {}", format, info))
+        };
+
+        let doc = match format {
+            InputFormat::Yaml => {
+                let docs = YamlLoader::load_from_str(data).map_err(|e| {
+                    let marker = e.marker();
+                    parse_error(marker.line(), marker.col() + 1, e.to_string())
+                })?;
+
+                if docs.is_empty() {
+                    return Err(LoadError::NoEntryError);
+                } else if docs.len() > 1 {
+                    return Err(LoadError::TooManyEntryError);
+                }
 
-        if docs.is_empty() {
-            return Err(LoadError::NoEntryError);
-        } else if docs.len() > 1 {
-            return Err(LoadError::TooManyEntryError);
+                docs[0].clone()
+            }
+            InputFormat::Json => {
+                serde_json::from_str::<YamlTree>(data)
+                    // Errors at the end of the input are at column 0.
+                    .map_err(|e| parse_error(e.line(), e.column().max(1), e.to_string()))?
+                    .0
+            }
+            InputFormat::Toml => {
+                toml::from_str::<YamlTree>(data)
+                    .map_err(|e| {
+                        let (line, col) = e.line_col().unwrap_or((0, 0));
+                        parse_error(line + 1, col + 1, e.to_string())
+                    })?
+                    .0
+            }
+        };
+
+        // JSON is read as YAML for positions.
+        let entries = match format {
+            InputFormat::Toml => toml_entries(map.add_text(path, data), data),
+            _ => map.add_file(path, data),
+        };
+        Ok((doc, entries))
+    }
+
+    // Returns the expanded document with the positions of its nodes.
+    fn load_file(
+        path: &Path,
+        format: Option<InputFormat>,
+        state: &mut Loading,
+    ) -> Result<(Yaml, Vec<Entry>), LoadError> {
+        let name = path.to_string_lossy().to_string();
+        let text = state.text.take();
+        let canonical = match fs::canonicalize(path) {
+            Ok(x) => x,
+            Err(_) if text.is_some() => path.to_path_buf(),
+            Err(_) => {
+                let e = LoadError::OpenError(name.clone());
+                return Err(Self::included_from(e, &state.chain));
+            }
+        };
+        if state.chain.iter().any(|(x, _)| *x == canonical) {
+            let mut names: Vec<&str> = state.chain.iter().map(|(_, x)| x.as_str()).collect();
+            names.push(name.as_str());
+            return Err(LoadError::CyclicIncludeError(names.join(" -> ")));
+        }
+
+        state.chain.push((canonical, name.clone()));
+        let format = format.unwrap_or_else(|| InputFormat::from_path(path));
+        let document = match &text {
+            Some(x) => Self::parse_document(&name, x, format, &mut state.map),
+            None => Self::load_document(&name, format, &mut state.map),
+        };
+        let ret = document.and_then(|(mut doc, raw)| {
+            let dir = path.parent().unwrap_or_else(|| Path::new(""));
+            let mut entries = Vec::new();
+            if let Yaml::Hash(x) = &mut doc {
+                Self::import(x, dir, state, &mut entries)?;
+            }
+            let mut at = Vec::new();
+            let doc = Self::expand(
+                doc,
+                dir,
+                state,
+                (&raw, &mut entries),
+                &mut at,
+                &mut Vec::new(),
+            )?;
+            Ok((doc, entries))
+        });
+        state.chain.pop();
+
+        ret.map_err(|e| Self::included_from(e, &state.chain))
+    }
+
+    fn included_from(e: LoadError, chain: &Chain) -> LoadError {
+        match e {
+            LoadError::CyclicIncludeError(_) | LoadError::IncludeError(_, _) => e,
+            _ if chain.is_empty() => e,
+            _ => {
+                let names: Vec<&str> = chain.iter().map(|(_, x)| x.as_str()).collect();
+                LoadError::IncludeError(names.join(" -> "), Box::new(e))
+            }
+        }
+    }
+
+    fn key(src: &Yaml) -> Segment {
+        Segment::Key(src.as_str().unwrap_or("").to_string())
+    }
+
+    fn import(
+        src: &mut Hash,
+        dir: &Path,
+        state: &mut Loading,
+        entries: &mut Vec<Entry>,
+    ) -> Result<(), LoadError> {
+        let imports = match src.remove(&Yaml::from_str("imports")) {
+            Some(Yaml::Array(x)) => x,
+            Some(_) => return Err(LoadError::InvalidIncludeError("imports".to_string())),
+            None => return Ok(()),
+        };
+
+        let mut vars = Hash::new();
+        let mut styles = Hash::new();
+        let mut themes = Hash::new();
+        for import in imports {
+            let path = dir.join(Self::include_path(&import)?);
+            if let (Yaml::Hash(x), imported) = Self::load_file(&path, None, state)? {
+                for (name, merged) in [
+                    ("defs", None),
+                    ("vars", Some(&mut vars)),
+                    ("styles", Some(&mut styles)),
+                    ("themes", Some(&mut themes)),
+                ] {
+                    let key = Yaml::from_str(name);
+                    if let Some(Yaml::Hash(x)) = x.get(&key) {
+                        let target = match merged {
+                            Some(merged) => {
+                                merged.extend(x.clone());
+                                &mut *entries
+                            }
+                            None => {
+                                state.defs.extend(x.clone());
+                                &mut state.def_entries
+                            }
+                        };
+                        for (k, _) in x {
+                            let path = vec![Self::key(&key), Self::key(k)];
+                            splice(target, &imported, &path, &path);
+                        }
+                    }
+                }
+            }
+        }
+
+        Self::merge_imported(src, "vars", vars);
+        Self::merge_imported(src, "styles", styles);
+        Self::merge_imported(src, "themes", themes);
+        Ok(())
+    }
+
+    // Entries of the importing file override imported ones.
+    fn merge_imported(src: &mut Hash, name: &str, imported: Hash) {
+        let key = Yaml::from_str(name);
+        let mut merged = imported;
+        if let Some(Yaml::Hash(x)) = src.get(&key) {
+            merged.extend(x.clone());
+        }
+        if !merged.is_empty() {
+            src.insert(key, Yaml::Hash(merged));
         }
+    }
+
+    // Replaces each `- include: path` item of any list with the layers of
+    // that file. `from` and `to` are the paths of `src` in the file and in
+    // the expanded document.
+    fn expand(
+        src: Yaml,
+        dir: &Path,
+        state: &mut Loading,
+        entries: (&[Entry], &mut Vec<Entry>),
+        from: &mut Vec<Segment>,
+        to: &mut Vec<Segment>,
+    ) -> Result<Yaml, LoadError> {
+        let (raw, out) = entries;
+        out.retain(|x| x.path != *to);
+        if let Some(x) = raw.iter().find(|x| x.path == *from) {
+            out.push(Entry {
+                path: to.clone(),
+                ..x.clone()
+            });
+        }
+
+        match src {
+            Yaml::Array(items) => {
+                let mut ret = Vec::new();
+                for (i, item) in items.into_iter().enumerate() {
+                    match Self::include_entry(&item) {
+                        Some(x) => {
+                            let path = dir.join(Self::include_path(x)?);
+                            let (doc, included) = Self::load_file(&path, None, state)?;
+                            let (prefix, layers) =
+                                Self::included_layers(doc, &path, state, &included)?;
+                            for (j, layer) in layers.into_iter().enumerate() {
+                                let mut src = prefix.clone();
+                                src.push(Segment::Index(j));
+                                to.push(Segment::Index(ret.len()));
+                                splice(out, &included, &src, to);
+                                to.pop();
+                                ret.push(layer);
+                            }
+                        }
+                        None => {
+                            from.push(Segment::Index(i));
+                            to.push(Segment::Index(ret.len()));
+                            let item = Self::expand(item, dir, state, (raw, &mut *out), from, to);
+                            from.pop();
+                            to.pop();
+                            ret.push(item?);
+                        }
+                    }
+                }
+                Ok(Yaml::Array(ret))
+            }
+            Yaml::Hash(x) => {
+                let mut ret = Hash::new();
+                for (key, value) in x {
+                    from.push(Self::key(&key));
+                    to.push(Self::key(&key));
+                    let value = Self::expand(value, dir, state, (raw, &mut *out), from, to);
+                    from.pop();
+                    to.pop();
+                    ret.insert(key, value?);
+                }
+                Ok(Yaml::Hash(ret))
+            }
+            _ => Ok(src),
+        }
+    }
+
+    fn include_entry(src: &Yaml) -> Option<&Yaml> {
+        match src {
+            Yaml::Hash(x) if x.len() == 1 => x.get(&Yaml::from_str("include")),
+            _ => None,
+        }
+    }
+
+    fn include_path(src: &Yaml) -> Result<&str, LoadError> {
+        src.as_str()
+            .ok_or_else(|| LoadError::InvalidIncludeError(format!("{:?}", src)))
+    }
+
+    // An included file is either a list of layers or a document with
+    // `layers` and optional `defs`; its `vars`, `styles` and `themes` would
+    // be lost, so they are refused. Returns the layers with the path of the
+    // list in the included file.
+    fn included_layers(
+        src: Yaml,
+        path: &Path,
+        state: &mut Loading,
+        entries: &[Entry],
+    ) -> Result<(Vec<Segment>, Vec<Yaml>), LoadError> {
+        match src {
+            Yaml::Array(x) => Ok((Vec::new(), x)),
+            Yaml::Hash(mut x) => {
+                let ignored: Vec<String> = ["vars", "styles", "themes"]
+                    .iter()
+                    .filter(|k| x.contains_key(&Yaml::from_str(k)))
+                    .map(|k| k.to_string())
+                    .collect();
+                if !ignored.is_empty() {
+                    return Err(LoadError::IgnoredIncludeError(
+                        path.to_string_lossy().to_string(),
+                        ignored,
+                    ));
+                }
+                if let Some(Yaml::Hash(d)) = x.get(&Yaml::from_str("defs")) {
+                    for (k, _) in d {
+                        let path = vec![Segment::Key("defs".to_string()), Self::key(k)];
+                        splice(&mut state.def_entries, entries, &path, &path);
+                    }
+                    state.defs.extend(d.clone());
+                }
+                match x.remove(&Yaml::from_str("layers")) {
+                    Some(Yaml::Array(layers)) => {
+                        Ok((vec![Segment::Key("layers".to_string())], layers))
+                    }
+                    _ => Err(LoadError::InvalidIncludeError(
+                        path.to_string_lossy().to_string(),
+                    )),
+                }
+            }
+            _ => Err(LoadError::InvalidIncludeError(
+                path.to_string_lossy().to_string(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SourceLoader;
+    use crate::format::InputFormat;
+    use crate::source::Segment;
+    use std::fs::File;
+    use std::io::Write;
+    use std::path::Path;
+    use tempfile::{tempdir, TempDir};
+
+    fn write(dir: &TempDir, name: &str, src: &str) -> String {
+        let path = dir.path().join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        File::create(&path)
+            .unwrap()
+            .write_all(src.as_bytes())
+            .unwrap();
+        path.to_string_lossy().to_string()
+    }
+
+    fn load(path: &str) -> yaml_rust::Yaml {
+        match SourceLoader::load(path, None) {
+            Ok((x, _)) => x,
+            Err(e) => panic!(e.to_string()),
+        }
+    }
+
+    #[test]
+    fn include_layers() {
+        let dir = tempdir().unwrap();
+        write(
+            &dir,
+            "parts/header.yml",
+            "---
+defs:
+  dot:
+    layers: []
+layers:
+  - circle: {x: 1, y: 1, radius: 1, color: '#AABBCC'}
+  - include: footer.yml
+",
+        );
+        write(
+            &dir,
+            "parts/footer.yml",
+            "---
+- line: {color: '#AABBCC', points: [[0, 0]]}
+",
+        );
+        let path = write(
+            &dir,
+            "main.yml",
+            "---
+width: 10
+height: 10
+layers:
+  - rectangle: {x: 0, y: 0, width: 1, height: 1, color: '#AABBCC'}
+  - include: parts/header.yml
+",
+        );
+        let subject = load(&path);
+        let layers = subject["layers"].as_vec().unwrap();
+        assert_eq!(layers.len(), 3);
+        assert!(!layers[1]["circle"].is_badvalue());
+        assert!(!layers[2]["line"].is_badvalue());
+        assert!(!subject["defs"]["dot"].is_badvalue());
+    }
+
+    #[test]
+    fn import_defs_and_vars() {
+        let dir = tempdir().unwrap();
+        write(
+            &dir,
+            "lib.yml",
+            "---
+vars:
+  r: 1
+  g: 2
+defs:
+  dot:
+    layers: []
+  bar:
+    layers: []
+styles:
+  primary:
+    color: red
+",
+        );
+        let path = write(
+            &dir,
+            "main.yml",
+            "---
+imports:
+  - lib.yml
+vars:
+  r: 10
+defs:
+  bar:
+    params: {a: 1}
+    layers: []
+layers: []
+",
+        );
+        let subject = load(&path);
+        assert!(subject["imports"].is_badvalue());
+        assert_eq!(subject["vars"]["r"].as_i64(), Some(10));
+        assert_eq!(subject["vars"]["g"].as_i64(), Some(2));
+        assert!(!subject["defs"]["dot"].is_badvalue());
+        assert!(!subject["defs"]["bar"]["params"].is_badvalue());
+        assert!(!subject["styles"]["primary"].is_badvalue());
+    }
+
+    #[test]
+    fn include_in_nested_layers() {
+        let dir = tempdir().unwrap();
+        write(
+            &dir,
+            "dot.yml",
+            "---\n- circle: {x: 1, y: 1, radius: 1, color: red}\n",
+        );
+        let path = write(
+            &dir,
+            "main.yml",
+            "---
+layers:
+  - repeat:
+      count: 2
+      layers:
+        - include: dot.yml
+",
+        );
+        let subject = load(&path);
+        let layers = subject["layers"][0]["repeat"]["layers"].as_vec().unwrap();
+        assert!(!layers[0]["circle"].is_badvalue());
+    }
+
+    #[test]
+    fn cyclic_include() {
+        let dir = tempdir().unwrap();
+        write(&dir, "a.yml", "---\nlayers:\n  - include: b.yml\n");
+        write(&dir, "b.yml", "---\nlayers:\n  - include: a.yml\n");
+        let path = dir.path().join("a.yml");
+        let subject = SourceLoader::load(path.to_str().unwrap(), None)
+            .unwrap_err()
+            .to_string();
+        let a = Path::new(&path).to_string_lossy().to_string();
+        let b = dir.path().join("b.yml").to_string_lossy().to_string();
+        assert_eq!(
+            subject,
+            format!("cyclic include! chain: {} -> {} -> {}", a, b, a)
+        );
+    }
+
+    #[test]
+    fn missing_include_reports_chain() {
+        let dir = tempdir().unwrap();
+        write(&dir, "a.yml", "---\nlayers:\n  - include: b.yml\n");
+        write(&dir, "b.yml", "---\nlayers:\n  - include: c.yml\n");
+        let a = dir.path().join("a.yml").to_string_lossy().to_string();
+        let b = dir.path().join("b.yml").to_string_lossy().to_string();
+        let c = dir.path().join("c.yml").to_string_lossy().to_string();
+        let subject = SourceLoader::load(&a, None).unwrap_err().to_string();
+        assert_eq!(
+            subject,
+            format!(
+                "file open error! path: '{}' included from {} -> {}",
+                c, a, b
+            )
+        );
+    }
+
+    #[test]
+    fn invalid_include() {
+        let dir = tempdir().unwrap();
+        write(&dir, "a.yml", "---\nwidth: 1\n");
+        let path = write(&dir, "main.yml", "---\nlayers:\n  - include: a.yml\n");
+        let subject = SourceLoader::load(&path, None).unwrap_err().to_string();
+        assert!(subject.starts_with("invalid include! path: "));
+    }
+
+    #[test]
+    fn include_with_vars_and_styles() {
+        let dir = tempdir().unwrap();
+        let part = write(
+            &dir,
+            "a.yml",
+            "---
+vars:
+  r: 1
+styles:
+  primary:
+    color: red
+layers: []
+",
+        );
+        let path = write(&dir, "main.yml", "---\nlayers:\n  - include: a.yml\n");
+        let subject = SourceLoader::load(&path, None).unwrap_err().to_string();
+        assert_eq!(
+            subject,
+            format!(
+                "included file can't set 'vars', 'styles', import it instead! path: '{}'",
+                part
+            )
+        );
+    }
+
+    #[test]
+    fn source_map_follows_includes() {
+        let dir = tempdir().unwrap();
+        let part = write(
+            &dir,
+            "part.yml",
+            "---\nlayers:\n  - circle: {x: 1, y: 1, color: red}\n",
+        );
+        let path = write(
+            &dir,
+            "main.yml",
+            "---
+layers:
+  - include: part.yml
+  - rectangle:
+      color: red
+",
+        );
+        let (_, map) = SourceLoader::load(&path, None).unwrap();
+        assert_eq!(map.files(), vec![path.as_str(), part.as_str()]);
+        let layer = |i| vec![Segment::Key("layers".to_string()), Segment::Index(i)];
+
+        let subject = map.locate(&layer(0), Some("red")).unwrap();
+        assert_eq!((subject.file, subject.line, subject.col), (part, 3, 33));
+        let subject = map.locate(&layer(1), Some("red")).unwrap();
+        assert_eq!((subject.file, subject.line, subject.col), (path, 5, 14));
+    }
+
+    #[test]
+    fn parse_error_location() {
+        let dir = tempdir().unwrap();
+        let path = write(&dir, "main.yml", "---\nwidth: 1\nlayers: [\n  - x\n");
+        let subject = SourceLoader::load(&path, None).unwrap_err().to_string();
+        assert_eq!(
+            subject,
+            format!(
+                "{}:4:3: invalid yaml format! \"-\" is only valid inside a block
+  |
+4 |   - x
+  |   ^",
+                path
+            )
+        );
+    }
+
+    #[test]
+    fn formats() {
+        let dir = tempdir().unwrap();
+        write(
+            &dir,
+            "part.json",
+            r##"[{"circle": {"x": 1, "y": 1, "radius": 1, "color": "red"}}]"##,
+        );
+        let path = write(
+            &dir,
+            "main.toml",
+            "width = 10\nheight = 10\nlayers = [{include = 'part.json'}]\n",
+        );
+        let subject = load(&path);
+        assert_eq!(subject["width"].as_i64(), Some(10));
+        assert_eq!(subject["layers"][0]["circle"]["radius"].as_i64(), Some(1));
+
+        let path = write(&dir, "main.txt", "{\"width\": 10}");
+        let (subject, _) = SourceLoader::load(&path, Some(InputFormat::Json)).unwrap();
+        assert_eq!(subject["width"].as_i64(), Some(10));
+    }
+
+    #[test]
+    fn json_error_location() {
+        let dir = tempdir().unwrap();
+        let path = write(&dir, "main.json", "{\n  \"width\": 10,\n}\n");
+        let subject = SourceLoader::load(&path, None).unwrap_err().to_string();
+        assert_eq!(
+            subject,
+            format!(
+                "{}:3:1: invalid json format! trailing comma
+  |
+3 | }}
+  | ^",
+                path
+            )
+        );
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("main.yml");
+        File::create(&path)
+            .unwrap()
+            .write_all(b"width: \xFF\n")
+            .unwrap();
+        let path = path.to_string_lossy().to_string();
+        let subject = SourceLoader::load(&path, None).unwrap_err().to_string();
+        assert_eq!(
+            subject,
+            format!("file is not valid UTF-8 at byte 7! path: '{}'", path)
+        );
+    }
+
+    #[test]
+    fn json_error_at_end() {
+        let dir = tempdir().unwrap();
+        let path = write(&dir, "main.json", "{\n  \"width\": 10\n");
+        let subject = SourceLoader::load(&path, None).unwrap_err().to_string();
+        assert!(subject.starts_with(&format!("{}:3:1: invalid json format!", path)));
+    }
 
-        let doc = &docs[0];
+    #[test]
+    fn toml_error_location() {
+        let dir = tempdir().unwrap();
+        let path = write(&dir, "main.toml", "width = 10\nheight = \n");
+        let subject = SourceLoader::load(&path, None).unwrap_err().to_string();
+        assert_eq!(
+            subject,
+            format!(
+                "{}:2:10: invalid toml format! expected a value, found a newline
+  |
+2 | height = 
+  |          ^",
+                path
+            )
+        );
 
-        Ok(doc.clone())
+        let path = write(&dir, "main.toml", "width = 10\nheight = 'x'\n");
+        let (_, map) = SourceLoader::load(&path, None).unwrap();
+        let subject = map
+            .locate(&[Segment::Key("height".to_string())], Some("x"))
+            .unwrap();
+        assert_eq!((subject.file, subject.line, subject.col), (path, 2, 10));
     }
 }
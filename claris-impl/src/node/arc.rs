@@ -6,18 +6,33 @@ use crate::node::error::NodeError;
 use crate::node::scale::Scale;
 use crate::node::stroke::Stroke;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArcClose {
+    Open,
+    Pie,
+    Chord,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArcDirection {
+    Clockwise,
+    CounterClockwise,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct Arc {
     pub x: f64,
     pub y: f64,
     pub start: f64,
     pub end: f64,
-    pub close: bool,
+    pub close: ArcClose,
+    pub direction: ArcDirection,
     pub fill: bool,
     pub color: Color,
     pub stroke: Stroke,
     pub scale: Scale,
     pub radius: f64,
+    pub inner_radius: f64,
 }
 
 impl Arc {
@@ -35,17 +50,30 @@ impl Arc {
             .f64_val("end")
             .ok_or_else(|| NodeError::Required("arc".to_string(), "end".to_string()))?;
         let fill = src.bool_val("fill").unwrap_or(false);
-        let close = src.bool_val("close").unwrap_or(false);
+        let close = match src.bool_val("close") {
+            Some(true) => ArcClose::Pie,
+            Some(false) => ArcClose::Open,
+            None => match src.str_val("close") {
+                Some("pie") => ArcClose::Pie,
+                Some("chord") => ArcClose::Chord,
+                _ => ArcClose::Open,
+            },
+        };
+        let direction = match src.str_val("direction") {
+            Some("ccw") => ArcDirection::CounterClockwise,
+            _ => ArcDirection::Clockwise,
+        };
         let radius = src
             .f64_val("radius")
             .ok_or_else(|| NodeError::Required("arc".to_string(), "radius".to_string()))?;
+        let inner_radius = src.f64_val("inner_radius").unwrap_or(0.0);
         let alpha = src.f32_val("alpha").unwrap_or(1.0);
         let color = src
             .string_val("color")
             .ok_or_else(|| NodeError::Required("arc".to_string(), "color".to_string()))
             .and_then(|x| -> Result<Color, NodeError> {
                 Color::parse(x).and_then(|c| -> Result<Color, NodeError> {
-                    Ok(Color::new(c.r, c.g, c.b, alpha))
+                    Ok(Color::new(c.r, c.g, c.b, c.a * alpha))
                 })
             })?;
         let stroke = src
@@ -62,17 +90,19 @@ impl Arc {
             end,
             fill,
             close,
+            direction,
             color,
             stroke,
             scale,
             radius,
+            inner_radius,
         })
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Arc;
+    use super::{Arc, ArcClose, ArcDirection};
     use crate::parse_yaml;
     use cairo::LineCap;
     use float_cmp::approx_eq;
@@ -242,6 +272,138 @@ end: 320
         assert_eq!(subject.fill, false)
     }
 
+    #[test]
+    fn close_is_true() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+close: true
+radius: 15
+start: 40
+end: 320
+";
+        let subject = parse!(s);
+        assert_eq!(subject.close, ArcClose::Pie)
+    }
+
+    #[test]
+    fn close_is_false() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+close: false
+radius: 15
+start: 40
+end: 320
+";
+        let subject = parse!(s);
+        assert_eq!(subject.close, ArcClose::Open)
+    }
+
+    #[test]
+    fn close_is_pie() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+close: pie
+radius: 15
+start: 40
+end: 320
+";
+        let subject = parse!(s);
+        assert_eq!(subject.close, ArcClose::Pie)
+    }
+
+    #[test]
+    fn close_is_chord() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+close: chord
+radius: 15
+start: 40
+end: 320
+";
+        let subject = parse!(s);
+        assert_eq!(subject.close, ArcClose::Chord)
+    }
+
+    #[test]
+    fn close_is_blank() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+radius: 15
+start: 40
+end: 320
+";
+        let subject = parse!(s);
+        assert_eq!(subject.close, ArcClose::Open)
+    }
+
+    #[test]
+    fn direction_is_ccw() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+direction: ccw
+radius: 15
+start: 40
+end: 320
+";
+        let subject = parse!(s);
+        assert_eq!(subject.direction, ArcDirection::CounterClockwise)
+    }
+
+    #[test]
+    fn direction_is_blank() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+radius: 15
+start: 40
+end: 320
+";
+        let subject = parse!(s);
+        assert_eq!(subject.direction, ArcDirection::Clockwise)
+    }
+
+    #[test]
+    fn inner_radius_is_not_blank() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+radius: 15
+inner_radius: 10
+start: 40
+end: 320
+";
+        let subject = parse!(s);
+        assert!(approx_eq!(f64, subject.inner_radius, 10.0));
+    }
+
+    #[test]
+    fn inner_radius_is_blank() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+radius: 15
+start: 40
+end: 320
+";
+        let subject = parse!(s);
+        assert!(approx_eq!(f64, subject.inner_radius, 0.0));
+    }
+
     #[test]
     fn radius_is_not_blank() {
         let s = "---
@@ -0,0 +1,271 @@
+use crate::node::schema::{self, expected, DefaultValue, Field, Kind};
+
+/// One step of the path from the document root to a mapping being edited.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    Key(String),
+    Item,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SuggestionKind {
+    Layer,
+    Option,
+    Value,
+}
+
+/// A completion candidate, with a one-line summary and Markdown docs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    pub label: String,
+    pub kind: SuggestionKind,
+    pub detail: String,
+    pub documentation: String,
+}
+
+// What the mapping at a path holds.
+enum Context {
+    Fields(Vec<Field>),
+    Kind(Kind),
+    // An entry of `layers:`, named by its only key.
+    Layer,
+    Unknown,
+}
+
+fn resolve(steps: &[Step]) -> Context {
+    let mut ret = Context::Fields(schema::root());
+    for step in steps {
+        ret = match (ret, step) {
+            (Context::Fields(fields), Step::Key(key)) => {
+                match fields.iter().find(|x| x.name == key) {
+                    Some(x) => of_kind(x.kind),
+                    None => Context::Unknown,
+                }
+            }
+            (Context::Layer, Step::Key(key)) => match schema::layer(key) {
+                Some(x) => Context::Fields(x),
+                None => Context::Unknown,
+            },
+            (Context::Kind(Kind::Layers), Step::Item) => Context::Layer,
+            (Context::Kind(Kind::ListOf(fields)), Step::Item) => Context::Fields(fields.to_vec()),
+            (Context::Kind(Kind::Map(inner)), Step::Key(_)) => of_kind(*inner),
+            _ => Context::Unknown,
+        };
+    }
+    ret
+}
+
+fn of_kind(kind: Kind) -> Context {
+    match kind {
+        Kind::Options(fields) => Context::Fields(fields.to_vec()),
+        _ => Context::Kind(kind),
+    }
+}
+
+/// Returns the keys that may be written in the mapping at `steps`, except
+/// those in `present`.
+pub fn keys(steps: &[Step], present: &[String]) -> Vec<Suggestion> {
+    let names: Vec<&str> = match resolve(steps) {
+        Context::Fields(fields) => {
+            return fields
+                .iter()
+                .filter(|x| !present.iter().any(|p| p == x.name))
+                .map(|x| Suggestion {
+                    label: x.name.to_string(),
+                    kind: SuggestionKind::Option,
+                    detail: expected(x.kind),
+                    documentation: field_doc(x),
+                })
+                .collect()
+        }
+        Context::Layer => {
+            let mut ret = schema::LAYERS.to_vec();
+            ret.extend_from_slice(&["repeat", "include"]);
+            ret
+        }
+        _ => Vec::new(),
+    };
+
+    names
+        .into_iter()
+        .map(|x| Suggestion {
+            label: x.to_string(),
+            kind: SuggestionKind::Layer,
+            detail: "layer".to_string(),
+            documentation: layer_doc(x),
+        })
+        .collect()
+}
+
+/// Returns the values `key` accepts in the mapping at `steps`.
+pub fn values(steps: &[Step], key: &str) -> Vec<Suggestion> {
+    let field = match resolve(steps) {
+        Context::Fields(fields) => fields.into_iter().find(|x| x.name == key),
+        _ => None,
+    };
+    let mut ret = Vec::new();
+    if let Some(x) = field {
+        literals(x.kind, &mut ret);
+    }
+
+    ret.into_iter()
+        .map(|x| Suggestion {
+            label: x.to_string(),
+            kind: SuggestionKind::Value,
+            detail: key.to_string(),
+            documentation: String::new(),
+        })
+        .collect()
+}
+
+fn literals(kind: Kind, out: &mut Vec<&'static str>) {
+    match kind {
+        Kind::Bool => out.extend_from_slice(&["true", "false"]),
+        Kind::Enum(values) => out.extend_from_slice(values),
+        Kind::Either(kinds) => kinds.iter().for_each(|x| literals(*x, out)),
+        _ => {}
+    }
+}
+
+/// Returns the docs of `key` in the mapping at `steps`, a layer type or an
+/// option.
+pub fn describe(steps: &[Step], key: &str) -> Option<String> {
+    match resolve(steps) {
+        Context::Fields(fields) => fields.iter().find(|x| x.name == key).map(field_doc),
+        Context::Layer => Some(layer_doc(key)),
+        _ => None,
+    }
+}
+
+fn field_doc(field: &Field) -> String {
+    let mut ret = format!("**{}**: {}.", field.name, expected(field.kind));
+    if field.required {
+        ret.push_str("\n\nRequired.");
+    }
+    match field.default {
+        Some(DefaultValue::Number(x)) => ret.push_str(&format!("\n\nDefault: `{}`", x)),
+        Some(DefaultValue::Bool(x)) => ret.push_str(&format!("\n\nDefault: `{}`", x)),
+        Some(DefaultValue::Text(x)) => ret.push_str(&format!("\n\nDefault: `{}`", x)),
+        None => {}
+    }
+    ret
+}
+
+fn layer_doc(name: &str) -> String {
+    match name {
+        "repeat" => "**repeat**: draws its `layers` `count` times, or in a grid of `rows` and \
+                     `cols`, with the index as a variable."
+            .to_string(),
+        "include" => "**include**: inserts the layers of another file.".to_string(),
+        "use" => "**use**: draws a component of `defs`.".to_string(),
+        _ => match schema::layer(name) {
+            Some(fields) => {
+                let names = |required: bool| -> String {
+                    fields
+                        .iter()
+                        .filter(|x| x.required == required)
+                        .map(|x| format!("`{}`", x.name))
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                };
+                format!(
+                    "**{}** layer.\n\nRequired: {}\n\nOptional: {}",
+                    name,
+                    names(true),
+                    names(false)
+                )
+            }
+            None => format!("unknown layer '{}'", name),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{describe, keys, values, Step, SuggestionKind};
+
+    fn key(x: &str) -> Step {
+        Step::Key(x.to_string())
+    }
+
+    fn labels(steps: &[Step], present: &[String]) -> Vec<String> {
+        keys(steps, present).into_iter().map(|x| x.label).collect()
+    }
+
+    #[test]
+    fn root_keys() {
+        let present = vec!["width".to_string()];
+        let subject = labels(&[], &present);
+        assert_eq!(subject[0], "imports");
+        assert!(subject.contains(&"layers".to_string()));
+        assert!(!subject.contains(&"width".to_string()));
+    }
+
+    #[test]
+    fn layer_types() {
+        let subject = keys(&[key("layers"), Step::Item], &[]);
+        assert_eq!(subject[0].label, "rectangle");
+        assert_eq!(subject[0].kind, SuggestionKind::Layer);
+        assert!(subject.iter().any(|x| x.label == "repeat"));
+        let nested = labels(
+            &[
+                key("defs"),
+                key("badge"),
+                key("layers"),
+                Step::Item,
+                key("repeat"),
+                key("layers"),
+                Step::Item,
+            ],
+            &[],
+        );
+        assert!(nested.contains(&"text".to_string()));
+    }
+
+    #[test]
+    fn layer_options() {
+        let subject = labels(
+            &[
+                key("layers"),
+                Step::Item,
+                key("text"),
+                key("spans"),
+                Step::Item,
+            ],
+            &[],
+        );
+        assert_eq!(subject[0], "text");
+        let subject = labels(
+            &[key("layers"), Step::Item, key("circle"), key("stroke")],
+            &[],
+        );
+        assert_eq!(subject, vec!["width", "cap"]);
+        assert!(labels(&[key("vars")], &[]).is_empty());
+    }
+
+    #[test]
+    fn option_values() {
+        let steps = [key("layers"), Step::Item, key("arc")];
+        let subject: Vec<String> = values(&steps, "close")
+            .into_iter()
+            .map(|x| x.label)
+            .collect();
+        assert_eq!(subject, vec!["true", "false", "open", "pie", "chord"]);
+        assert!(values(&steps, "x").is_empty());
+    }
+
+    #[test]
+    fn docs() {
+        let steps = [key("layers"), Step::Item, key("circle"), key("stroke")];
+        assert_eq!(
+            describe(&steps, "cap").unwrap(),
+            "**cap**: one of butt, round, square.\n\nDefault: `butt`"
+        );
+        let subject = describe(&[key("layers"), Step::Item], "circle").unwrap();
+        assert!(subject.starts_with("**circle** layer.\n\nRequired: `x`, `y`, `radius`, `color`"));
+        assert_eq!(
+            describe(&[key("layers"), Step::Item, key("circle")], "nope"),
+            None
+        );
+    }
+}
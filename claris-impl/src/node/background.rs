@@ -0,0 +1,100 @@
+use crate::ext::YamlExt;
+use crate::node::color::Color;
+use crate::node::error::NodeError;
+use yaml_rust::yaml::Hash;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Background {
+    pub color: Color,
+    pub padding: f64,
+    pub radius: f64,
+}
+
+impl Background {
+    const DEFAULT_PADDING: f64 = 0.0;
+    const DEFAULT_RADIUS: f64 = 0.0;
+
+    pub fn parse(src: &Hash) -> Result<Background, NodeError> {
+        let padding = src.f64_val("padding").unwrap_or(Self::DEFAULT_PADDING);
+        let radius = src.f64_val("radius").unwrap_or(Self::DEFAULT_RADIUS);
+        let alpha = src.f32_val("alpha").unwrap_or(1.0);
+        let color = src
+            .string_val("color")
+            .ok_or_else(|| NodeError::Required("background".to_string(), "color".to_string()))
+            .and_then(|x| -> Result<Color, NodeError> {
+                Color::parse(x).map(|c| -> Color { Color::new(c.r, c.g, c.b, c.a * alpha) })
+            })?;
+
+        Ok(Background {
+            color,
+            padding,
+            radius,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Background;
+    use crate::parse_yaml;
+    use float_cmp::approx_eq;
+    use yaml_rust::Yaml;
+    use yaml_rust::YamlLoader;
+
+    macro_rules! parse {
+        ($x:expr) => {{
+            let src = parse_yaml!($x);
+            match src {
+                Yaml::Hash(x) => match Background::parse(&x) {
+                    Ok(x) => x,
+                    Err(e) => panic!(e.to_string()),
+                },
+                _ => panic!("invalid yaml"),
+            }
+        }};
+    }
+
+    #[test]
+    fn padding_and_radius_are_blank() {
+        let s = "---
+color: '#AABBCC'
+        ";
+        let subject = parse!(s);
+        assert!(approx_eq!(f64, subject.padding, 0.0));
+        assert!(approx_eq!(f64, subject.radius, 0.0));
+    }
+
+    #[test]
+    fn padding_and_radius_are_not_blank() {
+        let s = "---
+color: '#AABBCC'
+padding: 4
+radius: 2.5
+        ";
+        let subject = parse!(s);
+        assert!(approx_eq!(f64, subject.padding, 4.0));
+        assert!(approx_eq!(f64, subject.radius, 2.5));
+    }
+
+    #[test]
+    fn color_and_alpha() {
+        let s = "---
+color: '#AABBCC'
+alpha: 0.5
+        ";
+        let subject = parse!(s);
+        assert_eq!(subject.color.r, 170);
+        assert_eq!(subject.color.g, 187);
+        assert_eq!(subject.color.b, 204);
+        assert!(approx_eq!(f32, subject.color.a, 0.5));
+    }
+
+    #[test]
+    #[should_panic(expected = "'background' is required 'color' option")]
+    fn color_is_blank() {
+        let s = "---
+padding: 4
+        ";
+        parse!(s);
+    }
+}
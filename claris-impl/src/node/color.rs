@@ -12,11 +12,87 @@ pub struct Color {
 impl Color {
     const DEFAULT_ALPHA: f32 = 1.0;
 
+    /// Parses a CSS colour, or one of the functions `lighten(color, amount)`,
+    /// `darken(color, amount)`, `mix(color, color, weight)`,
+    /// `hsv(hue, saturation, value)` and `alpha(color, alpha)`. Arguments
+    /// may be nested functions; amounts are fractions or percentages.
     pub fn parse(src: String) -> Result<Color, NodeError> {
-        match src.parse::<CssColor>() {
-            Ok(x) => Ok(Self::new(x.r, x.g, x.b, x.a)),
-            Err(_) => Err(NodeError::InvalidColor(src)),
+        Self::parse_str(src.trim()).ok_or(NodeError::InvalidColor(src))
+    }
+
+    fn parse_str(src: &str) -> Option<Color> {
+        let (name, args) = match split_call(src) {
+            Some(x) => x,
+            None => return Self::parse_css(src),
+        };
+        match (name, args.as_slice()) {
+            ("lighten", [c, x]) => Self::parse_str(c)?.shift_lightness(amount(x)?),
+            ("darken", [c, x]) => Self::parse_str(c)?.shift_lightness(-amount(x)?),
+            ("mix", [a, b, x]) => Self::parse_str(a)?.mix(Self::parse_str(b)?, amount(x)?),
+            ("alpha", [c, x]) => {
+                let c = Self::parse_str(c)?;
+                Some(Self::new(c.r, c.g, c.b, amount(x)? as f32))
+            }
+            ("hsv", [h, s, v]) => {
+                let (r, g, b) = hsv_to_rgb(h.parse().ok()?, amount(s)?, amount(v)?);
+                Some(Self::from_unit(r, g, b, 1.0))
+            }
+            _ => Self::parse_css(src),
+        }
+    }
+
+    fn parse_css(src: &str) -> Option<Color> {
+        if let Some(x) = Self::parse_hex_alpha(src) {
+            return Some(x);
+        }
+        src.parse::<CssColor>()
+            .ok()
+            .map(|x| Self::new(x.r, x.g, x.b, x.a))
+    }
+
+    // Parses `#RRGGBBAA`, which the CSS parser does not know.
+    fn parse_hex_alpha(src: &str) -> Option<Color> {
+        let hex = src.strip_prefix('#')?;
+        if hex.len() != 8 || !hex.bytes().all(|x| x.is_ascii_hexdigit()) {
+            return None;
         }
+        let byte = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        let a = byte(6)? as f32 / 255.0;
+        Some(Self::new(byte(0)?, byte(2)?, byte(4)?, a))
+    }
+
+    // Moves the HSL lightness by `amount` percentage points, like Sass.
+    fn shift_lightness(self, amount: f64) -> Option<Color> {
+        let (h, s, l) = rgb_to_hsl(self.unit());
+        let (r, g, b) = hsl_to_rgb(h, s, (l + amount).clamp(0.0, 1.0));
+        Some(Self::from_unit(r, g, b, self.a))
+    }
+
+    // A weight of 0 keeps `self` and 1 gives `other`.
+    fn mix(self, other: Color, weight: f64) -> Option<Color> {
+        let w = weight.clamp(0.0, 1.0);
+        let (r1, g1, b1) = self.unit();
+        let (r2, g2, b2) = other.unit();
+        let a = self.a as f64 * (1.0 - w) + other.a as f64 * w;
+        Some(Self::from_unit(
+            r1 + (r2 - r1) * w,
+            g1 + (g2 - g1) * w,
+            b1 + (b2 - b1) * w,
+            a as f32,
+        ))
+    }
+
+    fn unit(self) -> (f64, f64, f64) {
+        (
+            self.r as f64 / 255.0,
+            self.g as f64 / 255.0,
+            self.b as f64 / 255.0,
+        )
+    }
+
+    fn from_unit(r: f64, g: f64, b: f64, a: f32) -> Color {
+        let byte = |x: f64| -> u8 { (x * 255.0).round().clamp(0.0, 255.0) as u8 };
+        Self::new(byte(r), byte(g), byte(b), a)
     }
 
     pub fn new(r: u8, g: u8, b: u8, a: f32) -> Color {
@@ -33,6 +109,89 @@ impl Color {
     }
 }
 
+// Splits `name(a, b(c, d))` into its name and top level arguments.
+fn split_call(src: &str) -> Option<(&str, Vec<&str>)> {
+    let open = src.find('(')?;
+    if !src.ends_with(')') {
+        return None;
+    }
+    let name = src[..open].trim();
+    let body = &src[open + 1..src.len() - 1];
+
+    let mut args = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in body.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                args.push(body[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    args.push(body[start..].trim());
+    Some((name, args))
+}
+
+fn amount(src: &str) -> Option<f64> {
+    match src.strip_suffix('%') {
+        Some(x) => x.trim().parse::<f64>().ok().map(|x| x / 100.0),
+        None => src.parse::<f64>().ok(),
+    }
+}
+
+fn rgb_to_hsl((r, g, b): (f64, f64, f64)) -> (f64, f64, f64) {
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let l = (max + min) / 2.0;
+    if (max - min).abs() < f64::EPSILON {
+        return (0.0, 0.0, l);
+    }
+    let d = max - min;
+    let s = if l > 0.5 {
+        d / (2.0 - max - min)
+    } else {
+        d / (max + min)
+    };
+    let h = if (max - r).abs() < f64::EPSILON {
+        (g - b) / d + if g < b { 6.0 } else { 0.0 }
+    } else if (max - g).abs() < f64::EPSILON {
+        (b - r) / d + 2.0
+    } else {
+        (r - g) / d + 4.0
+    };
+    (h * 60.0, s, l)
+}
+
+fn hsl_to_rgb(h: f64, s: f64, l: f64) -> (f64, f64, f64) {
+    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    from_chroma(h, c, l - c / 2.0)
+}
+
+fn hsv_to_rgb(h: f64, s: f64, v: f64) -> (f64, f64, f64) {
+    let s = s.clamp(0.0, 1.0);
+    let v = v.clamp(0.0, 1.0);
+    let c = v * s;
+    from_chroma(h, c, v - c)
+}
+
+fn from_chroma(h: f64, c: f64, m: f64) -> (f64, f64, f64) {
+    let h = h.rem_euclid(360.0) / 60.0;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    (r + m, g + m, b + m)
+}
+
 #[cfg(test)]
 mod tests {
     use super::Color;
@@ -47,6 +206,14 @@ mod tests {
         assert!(approx_eq!(f32, subject.a, 1.0));
     }
 
+    #[test]
+    fn hex_color_with_alpha() {
+        let subject = Color::parse("#AABBCC80".to_string()).unwrap();
+        assert_eq!((subject.r, subject.g, subject.b), (170, 187, 204));
+        assert!(approx_eq!(f32, subject.a, 128.0 / 255.0));
+        assert!(Color::parse("#AABBCCGG".to_string()).is_err());
+    }
+
     #[test]
     #[should_panic(expected = "invalid color format '#AABBCG'")]
     fn invalid_color_format() {
@@ -54,4 +221,63 @@ mod tests {
             panic!(e.to_string())
         }
     }
+
+    fn parse(src: &str) -> Color {
+        match Color::parse(src.to_string()) {
+            Ok(x) => x,
+            Err(e) => panic!(e.to_string()),
+        }
+    }
+
+    fn assert_rgb(subject: Color, r: u8, g: u8, b: u8) {
+        assert_eq!((subject.r, subject.g, subject.b), (r, g, b));
+    }
+
+    #[test]
+    fn lighten_and_darken() {
+        assert_rgb(parse("lighten(#3366ff, 20%)"), 153, 179, 255);
+        assert_rgb(parse("darken(#3366ff, 0.2)"), 0, 51, 204);
+        assert_rgb(parse("lighten(#ffffff, 10%)"), 255, 255, 255);
+        assert_rgb(parse("darken(black, 50%)"), 0, 0, 0);
+    }
+
+    #[test]
+    fn mix() {
+        assert_rgb(parse("mix(red, blue, 0.3)"), 179, 0, 77);
+        assert_rgb(parse("mix(red, blue, 0%)"), 255, 0, 0);
+        assert_rgb(parse("mix(#000, #fff, 50%)"), 128, 128, 128);
+    }
+
+    #[test]
+    fn hsl_and_hsv() {
+        assert_rgb(parse("hsl(120, 100%, 25%)"), 0, 128, 0);
+        assert_rgb(parse("hsv(210, 80%, 100%)"), 51, 153, 255);
+        assert_rgb(parse("hsv(0, 0, 0.5)"), 128, 128, 128);
+    }
+
+    #[test]
+    fn alpha() {
+        let subject = parse("alpha(#3366ff, 0.5)");
+        assert_rgb(subject, 51, 102, 255);
+        assert!(approx_eq!(f32, subject.a, 0.5));
+    }
+
+    #[test]
+    fn nested_functions() {
+        let subject = parse("alpha(mix(lighten(#000000, 50%), rgb(255, 0, 0), 0.5), 25%)");
+        assert_rgb(subject, 192, 64, 64);
+        assert!(approx_eq!(f32, subject.a, 0.25));
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid color format 'lighten(#3366ff)'")]
+    fn missing_argument() {
+        parse("lighten(#3366ff)");
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid color format 'mix(red, nope, 0.5)'")]
+    fn invalid_argument() {
+        parse("mix(red, nope, 0.5)");
+    }
 }
@@ -0,0 +1,156 @@
+use yaml_rust::yaml::Hash;
+use yaml_rust::Yaml;
+
+use crate::expr::{Scope, Value};
+use crate::ext::YamlExt;
+use crate::node::error::NodeError;
+
+/// A reusable list of layers declared under `defs:`.
+#[derive(Debug, Clone)]
+pub struct Component {
+    pub name: String,
+    pub params: Hash,
+    pub layers: Vec<Yaml>,
+}
+
+impl Component {
+    pub fn parse(name: &str, src: &Yaml) -> Result<Component, NodeError> {
+        let params = src.hash_val("params").cloned().unwrap_or_else(Hash::new);
+        let layers = src
+            .array_val("layers")
+            .cloned()
+            .ok_or_else(|| NodeError::Required(name.to_string(), "layers".to_string()))?;
+
+        Ok(Component {
+            name: name.to_string(),
+            params,
+            layers,
+        })
+    }
+
+    /// Returns the scope the layers of an instance are resolved in: the
+    /// caller's variables, then the parameter defaults, then `overrides`.
+    pub fn scope(&self, caller: &Scope, overrides: Option<&Hash>) -> Result<Scope, NodeError> {
+        let mut scope = caller.clone();
+        scope.define(&self.params)?;
+
+        if let Some(overrides) = overrides {
+            for (key, value) in overrides {
+                let name = key.as_str().ok_or(NodeError::InvalidVariable)?;
+                if !self.params.contains_key(key) {
+                    return Err(NodeError::UnknownParameter(
+                        self.name.clone(),
+                        name.to_string(),
+                    ));
+                }
+                let value = Value::from_yaml(value).ok_or(NodeError::InvalidVariable)?;
+                scope.set(name, value);
+            }
+        }
+
+        Ok(scope)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Component;
+    use crate::expr::{Scope, Value};
+    use crate::parse_yaml;
+    use yaml_rust::YamlLoader;
+
+    macro_rules! parse {
+        ($x:expr) => {{
+            let src = parse_yaml!($x);
+            match Component::parse("button", &src) {
+                Ok(x) => x,
+                Err(e) => panic!(e.to_string()),
+            }
+        }};
+    }
+
+    #[test]
+    fn full_options() {
+        let s = "---
+params:
+  color: '#AABBCC'
+  size: 10
+layers:
+  - circle:
+      x: 0
+      y: 0
+      radius: $size
+      color: $color
+";
+        let subject = parse!(s);
+        assert_eq!(subject.name, "button");
+        assert_eq!(subject.params.len(), 2);
+        assert_eq!(subject.layers.len(), 1);
+    }
+
+    #[test]
+    fn params_is_blank() {
+        let s = "---
+layers: []
+";
+        let subject = parse!(s);
+        assert!(subject.params.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "'button' is required 'layers' option")]
+    fn layers_is_blank() {
+        let s = "---
+params:
+  size: 10
+";
+        parse!(s);
+    }
+
+    #[test]
+    fn scope_with_overrides() {
+        let s = "---
+params:
+  size: 10
+  half: $size / 2
+  color: '#AABBCC'
+layers: []
+";
+        let subject = parse!(s);
+        let overrides = parse_yaml!("---\ncolor: '#112233'\n");
+        let scope = subject.scope(&Scope::new(), overrides.as_hash()).unwrap();
+        assert_eq!(scope.get("half"), Some(&Value::Number(5.0)));
+        assert_eq!(scope.get("color"), Some(&Value::Str("#112233".to_string())));
+    }
+
+    #[test]
+    fn scope_with_boolean() {
+        let s = "---
+params:
+  fill: false
+layers: []
+";
+        let subject = parse!(s);
+        let overrides = parse_yaml!("---\nfill: true\n");
+        let scope = match subject.scope(&Scope::new(), overrides.as_hash()) {
+            Ok(x) => x,
+            Err(e) => panic!(e.to_string()),
+        };
+        assert_eq!(scope.get("fill"), Some(&Value::Str("true".to_string())));
+    }
+
+    #[test]
+    #[should_panic(expected = "component 'button' has no parameter 'label'")]
+    fn scope_with_unknown_parameter() {
+        let s = "---
+params:
+  size: 10
+layers: []
+";
+        let subject = parse!(s);
+        let overrides = parse_yaml!("---\nlabel: OK\n");
+        if let Err(e) = subject.scope(&Scope::new(), overrides.as_hash()) {
+            panic!(e.to_string());
+        }
+    }
+}
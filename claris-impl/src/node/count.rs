@@ -0,0 +1,78 @@
+use yaml_rust::Yaml;
+
+use crate::node::error::NodeError;
+use crate::node::schema::describe;
+
+/// Reads the whole number of `key` of a `layer` layer, from `min` up to
+/// `max`. Returns `None` when it is not written.
+pub fn parse_count(
+    src: &Yaml,
+    layer: &str,
+    key: &str,
+    min: u32,
+    max: u32,
+) -> Result<Option<u32>, NodeError> {
+    let value = &src[key];
+    let x = match value {
+        Yaml::BadValue | Yaml::Null => return Ok(None),
+        Yaml::Integer(x) => *x as f64,
+        Yaml::Real(_) => value.as_f64().unwrap_or(f64::NAN),
+        _ => f64::NAN,
+    };
+    if !x.is_finite() || x.fract() != 0.0 {
+        return Err(NodeError::InvalidValue(
+            key.to_string(),
+            describe(value),
+            "a whole number".to_string(),
+        ));
+    }
+    if x < min as f64 {
+        return Err(NodeError::TooSmall(layer.to_string(), key.to_string(), min));
+    }
+    if x > max as f64 {
+        return Err(NodeError::TooLarge(layer.to_string(), key.to_string(), max));
+    }
+    Ok(Some(x as u32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_count;
+    use crate::parse_yaml;
+    use yaml_rust::YamlLoader;
+
+    fn parse(src: &str) -> Result<Option<u32>, String> {
+        parse_count(&parse_yaml!(src), "star", "points", 2, 100).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn counts() {
+        assert_eq!(parse("---\npoints: 5\n"), Ok(Some(5)));
+        assert_eq!(parse("---\npoints: 5.0\n"), Ok(Some(5)));
+        assert_eq!(parse("---\nx: 1\n"), Ok(None));
+    }
+
+    #[test]
+    fn invalid_counts() {
+        assert_eq!(
+            parse("---\npoints: 5.5\n"),
+            Err("invalid 'points' value '5.5', expected a whole number".to_string())
+        );
+        assert_eq!(
+            parse("---\npoints: five\n"),
+            Err("invalid 'points' value 'five', expected a whole number".to_string())
+        );
+        assert_eq!(
+            parse("---\npoints: 1\n"),
+            Err("'star' option 'points' must be at least 2".to_string())
+        );
+        assert_eq!(
+            parse("---\npoints: -3\n"),
+            Err("'star' option 'points' must be at least 2".to_string())
+        );
+        assert_eq!(
+            parse("---\npoints: 8589934592\n"),
+            Err("'star' option 'points' must be at most 100".to_string())
+        );
+    }
+}
@@ -0,0 +1,182 @@
+use serde::de::{Deserialize, Deserializer, Error};
+use yaml_rust::yaml::Hash;
+use yaml_rust::Yaml;
+
+use crate::expr::Scope;
+use crate::format::YamlTree;
+use crate::node::point::DefPoint;
+use crate::node::schema::describe;
+use crate::node::{
+    Arc, Background, Circle, Color, Curve, Ellipse, Fit, Line, NodeError, Outline, Point, Polygon,
+    Rectangle, RegularPolygon, Root, Scale, Star, Stroke, Text, TextPath, Triangle,
+};
+
+// Nodes are read into the YAML tree their parsers take, so every serde
+// format gets the same defaults and errors as YAML sources. A `Layer` is
+// only read as part of a `Root`, since `use`, `repeat` and `class` need the
+// document's `defs`, `styles` and `vars`, and a `Span` only as part of a
+// `Text`, whose options it takes by default.
+macro_rules! deserialize_node {
+    ($($name:ident),*) => {
+        $(
+            impl<'de> Deserialize<'de> for $name {
+                fn deserialize<D>(deserializer: D) -> Result<$name, D::Error>
+                where
+                    D: Deserializer<'de>,
+                {
+                    let YamlTree(src) = YamlTree::deserialize(deserializer)?;
+                    $name::parse(&src).map_err(D::Error::custom)
+                }
+            }
+        )*
+    };
+}
+
+deserialize_node!(
+    Arc,
+    Circle,
+    Curve,
+    Ellipse,
+    Line,
+    Polygon,
+    Rectangle,
+    RegularPolygon,
+    Star,
+    Text,
+    Triangle
+);
+
+// Options of a layer, read from a map.
+macro_rules! deserialize_options {
+    ($($name:ident($key:literal) => $parse:expr),*) => {
+        $(
+            impl<'de> Deserialize<'de> for $name {
+                fn deserialize<D>(deserializer: D) -> Result<$name, D::Error>
+                where
+                    D: Deserializer<'de>,
+                {
+                    let src = options(deserializer, $key)?;
+                    $parse(&src).map_err(D::Error::custom)
+                }
+            }
+        )*
+    };
+}
+
+deserialize_options!(
+    Stroke("stroke") => |x| -> Result<Stroke, NodeError> { Ok(Stroke::parse(x)) },
+    Scale("scale") => |x| -> Result<Scale, NodeError> { Ok(Scale::parse(x)) },
+    Fit("fit") => Fit::parse,
+    Outline("outline") => Outline::parse,
+    Background("background") => Background::parse,
+    TextPath("path") => TextPath::parse
+);
+
+fn options<'de, D>(deserializer: D, key: &str) -> Result<Hash, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match YamlTree::deserialize(deserializer)? {
+        YamlTree(Yaml::Hash(x)) => Ok(x),
+        YamlTree(x) => Err(D::Error::custom(NodeError::InvalidValue(
+            key.to_string(),
+            describe(&x),
+            "options".to_string(),
+        ))),
+    }
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D>(deserializer: D) -> Result<Color, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match YamlTree::deserialize(deserializer)? {
+            YamlTree(Yaml::String(x)) => Color::parse(x).map_err(D::Error::custom),
+            YamlTree(x) => Err(D::Error::custom(NodeError::InvalidColor(describe(&x)))),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Point {
+    fn deserialize<D>(deserializer: D) -> Result<Point, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let YamlTree(src) = YamlTree::deserialize(deserializer)?;
+        src.as_point()
+            .ok_or_else(|| D::Error::custom(NodeError::InvalidPoint))
+    }
+}
+
+impl<'de> Deserialize<'de> for Root {
+    fn deserialize<D>(deserializer: D) -> Result<Root, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let YamlTree(src) = YamlTree::deserialize(deserializer)?;
+        Root::parse(&src, &Scope::new()).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::node::{Circle, Color, Layer, Point, Root, Stroke, TextPath};
+    use float_cmp::approx_eq;
+
+    #[test]
+    fn root_from_json() {
+        let s = r##"{
+  "width": 400,
+  "height": 300,
+  "vars": {"r": 10},
+  "layers": [{"circle": {"x": 1, "y": 2.5, "radius": "$r * 2", "color": "#AABBCC"}}]
+}"##;
+        let subject: Root = serde_json::from_str(s).unwrap();
+        assert_eq!((subject.width, subject.height), (400, 300));
+        match &subject.layers[0] {
+            Layer::Circle(x) => {
+                assert!(approx_eq!(f64, x.y, 2.5));
+                assert!(approx_eq!(f64, x.radius, 20.0));
+            }
+            _ => panic!("not a circle"),
+        }
+    }
+
+    #[test]
+    fn circle_from_toml() {
+        let s = "x = 1\ny = 2\nradius = 3\ncolor = 'red'\nfill = true\n";
+        let subject: Circle = toml::from_str(s).unwrap();
+        assert!(approx_eq!(f64, subject.radius, 3.0));
+        assert!(subject.fill);
+    }
+
+    #[test]
+    fn error_from_parser() {
+        let s = r##"{"x": 1, "y": 2, "color": "red"}"##;
+        let subject = serde_json::from_str::<Circle>(s).unwrap_err().to_string();
+        assert_eq!(subject, "'circle' is required 'radius' option");
+    }
+
+    #[test]
+    fn options_from_json() {
+        let subject: Stroke = serde_json::from_str(r#"{"width": 2}"#).unwrap();
+        assert!(approx_eq!(f64, subject.width, 2.0));
+        let subject: TextPath = serde_json::from_str(r#"{"points": [[0, 0], [10, 0]]}"#).unwrap();
+        assert!(approx_eq!(f64, subject.length(), 10.0));
+        let subject = serde_json::from_str::<Stroke>("2").unwrap_err().to_string();
+        assert_eq!(subject, "invalid 'stroke' value '2', expected options");
+    }
+
+    #[test]
+    fn values_from_json() {
+        let subject: Color = serde_json::from_str(r##""#FF0000""##).unwrap();
+        assert_eq!((subject.r, subject.g, subject.b), (255, 0, 0));
+        let subject: Point = serde_json::from_str("[1, 2.5]").unwrap();
+        assert!(approx_eq!(f64, subject.y, 2.5));
+        let subject = serde_json::from_str::<Point>("[1]")
+            .unwrap_err()
+            .to_string();
+        assert_eq!(subject, "invalid point");
+    }
+}
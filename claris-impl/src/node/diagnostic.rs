@@ -0,0 +1,34 @@
+use crate::node::error::NodeError;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A problem found by `Root::validate`.
+#[derive(Debug)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub error: NodeError,
+}
+
+impl Diagnostic {
+    pub fn error(error: NodeError) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Error,
+            error,
+        }
+    }
+
+    pub fn warning(error: NodeError) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Warning,
+            error,
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
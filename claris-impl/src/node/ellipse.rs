@@ -0,0 +1,281 @@
+use yaml_rust::Yaml;
+
+use crate::ext::YamlExt;
+use crate::node::color::Color;
+use crate::node::error::NodeError;
+use crate::node::stroke::Stroke;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Ellipse {
+    pub x: f64,
+    pub y: f64,
+    pub rx: f64,
+    pub ry: f64,
+    pub rotation: f64,
+    pub fill: bool,
+    pub color: Color,
+    pub stroke: Stroke,
+}
+
+impl Ellipse {
+    pub fn parse(src: &Yaml) -> Result<Ellipse, NodeError> {
+        let x = src
+            .f64_val("x")
+            .ok_or_else(|| NodeError::Required("ellipse".to_string(), "x".to_string()))?;
+        let y = src
+            .f64_val("y")
+            .ok_or_else(|| NodeError::Required("ellipse".to_string(), "y".to_string()))?;
+        let rx = src
+            .f64_val("rx")
+            .ok_or_else(|| NodeError::Required("ellipse".to_string(), "rx".to_string()))?;
+        let ry = src
+            .f64_val("ry")
+            .ok_or_else(|| NodeError::Required("ellipse".to_string(), "ry".to_string()))?;
+        let rotation = src.f64_val("rotation").unwrap_or(0.0);
+        let fill = src.bool_val("fill").unwrap_or(false);
+        let alpha = src.f32_val("alpha").unwrap_or(1.0);
+        let color = src
+            .string_val("color")
+            .ok_or_else(|| NodeError::Required("ellipse".to_string(), "color".to_string()))
+            .and_then(|x| -> Result<Color, NodeError> {
+                Color::parse(x).map(|c| -> Color { Color::new(c.r, c.g, c.b, c.a * alpha) })
+            })?;
+        let stroke = src
+            .hash_val("stroke")
+            .map_or(Stroke::default(), |x| -> Stroke { Stroke::parse(x) });
+
+        Ok(Ellipse {
+            x,
+            y,
+            rx,
+            ry,
+            rotation,
+            fill,
+            color,
+            stroke,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Ellipse;
+    use crate::parse_yaml;
+    use cairo::LineCap;
+    use float_cmp::approx_eq;
+    use yaml_rust::YamlLoader;
+
+    macro_rules! parse {
+        ($x:expr) => {{
+            let src = parse_yaml!($x);
+            match Ellipse::parse(&src) {
+                Ok(x) => x,
+                Err(e) => panic!(e.to_string()),
+            }
+        }};
+    }
+
+    #[test]
+    fn parse_integer_x_y() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+rx: 15
+ry: 5
+";
+        let subject = parse!(s);
+        assert!(approx_eq!(f64, subject.x, 10.0));
+        assert!(approx_eq!(f64, subject.y, 20.0));
+    }
+
+    #[test]
+    fn parse_float_x_y() {
+        let s = "---
+x: 10.1
+y: 20.2
+color: '#AABBCC'
+rx: 15
+ry: 5
+";
+        let subject = parse!(s);
+        assert!(approx_eq!(f64, subject.x, 10.1));
+        assert!(approx_eq!(f64, subject.y, 20.2));
+    }
+
+    #[test]
+    #[should_panic(expected = "'ellipse' is required 'x' option")]
+    fn x_is_blank() {
+        let s = "---
+y: 20
+color: '#AABBCC'
+rx: 15
+ry: 5
+";
+        parse!(s);
+    }
+
+    #[test]
+    #[should_panic(expected = "'ellipse' is required 'y' option")]
+    fn y_is_blank() {
+        let s = "---
+x: 10
+color: '#AABBCC'
+rx: 15
+ry: 5
+";
+        parse!(s);
+    }
+
+    #[test]
+    fn radii_are_not_blank() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+rx: 15
+ry: 5.5
+";
+        let subject = parse!(s);
+        assert!(approx_eq!(f64, subject.rx, 15.0));
+        assert!(approx_eq!(f64, subject.ry, 5.5));
+    }
+
+    #[test]
+    #[should_panic(expected = "'ellipse' is required 'rx' option")]
+    fn rx_is_blank() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+ry: 5
+";
+        parse!(s);
+    }
+
+    #[test]
+    #[should_panic(expected = "'ellipse' is required 'ry' option")]
+    fn ry_is_blank() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+rx: 15
+";
+        parse!(s);
+    }
+
+    #[test]
+    fn rotation_is_blank() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+rx: 15
+ry: 5
+";
+        let subject = parse!(s);
+        assert!(approx_eq!(f64, subject.rotation, 0.0));
+    }
+
+    #[test]
+    fn rotation_is_not_blank() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+rx: 15
+ry: 5
+rotation: 45
+";
+        let subject = parse!(s);
+        assert!(approx_eq!(f64, subject.rotation, 45.0));
+    }
+
+    #[test]
+    fn fill_is_true() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+fill: true
+rx: 15
+ry: 5
+";
+        let subject = parse!(s);
+        assert!(subject.fill)
+    }
+
+    #[test]
+    fn fill_is_blank() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+rx: 15
+ry: 5
+";
+        let subject = parse!(s);
+        assert!(!subject.fill)
+    }
+
+    #[test]
+    #[should_panic(expected = "'ellipse' is required 'color' option")]
+    fn color_is_blank() {
+        let s = "---
+x: 10
+y: 20
+rx: 15
+ry: 5
+";
+        parse!(s);
+    }
+
+    #[test]
+    fn color_and_alpha() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+alpha: 0.5
+rx: 15
+ry: 5
+";
+        let subject = parse!(s);
+        assert_eq!(subject.color.r, 170);
+        assert_eq!(subject.color.g, 187);
+        assert_eq!(subject.color.b, 204);
+        assert!(approx_eq!(f32, subject.color.a, 0.5));
+    }
+
+    #[test]
+    fn stroke_is_blank() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+rx: 15
+ry: 5
+";
+        let subject = parse!(s);
+        assert!(approx_eq!(f64, subject.stroke.width, 1.0));
+        assert_eq!(subject.stroke.cap, LineCap::Butt);
+    }
+
+    #[test]
+    fn stroke_with_cap() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+stroke:
+  width: 2
+  cap: round
+rx: 15
+ry: 5
+";
+        let subject = parse!(s);
+        assert!(approx_eq!(f64, subject.stroke.width, 2.0));
+        assert_eq!(subject.stroke.cap, LineCap::Round);
+    }
+}
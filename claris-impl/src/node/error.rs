@@ -1,5 +1,7 @@
 use std::fmt;
 
+use crate::source::Segment;
+
 #[derive(Debug)]
 pub enum NodeError {
     Required(String, String),
@@ -10,6 +12,24 @@ pub enum NodeError {
     UnknownLayer(String),
     InvalidVertex,
     InvalidPoint,
+    InvalidPath,
+    InvalidVariable,
+    InvalidExpression(String, String),
+    UnknownComponent(String),
+    RecursiveComponent(String),
+    UnknownParameter(String, String),
+    InvalidRepeat,
+    InvalidStyle,
+    UnknownClass(String),
+    InvalidTheme,
+    UnknownTheme(String),
+    UnusedComponent(String),
+    UnknownOption(String, String, Option<String>),
+    InvalidValue(String, String, String),
+    TooSmall(String, String, u32),
+    TooLarge(String, String, u32),
+    TooManyInstances(String, u32),
+    At(Vec<Segment>, Box<NodeError>),
 }
 
 impl fmt::Display for NodeError {
@@ -25,6 +45,90 @@ impl fmt::Display for NodeError {
             NodeError::UnknownLayer(x) => f.write_fmt(format_args!("unknown layer type '{}'", x)),
             NodeError::InvalidVertex => f.write_str("invalid vertex"),
             NodeError::InvalidPoint => f.write_str("invalid point"),
+            NodeError::InvalidPath => f.write_str("invalid path"),
+            NodeError::InvalidVariable => f.write_str("invalid variable"),
+            NodeError::InvalidExpression(x, y) => {
+                f.write_fmt(format_args!("invalid expression '{}': {}", x, y))
+            }
+            NodeError::UnknownComponent(x) => {
+                f.write_fmt(format_args!("unknown component '{}'", x))
+            }
+            NodeError::RecursiveComponent(x) => {
+                f.write_fmt(format_args!("component '{}' uses itself", x))
+            }
+            NodeError::UnknownParameter(x, y) => {
+                f.write_fmt(format_args!("component '{}' has no parameter '{}'", x, y))
+            }
+            NodeError::InvalidRepeat => f.write_str("invalid repeat"),
+            NodeError::InvalidStyle => f.write_str("invalid style"),
+            NodeError::UnknownClass(x) => f.write_fmt(format_args!("unknown class '{}'", x)),
+            NodeError::InvalidTheme => f.write_str("invalid theme"),
+            NodeError::UnknownTheme(x) => f.write_fmt(format_args!("unknown theme '{}'", x)),
+            NodeError::UnusedComponent(x) => {
+                f.write_fmt(format_args!("component '{}' is never used", x))
+            }
+            NodeError::UnknownOption(x, y, None) => {
+                f.write_fmt(format_args!("'{}' has no option '{}'", x, y))
+            }
+            NodeError::UnknownOption(x, y, Some(z)) => f.write_fmt(format_args!(
+                "'{}' has no option '{}', did you mean '{}'?",
+                x, y, z
+            )),
+            NodeError::InvalidValue(x, y, z) => f.write_fmt(format_args!(
+                "invalid '{}' value '{}', expected {}",
+                x, y, z
+            )),
+            NodeError::TooSmall(x, y, z) => f.write_fmt(format_args!(
+                "'{}' option '{}' must be at least {}",
+                x, y, z
+            )),
+            NodeError::TooLarge(x, y, z) => {
+                f.write_fmt(format_args!("'{}' option '{}' must be at most {}", x, y, z))
+            }
+            NodeError::TooManyInstances(x, y) => f.write_fmt(format_args!(
+                "component '{}' makes more than {} instances of repeats and components",
+                x, y
+            )),
+            NodeError::At(_, e) => e.fmt(f),
+        }
+    }
+}
+
+impl NodeError {
+    /// Records the path of the node that failed. The innermost path is kept.
+    pub fn at(self, path: &[Segment]) -> NodeError {
+        match self {
+            NodeError::At(_, _) => self,
+            _ => NodeError::At(path.to_vec(), Box::new(self)),
+        }
+    }
+
+    /// Returns the recorded path and the error itself.
+    pub fn location(&self) -> (&[Segment], &NodeError) {
+        match self {
+            NodeError::At(x, e) => (x, e),
+            _ => (&[], self),
+        }
+    }
+
+    /// Returns the value the error is about, to be found in the source.
+    pub fn subject(&self) -> Option<&str> {
+        match self {
+            NodeError::InvalidColor(x)
+            | NodeError::UnknownLayer(x)
+            | NodeError::InvalidExpression(x, _)
+            | NodeError::UnknownComponent(x)
+            | NodeError::RecursiveComponent(x)
+            | NodeError::TooManyInstances(x, _)
+            | NodeError::UnknownParameter(_, x)
+            | NodeError::UnknownClass(x)
+            | NodeError::UnknownTheme(x)
+            | NodeError::UnusedComponent(x)
+            | NodeError::InvalidValue(_, x, _)
+            | NodeError::TooSmall(_, x, _)
+            | NodeError::TooLarge(_, x, _) => Some(x),
+            NodeError::At(_, e) => e.subject(),
+            _ => None,
         }
     }
 }
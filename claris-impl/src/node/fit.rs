@@ -0,0 +1,156 @@
+use crate::ext::YamlExt;
+use crate::node::error::NodeError;
+use yaml_rust::yaml::Hash;
+
+/// A box that a text layer's font size is chosen to fill.
+#[derive(Debug, Clone, Copy)]
+pub struct Fit {
+    pub width: f64,
+    pub height: f64,
+    pub min_size: f64,
+    pub max_size: f64,
+    pub wrap: bool,
+}
+
+impl Fit {
+    const DEFAULT_MIN_SIZE: f64 = 1.0;
+
+    pub fn parse(src: &Hash) -> Result<Fit, NodeError> {
+        let width = src
+            .f64_val("width")
+            .ok_or_else(|| NodeError::Required("fit".to_string(), "width".to_string()))?;
+        let height = src
+            .f64_val("height")
+            .ok_or_else(|| NodeError::Required("fit".to_string(), "height".to_string()))?;
+        let min_size = src.f64_val("min_size").unwrap_or(Self::DEFAULT_MIN_SIZE);
+        let max_size = src.f64_val("max_size").unwrap_or(height);
+        let wrap = src.bool_val("wrap").unwrap_or(false);
+        for (key, size) in [("min_size", min_size), ("max_size", max_size)].iter() {
+            if *size <= 0.0 || !size.is_finite() {
+                return Err(NodeError::InvalidValue(
+                    key.to_string(),
+                    size.to_string(),
+                    "a positive number".to_string(),
+                ));
+            }
+        }
+        if min_size > max_size {
+            return Err(NodeError::InvalidValue(
+                "min_size".to_string(),
+                min_size.to_string(),
+                format!("at most the 'max_size' {}", max_size),
+            ));
+        }
+
+        Ok(Fit {
+            width,
+            height,
+            min_size,
+            max_size,
+            wrap,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Fit;
+    use crate::parse_yaml;
+    use float_cmp::approx_eq;
+    use yaml_rust::Yaml;
+    use yaml_rust::YamlLoader;
+
+    macro_rules! parse {
+        ($x:expr) => {{
+            let src = parse_yaml!($x);
+            match src {
+                Yaml::Hash(x) => match Fit::parse(&x) {
+                    Ok(x) => x,
+                    Err(e) => panic!(e.to_string()),
+                },
+                _ => panic!("invalid yaml"),
+            }
+        }};
+    }
+
+    #[test]
+    fn sizes_are_blank() {
+        let s = "---
+width: 200
+height: 50
+        ";
+        let subject = parse!(s);
+        assert!(approx_eq!(f64, subject.width, 200.0));
+        assert!(approx_eq!(f64, subject.height, 50.0));
+        assert!(approx_eq!(f64, subject.min_size, 1.0));
+        assert!(approx_eq!(f64, subject.max_size, 50.0));
+        assert!(!subject.wrap);
+    }
+
+    #[test]
+    fn sizes_are_not_blank() {
+        let s = "---
+width: 200
+height: 50
+min_size: 8
+max_size: 32.5
+wrap: true
+        ";
+        let subject = parse!(s);
+        assert!(approx_eq!(f64, subject.min_size, 8.0));
+        assert!(approx_eq!(f64, subject.max_size, 32.5));
+        assert!(subject.wrap);
+    }
+
+    #[test]
+    #[should_panic(expected = "'fit' is required 'width' option")]
+    fn width_is_blank() {
+        let s = "---
+height: 50
+        ";
+        parse!(s);
+    }
+
+    #[test]
+    #[should_panic(expected = "'fit' is required 'height' option")]
+    fn height_is_blank() {
+        let s = "---
+width: 200
+        ";
+        parse!(s);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid 'min_size' value '0', expected a positive number")]
+    fn min_size_is_not_positive() {
+        let s = "---
+width: 200
+height: 50
+min_size: 0
+        ";
+        parse!(s);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid 'max_size' value '-4', expected a positive number")]
+    fn max_size_is_not_positive() {
+        let s = "---
+width: 200
+height: 50
+max_size: -4
+        ";
+        parse!(s);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid 'min_size' value '40', expected at most the 'max_size' 32")]
+    fn min_size_is_larger_than_max_size() {
+        let s = "---
+width: 200
+height: 50
+min_size: 40
+max_size: 32
+        ";
+        parse!(s);
+    }
+}
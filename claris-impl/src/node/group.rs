@@ -0,0 +1,9 @@
+use crate::node::Layer;
+
+/// Layers drawn together, offset by `x` and `y`.
+#[derive(Debug, Clone)]
+pub struct Group {
+    pub x: f64,
+    pub y: f64,
+    pub layers: Vec<Layer>,
+}
@@ -1,9 +1,13 @@
 use crate::node::Arc;
 use crate::node::Circle;
 use crate::node::Curve;
+use crate::node::Ellipse;
+use crate::node::Group;
 use crate::node::Line;
 use crate::node::Polygon;
 use crate::node::Rectangle;
+use crate::node::RegularPolygon;
+use crate::node::Star;
 use crate::node::Text;
 use crate::node::Triangle;
 
@@ -11,10 +15,14 @@ use crate::node::Triangle;
 pub enum Layer {
     Rectangle(Rectangle),
     Circle(Circle),
+    Ellipse(Ellipse),
     Arc(Arc),
     Triangle(Triangle),
     Polygon(Polygon),
     Line(Line),
     Curve(Curve),
-    Text(Text),
+    Text(Box<Text>),
+    RegularPolygon(RegularPolygon),
+    Star(Star),
+    Group(Group),
 }
@@ -1,30 +1,65 @@
 mod arc;
+mod assist;
+mod background;
 mod circle;
 mod color;
+mod component;
+mod count;
 mod curve;
+mod de;
+mod diagnostic;
+mod ellipse;
 mod error;
+mod fit;
+mod group;
 mod layer;
 mod line;
+mod outline;
 mod point;
 mod polygon;
 mod rectangle;
+mod regular_polygon;
+mod repeat;
 mod root;
 mod scale;
+mod schema;
+mod ser;
+mod span;
+mod star;
 mod stroke;
+mod style;
 mod text;
+mod text_path;
 pub mod triangle;
 
-pub use arc::Arc;
+pub use arc::{Arc, ArcClose, ArcDirection};
+pub use assist::{describe, keys, values, Step, Suggestion, SuggestionKind};
+pub use background::Background;
 pub use circle::Circle;
 pub use color::Color;
+pub use component::Component;
 pub use curve::Curve;
+pub use diagnostic::{Diagnostic, Severity};
+pub use ellipse::Ellipse;
+pub use error::NodeError;
+pub use fit::Fit;
+pub use group::Group;
 pub use layer::Layer;
 pub use line::Line;
+pub use outline::Outline;
 pub use point::Point;
 pub use polygon::Polygon;
 pub use rectangle::Rectangle;
+pub use regular_polygon::RegularPolygon;
+pub use repeat::Repeat;
 pub use root::Root;
 pub use scale::Scale;
+pub use schema::json_schema;
+pub use ser::to_canonical_yaml;
+pub use span::Span;
+pub use star::Star;
 pub use stroke::Stroke;
-pub use text::Text;
+pub use style::Styles;
+pub use text::{Text, TextDirection};
+pub use text_path::{TextAlign, TextPath};
 pub use triangle::Triangle;
@@ -0,0 +1,90 @@
+use crate::ext::YamlExt;
+use crate::node::color::Color;
+use crate::node::error::NodeError;
+use yaml_rust::yaml::Hash;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Outline {
+    pub color: Color,
+    pub width: f64,
+}
+
+impl Outline {
+    const DEFAULT_WIDTH: f64 = 1.0;
+
+    pub fn parse(src: &Hash) -> Result<Outline, NodeError> {
+        let width = src.f64_val("width").unwrap_or(Self::DEFAULT_WIDTH);
+        let alpha = src.f32_val("alpha").unwrap_or(1.0);
+        let color = src
+            .string_val("color")
+            .ok_or_else(|| NodeError::Required("outline".to_string(), "color".to_string()))
+            .and_then(|x| -> Result<Color, NodeError> {
+                Color::parse(x).map(|c| -> Color { Color::new(c.r, c.g, c.b, c.a * alpha) })
+            })?;
+
+        Ok(Outline { color, width })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Outline;
+    use crate::parse_yaml;
+    use float_cmp::approx_eq;
+    use yaml_rust::Yaml;
+    use yaml_rust::YamlLoader;
+
+    macro_rules! parse {
+        ($x:expr) => {{
+            let src = parse_yaml!($x);
+            match src {
+                Yaml::Hash(x) => match Outline::parse(&x) {
+                    Ok(x) => x,
+                    Err(e) => panic!(e.to_string()),
+                },
+                _ => panic!("invalid yaml"),
+            }
+        }};
+    }
+
+    #[test]
+    fn width_is_blank() {
+        let s = "---
+color: '#AABBCC'
+        ";
+        let subject = parse!(s);
+        assert!(approx_eq!(f64, subject.width, 1.0));
+    }
+
+    #[test]
+    fn width_is_not_blank() {
+        let s = "---
+color: '#AABBCC'
+width: 2.5
+        ";
+        let subject = parse!(s);
+        assert!(approx_eq!(f64, subject.width, 2.5));
+    }
+
+    #[test]
+    fn color_and_alpha() {
+        let s = "---
+color: '#AABBCC'
+alpha: 0.5
+        ";
+        let subject = parse!(s);
+        assert_eq!(subject.color.r, 170);
+        assert_eq!(subject.color.g, 187);
+        assert_eq!(subject.color.b, 204);
+        assert!(approx_eq!(f32, subject.color.a, 0.5));
+    }
+
+    #[test]
+    #[should_panic(expected = "'outline' is required 'color' option")]
+    fn color_is_blank() {
+        let s = "---
+width: 2.5
+        ";
+        parse!(s);
+    }
+}
@@ -25,7 +25,7 @@ impl Polygon {
             .ok_or_else(|| NodeError::Required("polygon".to_string(), "color".to_string()))
             .and_then(|x| -> Result<Color, NodeError> {
                 Color::parse(x).and_then(|c| -> Result<Color, NodeError> {
-                    Ok(Color::new(c.r, c.g, c.b, alpha))
+                    Ok(Color::new(c.r, c.g, c.b, c.a * alpha))
                 })
             })?;
         let stroke = src
@@ -0,0 +1,253 @@
+use std::f64::consts::PI;
+use yaml_rust::Yaml;
+
+use crate::ext::YamlExt;
+use crate::node::color::Color;
+use crate::node::count::parse_count;
+use crate::node::error::NodeError;
+use crate::node::point::Point;
+use crate::node::scale::Scale;
+use crate::node::stroke::Stroke;
+
+#[derive(Debug, Clone, Copy)]
+pub struct RegularPolygon {
+    pub x: f64,
+    pub y: f64,
+    pub radius: f64,
+    pub sides: u32,
+    pub rotation: f64,
+    pub fill: bool,
+    pub color: Color,
+    pub stroke: Stroke,
+    pub scale: Scale,
+}
+
+impl RegularPolygon {
+    const MIN_SIDES: u32 = 3;
+    const MAX_SIDES: u32 = 10_000;
+
+    pub fn parse(src: &Yaml) -> Result<RegularPolygon, NodeError> {
+        let x = src
+            .f64_val("x")
+            .ok_or_else(|| NodeError::Required("regular_polygon".to_string(), "x".to_string()))?;
+        let y = src
+            .f64_val("y")
+            .ok_or_else(|| NodeError::Required("regular_polygon".to_string(), "y".to_string()))?;
+        let radius = src.f64_val("radius").ok_or_else(|| {
+            NodeError::Required("regular_polygon".to_string(), "radius".to_string())
+        })?;
+        let sides = parse_count(
+            src,
+            "regular_polygon",
+            "sides",
+            Self::MIN_SIDES,
+            Self::MAX_SIDES,
+        )?
+        .ok_or_else(|| NodeError::Required("regular_polygon".to_string(), "sides".to_string()))?;
+        let rotation = src.f64_val("rotation").unwrap_or(0.0);
+        let fill = src.bool_val("fill").unwrap_or(false);
+        let alpha = src.f32_val("alpha").unwrap_or(1.0);
+        let color = src
+            .string_val("color")
+            .ok_or_else(|| NodeError::Required("regular_polygon".to_string(), "color".to_string()))
+            .and_then(|x| -> Result<Color, NodeError> {
+                Color::parse(x).map(|c| -> Color { Color::new(c.r, c.g, c.b, c.a * alpha) })
+            })?;
+        let stroke = src
+            .hash_val("stroke")
+            .map_or(Stroke::default(), |x| -> Stroke { Stroke::parse(x) });
+        let scale = src
+            .hash_val("scale")
+            .map_or(Scale::default(), |x| -> Scale { Scale::parse(x) });
+
+        Ok(RegularPolygon {
+            x,
+            y,
+            radius,
+            sides,
+            rotation,
+            fill,
+            color,
+            stroke,
+            scale,
+        })
+    }
+
+    /// Vertices relative to the centre, starting at the top and going clockwise.
+    pub fn vertex(&self) -> Vec<Point> {
+        let offset = self.rotation * (PI / 180.0) - PI / 2.0;
+        let step = 2.0 * PI / self.sides as f64;
+        (0..self.sides)
+            .map(|i| -> Point {
+                let angle = offset + step * i as f64;
+                Point {
+                    x: self.radius * angle.cos(),
+                    y: self.radius * angle.sin(),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RegularPolygon;
+    use crate::parse_yaml;
+    use float_cmp::approx_eq;
+    use yaml_rust::YamlLoader;
+
+    macro_rules! parse {
+        ($x:expr) => {{
+            let src = parse_yaml!($x);
+            match RegularPolygon::parse(&src) {
+                Ok(x) => x,
+                Err(e) => panic!(e.to_string()),
+            }
+        }};
+    }
+
+    #[test]
+    fn parse_x_y() {
+        let s = "---
+x: 10
+y: 20.5
+color: '#AABBCC'
+radius: 15
+sides: 6
+";
+        let subject = parse!(s);
+        assert!(approx_eq!(f64, subject.x, 10.0));
+        assert!(approx_eq!(f64, subject.y, 20.5));
+    }
+
+    #[test]
+    #[should_panic(expected = "'regular_polygon' is required 'x' option")]
+    fn x_is_blank() {
+        let s = "---
+y: 20
+color: '#AABBCC'
+radius: 15
+sides: 6
+";
+        parse!(s);
+    }
+
+    #[test]
+    #[should_panic(expected = "'regular_polygon' is required 'y' option")]
+    fn y_is_blank() {
+        let s = "---
+x: 10
+color: '#AABBCC'
+radius: 15
+sides: 6
+";
+        parse!(s);
+    }
+
+    #[test]
+    #[should_panic(expected = "'regular_polygon' is required 'radius' option")]
+    fn radius_is_blank() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+sides: 6
+";
+        parse!(s);
+    }
+
+    #[test]
+    fn sides_is_not_blank() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+radius: 15
+sides: 6
+";
+        let subject = parse!(s);
+        assert_eq!(subject.sides, 6);
+    }
+
+    #[test]
+    #[should_panic(expected = "'regular_polygon' is required 'sides' option")]
+    fn sides_is_blank() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+radius: 15
+";
+        parse!(s);
+    }
+
+    #[test]
+    #[should_panic(expected = "'regular_polygon' option 'sides' must be at least 3")]
+    fn sides_is_too_few() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+radius: 15
+sides: 2
+";
+        parse!(s);
+    }
+
+    #[test]
+    fn rotation_is_blank() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+radius: 15
+sides: 6
+";
+        let subject = parse!(s);
+        assert!(approx_eq!(f64, subject.rotation, 0.0));
+    }
+
+    #[test]
+    #[should_panic(expected = "'regular_polygon' is required 'color' option")]
+    fn color_is_blank() {
+        let s = "---
+x: 10
+y: 20
+radius: 15
+sides: 6
+";
+        parse!(s);
+    }
+
+    #[test]
+    fn vertex() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+radius: 10
+sides: 4
+";
+        let subject = parse!(s).vertex();
+        assert_eq!(subject.len(), 4);
+        assert!(approx_eq!(f64, subject[0].x, 0.0, epsilon = 1e-9));
+        assert!(approx_eq!(f64, subject[0].y, -10.0, epsilon = 1e-9));
+        assert!(approx_eq!(f64, subject[1].x, 10.0, epsilon = 1e-9));
+        assert!(approx_eq!(f64, subject[1].y, 0.0, epsilon = 1e-9));
+    }
+
+    #[test]
+    fn vertex_with_rotation() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+radius: 10
+sides: 4
+rotation: 90
+";
+        let subject = parse!(s).vertex();
+        assert!(approx_eq!(f64, subject[0].x, 10.0, epsilon = 1e-9));
+        assert!(approx_eq!(f64, subject[0].y, 0.0, epsilon = 1e-9));
+    }
+}
@@ -0,0 +1,208 @@
+use yaml_rust::Yaml;
+
+use crate::ext::YamlExt;
+use crate::node::count::parse_count;
+use crate::node::error::NodeError;
+
+/// Placement of the instances of a `repeat` layer. Its child layers are kept
+/// unresolved by the parser so each instance can see its own index.
+#[derive(Debug, Clone)]
+pub struct Repeat {
+    pub x: f64,
+    pub y: f64,
+    pub rows: u32,
+    pub cols: u32,
+    pub dx: f64,
+    pub dy: f64,
+    pub index: String,
+    grid: bool,
+}
+
+/// One instance of a `repeat` layer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cell {
+    pub index: u32,
+    pub row: u32,
+    pub col: u32,
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Repeat {
+    /// Instances drawn by the `repeat` layers and components of one document
+    /// at most, nested ones included.
+    pub const MAX_CELLS: u32 = 100_000;
+
+    pub fn parse(src: &Yaml) -> Result<Repeat, NodeError> {
+        let count = parse_count(src, "repeat", "count", 0, Self::MAX_CELLS)?;
+        let rows = parse_count(src, "repeat", "rows", 0, Self::MAX_CELLS)?;
+        let cols = parse_count(src, "repeat", "cols", 0, Self::MAX_CELLS)?;
+        let grid = rows.is_some() || cols.is_some();
+        let (rows, cols) = match count {
+            Some(x) if !grid => (1, x),
+            None if grid => (rows.unwrap_or(1), cols.unwrap_or(1)),
+            Some(_) => return Err(NodeError::InvalidRepeat),
+            None => {
+                return Err(NodeError::Required(
+                    "repeat".to_string(),
+                    "count".to_string(),
+                ))
+            }
+        };
+        match rows.checked_mul(cols) {
+            Some(x) if x <= Self::MAX_CELLS => {}
+            _ => {
+                return Err(NodeError::TooLarge(
+                    "repeat".to_string(),
+                    "cols".to_string(),
+                    Self::MAX_CELLS / rows,
+                ))
+            }
+        }
+        let x = src.f64_val("x").unwrap_or(0.0);
+        let y = src.f64_val("y").unwrap_or(0.0);
+        let dx = src.f64_val("dx").unwrap_or(0.0);
+        let dy = src.f64_val("dy").unwrap_or(0.0);
+        let index = src.string_val("index").unwrap_or_else(|| "i".to_string());
+
+        Ok(Repeat {
+            x,
+            y,
+            rows,
+            cols,
+            dx,
+            dy,
+            index,
+            grid,
+        })
+    }
+
+    /// The error for a repeat whose instances would exceed the `max` left
+    /// to the document.
+    pub fn too_many(&self, max: u32) -> NodeError {
+        let key = if self.grid { "cols" } else { "count" };
+        NodeError::TooLarge("repeat".to_string(), key.to_string(), max)
+    }
+
+    /// Returns every instance in row-major order. A `count` steps each
+    /// instance by both `dx` and `dy`; a grid steps columns by `dx` and rows
+    /// by `dy`.
+    pub fn cells(&self) -> Vec<Cell> {
+        let mut ret = Vec::new();
+        for row in 0..self.rows {
+            for col in 0..self.cols {
+                let index = row * self.cols + col;
+                let (sx, sy) = if self.grid {
+                    (col as f64, row as f64)
+                } else {
+                    (index as f64, index as f64)
+                };
+                ret.push(Cell {
+                    index,
+                    row,
+                    col,
+                    x: self.x + sx * self.dx,
+                    y: self.y + sy * self.dy,
+                });
+            }
+        }
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cell, Repeat};
+    use crate::parse_yaml;
+    use float_cmp::approx_eq;
+    use yaml_rust::YamlLoader;
+
+    macro_rules! parse {
+        ($x:expr) => {{
+            let src = parse_yaml!($x);
+            match Repeat::parse(&src) {
+                Ok(x) => x,
+                Err(e) => panic!(e.to_string()),
+            }
+        }};
+    }
+
+    #[test]
+    fn count() {
+        let s = "---
+count: 3
+x: 5
+dx: 10
+dy: 2
+";
+        let subject = parse!(s);
+        assert_eq!(subject.rows, 1);
+        assert_eq!(subject.cols, 3);
+        assert_eq!(subject.index, "i");
+        let cells = subject.cells();
+        assert_eq!(cells.len(), 3);
+        assert!(approx_eq!(f64, cells[2].x, 25.0));
+        assert!(approx_eq!(f64, cells[2].y, 4.0));
+    }
+
+    #[test]
+    fn grid() {
+        let s = "---
+rows: 2
+cols: 3
+dx: 10
+dy: 20
+index: n
+";
+        let subject = parse!(s);
+        assert_eq!(subject.index, "n");
+        let cells = subject.cells();
+        assert_eq!(cells.len(), 6);
+        assert_eq!(
+            cells[4],
+            Cell {
+                index: 4,
+                row: 1,
+                col: 1,
+                x: 10.0,
+                y: 20.0,
+            }
+        );
+    }
+
+    #[test]
+    fn zero_count() {
+        let subject = parse!("---\ncount: 0\n");
+        assert!(subject.cells().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "'repeat' is required 'count' option")]
+    fn count_is_blank() {
+        parse!("---\ndx: 10\n");
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid repeat")]
+    fn count_and_grid() {
+        parse!("---\ncount: 2\nrows: 2\n");
+    }
+
+    #[test]
+    #[should_panic(expected = "'repeat' option 'count' must be at least 0")]
+    fn negative_count() {
+        parse!("---\ncount: -1\n");
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid 'count' value '2.5', expected a whole number")]
+    fn fractional_count() {
+        parse!("---\ncount: 2.5\n");
+    }
+
+    #[test]
+    #[should_panic(expected = "'repeat' option 'cols' must be at most 1")]
+    fn too_many_cells() {
+        parse!("---\nrows: 100000\ncols: 100000\n");
+    }
+}
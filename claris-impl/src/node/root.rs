@@ -1,18 +1,32 @@
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+
 use yaml_rust::yaml::Hash;
 use yaml_rust::Yaml;
 
+use crate::expr::{Scope, Value};
 use crate::ext::YamlExt;
 use crate::node::color::Color;
 use crate::node::error::NodeError;
+use crate::node::schema;
 use crate::node::Arc;
 use crate::node::Circle;
+use crate::node::Component;
 use crate::node::Curve;
+use crate::node::Diagnostic;
+use crate::node::Ellipse;
+use crate::node::Group;
 use crate::node::Layer;
 use crate::node::Line;
 use crate::node::Polygon;
 use crate::node::Rectangle;
+use crate::node::RegularPolygon;
+use crate::node::Repeat;
+use crate::node::Star;
+use crate::node::Styles;
 use crate::node::Text;
 use crate::node::Triangle;
+use crate::source::Segment;
 
 #[derive(Debug, Clone)]
 pub struct Root {
@@ -22,24 +36,187 @@ pub struct Root {
     pub layers: Vec<Layer>,
 }
 
+// State shared by the layers of one level of nesting.
+struct Env<'a> {
+    scope: Scope,
+    defs: &'a HashMap<String, Component>,
+    styles: &'a Styles,
+    stack: Vec<String>,
+    // Where the layers are written in the source.
+    path: Vec<Segment>,
+    report: Option<&'a RefCell<Report>>,
+    // Instances of repeats and components made so far in the document.
+    instances: &'a Cell<u32>,
+}
+
+// Problems collected by `Root::validate` instead of stopping at the first.
+#[derive(Default)]
+struct Report {
+    diagnostics: Vec<Diagnostic>,
+    used: HashSet<String>,
+}
+
+impl Report {
+    // Layers of repeats and components are parsed more than once, so the
+    // same problem is kept once.
+    fn push(&mut self, diagnostic: Diagnostic) {
+        let message = diagnostic.error.to_string();
+        let path = diagnostic.error.location().0;
+        if !self
+            .diagnostics
+            .iter()
+            .any(|x| x.error.location().0 == path && x.error.to_string() == message)
+        {
+            self.diagnostics.push(diagnostic);
+        }
+    }
+}
+
+// Records the error while validating instead of returning it.
+fn check<T>(
+    report: Option<&RefCell<Report>>,
+    ret: Result<T, NodeError>,
+) -> Result<Option<T>, NodeError> {
+    match (ret, report) {
+        (Ok(x), _) => Ok(Some(x)),
+        (Err(e), Some(report)) => {
+            report.borrow_mut().push(Diagnostic::error(e));
+            Ok(None)
+        }
+        (Err(e), None) => Err(e),
+    }
+}
+
+// Unknown options, invalid option values and invalid colors are errors in
+// strict mode and warnings while validating, and are ignored otherwise.
+fn lint(
+    report: Option<&RefCell<Report>>,
+    strict: bool,
+    issues: Vec<NodeError>,
+) -> Result<(), NodeError> {
+    for e in issues {
+        match report {
+            Some(report) if strict => report.borrow_mut().push(Diagnostic::error(e)),
+            Some(report) => report.borrow_mut().push(Diagnostic::warning(e)),
+            None if strict => return Err(e),
+            None => {}
+        }
+    }
+    Ok(())
+}
+
+// Runs `schema::check` on `src` at `path` when its result is used.
+fn lint_options(
+    report: Option<&RefCell<Report>>,
+    strict: bool,
+    owner: &str,
+    fields: &[schema::Field],
+    raw: &Yaml,
+    src: &Yaml,
+    path: &[Segment],
+) -> Result<(), NodeError> {
+    if report.is_none() && !strict {
+        return Ok(());
+    }
+    let src = match src.as_hash() {
+        Some(x) => x,
+        None => return Ok(()),
+    };
+    let issues = schema::check(owner, fields, raw.as_hash(), src)
+        .into_iter()
+        .map(|(inner, e)| {
+            let mut path = path.to_vec();
+            path.extend(inner);
+            e.at(&path)
+        })
+        .collect();
+    lint(report, strict, issues)
+}
+
 impl Root {
-    pub fn parse(src: &Yaml) -> Result<Root, NodeError> {
-        let width = src
-            .i32_val("width")
-            .ok_or_else(|| NodeError::Required("root node".to_string(), "width".to_string()))?;
-        let height = src
-            .i32_val("height")
-            .ok_or_else(|| NodeError::Required("root node".to_string(), "height".to_string()))?;
-        let color = src
-            .string_val("color")
-            .and_then(|x| -> Option<Color> {
-                Color::parse(x)
-                    .and_then(|c| -> Result<Color, _> { Ok(Color::new(c.r, c.g, c.b, 1.0)) })
-                    .ok()
-            })
-            .unwrap_or_else(|| Color::new(0, 0, 0, 0.0));
+    /// Parses `src` with variables given from outside the document, which
+    /// take precedence over its `vars`.
+    pub fn parse(src: &Yaml, external: &Scope) -> Result<Root, NodeError> {
+        Self::parse_with(src, external, None)
+    }
 
-        let layers = Self::parse_layers(src)?;
+    /// Parses every layer of `src` and returns all the errors found instead
+    /// of the first one, followed by warnings for components that are never
+    /// used.
+    pub fn validate(src: &Yaml, external: &Scope) -> Vec<Diagnostic> {
+        let report = RefCell::new(Report::default());
+        let ret = Self::parse_with(src, external, Some(&report));
+        let mut report = report.into_inner();
+
+        match ret {
+            Ok(_) => {
+                if let Some(defs) = src.hash_val("defs") {
+                    for name in defs.keys().filter_map(Yaml::as_str) {
+                        if !report.used.contains(name) {
+                            let e = NodeError::UnusedComponent(name.to_string());
+                            report.push(Diagnostic::warning(e.at(&[key("defs"), key(name)])));
+                        }
+                    }
+                }
+            }
+            Err(e) => report.push(Diagnostic::error(e)),
+        }
+
+        report.diagnostics
+    }
+
+    fn parse_with(
+        src: &Yaml,
+        external: &Scope,
+        report: Option<&RefCell<Report>>,
+    ) -> Result<Root, NodeError> {
+        let scope = Self::parse_scope(src, external)?;
+        let strict = scope.strict();
+        lint_options(report, strict, "root node", schema::ROOT, src, src, &[])?;
+        let width = scope
+            .get("width")
+            .and_then(Value::as_f64)
+            .map(|x| x as i32)
+            .ok_or_else(|| NodeError::Required("root node".to_string(), "width".to_string()));
+        let width = check(report, width)?.unwrap_or(0);
+        let height = scope
+            .get("height")
+            .and_then(Value::as_f64)
+            .map(|x| x as i32)
+            .ok_or_else(|| NodeError::Required("root node".to_string(), "height".to_string()));
+        let height = check(report, height)?.unwrap_or(0);
+        let color = match scope.resolve(&src["color"])?.as_str() {
+            Some(x) => match Color::parse(x.to_string()) {
+                Ok(c) => Some(Color::new(c.r, c.g, c.b, 1.0)),
+                Err(e) => {
+                    lint(report, strict, vec![e.at(&[key("color")])])?;
+                    None
+                }
+            },
+            None => None,
+        }
+        .unwrap_or_else(|| Color::new(0, 0, 0, 0.0));
+
+        let defs = Self::parse_defs(src, report)?;
+        let styles = Styles::parse(src).map_err(|e| e.at(&[key("styles")]));
+        let styles = check(report, styles)?.unwrap_or_default();
+        let instances = Cell::new(0);
+        let env = Env {
+            scope,
+            defs: &defs,
+            styles: &styles,
+            stack: Vec::new(),
+            path: vec![key("layers")],
+            report,
+            instances: &instances,
+        };
+        let layers = src
+            .array_val("layers")
+            .ok_or_else(|| NodeError::Required("root node".to_string(), "layers".to_string()));
+        let layers = match check(report, layers)? {
+            Some(x) => Self::parse_layers(x, &env)?,
+            None => Vec::new(),
+        };
 
         Ok(Root {
             width,
@@ -49,27 +226,100 @@ impl Root {
         })
     }
 
-    fn parse_layers(src: &Yaml) -> Result<Vec<Layer>, NodeError> {
-        let mut ret = Vec::new();
+    // Literal canvas sizes are visible to `vars`, and `vars` are visible to
+    // canvas sizes written as expressions.
+    fn parse_scope(src: &Yaml, external: &Scope) -> Result<Scope, NodeError> {
+        let mut scope = external.clone();
+        for key in &["width", "height"] {
+            if let Some(x) = src.i32_val(key) {
+                scope.set(key, Value::Number(x as f64));
+            }
+        }
+        if let Some(vars) = src.hash_val("vars") {
+            scope
+                .define_defaults(vars)
+                .map_err(|e| e.at(&[key("vars")]))?;
+        }
+        Self::parse_theme(src, &mut scope).map_err(|e| e.at(&[key("themes")]))?;
+        for key in &["width", "height"] {
+            if let Yaml::String(_) = src[*key] {
+                let value = scope.value(&src[*key])?;
+                scope.set(key, value);
+            }
+        }
+        Ok(scope)
+    }
 
-        let layers = src
-            .array_val("layers")
-            .ok_or_else(|| NodeError::Required("root node".to_string(), "layers".to_string()))?;
+    // Without a selected theme the first one is used.
+    fn parse_theme(src: &Yaml, scope: &mut Scope) -> Result<(), NodeError> {
+        let themes = match src.hash_val("themes") {
+            Some(x) => x,
+            None => {
+                return match scope.theme() {
+                    Some(x) => Err(NodeError::UnknownTheme(x.to_string())),
+                    None => Ok(()),
+                }
+            }
+        };
+        let palette = match scope.theme() {
+            Some(x) => themes
+                .get(&Yaml::from_str(x))
+                .ok_or_else(|| NodeError::UnknownTheme(x.to_string()))?,
+            None => match themes.iter().next() {
+                Some((_, x)) => x,
+                None => return Ok(()),
+            },
+        };
+        let palette = palette.as_hash().ok_or(NodeError::InvalidTheme)?;
+        for (key, value) in palette {
+            let name = key.as_str().ok_or(NodeError::InvalidTheme)?;
+            match scope.value(value)? {
+                Value::Str(x) => scope.set_color(name, &x),
+                _ => return Err(NodeError::InvalidTheme),
+            }
+        }
+        Ok(())
+    }
 
-        for layer in layers {
-            match layer {
-                Yaml::Hash(x) => Self::parse_layer(x).and_then(|x| -> Result<(), NodeError> {
-                    ret.push(x);
-                    Ok(())
-                })?,
-                _ => return Err(NodeError::InvalidLayer),
+    fn parse_defs(
+        src: &Yaml,
+        report: Option<&RefCell<Report>>,
+    ) -> Result<HashMap<String, Component>, NodeError> {
+        let mut ret = HashMap::new();
+
+        if let Some(defs) = src.hash_val("defs") {
+            for (name, value) in defs {
+                let name = name.as_str().ok_or(NodeError::InvalidLayerDefine)?;
+                let component =
+                    Component::parse(name, value).map_err(|e| e.at(&[key("defs"), key(name)]));
+                if let Some(x) = check(report, component)? {
+                    ret.insert(name.to_string(), x);
+                }
             }
         }
 
         Ok(ret)
     }
 
-    fn parse_layer(src: &Hash) -> Result<Layer, NodeError> {
+    fn parse_layers(layers: &[Yaml], env: &Env) -> Result<Vec<Layer>, NodeError> {
+        let mut ret = Vec::new();
+
+        for (i, layer) in layers.iter().enumerate() {
+            let mut path = env.path.clone();
+            path.push(Segment::Index(i));
+            let layer = match layer {
+                Yaml::Hash(x) => Self::parse_layer(x, env, &path).map_err(|e| e.at(&path)),
+                _ => Err(NodeError::InvalidLayer.at(&path)),
+            };
+            if let Some(x) = check(env.report, layer)? {
+                ret.push(x);
+            }
+        }
+
+        Ok(ret)
+    }
+
+    fn parse_layer(src: &Hash, env: &Env, path: &[Segment]) -> Result<Layer, NodeError> {
         if src.len() != 1 {
             return Err(NodeError::InvalidLayerCount);
         }
@@ -83,6 +333,22 @@ impl Root {
         };
 
         let entry = src.entry(key).ok_or(NodeError::InvalidLayerDefine)?;
+        if key == "repeat" {
+            return Self::parse_repeat(entry, env, path).map(Layer::Group);
+        }
+        let raw = entry;
+        let fields = schema::layer(key);
+        let entry = &schema::resolve(
+            &env.scope,
+            &env.styles.apply(entry)?,
+            fields.as_deref().unwrap_or_default(),
+        )?;
+        if let Some(fields) = fields {
+            let mut path = path.to_vec();
+            path.push(Segment::Key(key.to_string()));
+            let strict = env.scope.strict();
+            lint_options(env.report, strict, key, &fields, raw, entry, &path)?;
+        }
 
         let ret = match key {
             "rectangle" => {
@@ -93,6 +359,10 @@ impl Root {
                 let circle = Circle::parse(entry)?;
                 Layer::Circle(circle)
             }
+            "ellipse" => {
+                let ellipse = Ellipse::parse(entry)?;
+                Layer::Ellipse(ellipse)
+            }
             "arc" => {
                 let arc = Arc::parse(entry)?;
                 Layer::Arc(arc)
@@ -115,27 +385,151 @@ impl Root {
             }
             "text" => {
                 let text = Text::parse(entry)?;
-                Layer::Text(text)
+                Layer::Text(Box::new(text))
+            }
+            "regular_polygon" => {
+                let regular_polygon = RegularPolygon::parse(entry)?;
+                Layer::RegularPolygon(regular_polygon)
+            }
+            "star" => {
+                let star = Star::parse(entry)?;
+                Layer::Star(star)
+            }
+            "use" => {
+                let group = Self::parse_use(entry, env)?;
+                Layer::Group(group)
             }
             _ => return Err(NodeError::UnknownLayer(key.to_string())),
         };
 
         Ok(ret)
     }
+
+    fn parse_use(src: &Yaml, env: &Env) -> Result<Group, NodeError> {
+        let name = src
+            .str_val("component")
+            .ok_or_else(|| NodeError::Required("use".to_string(), "component".to_string()))?;
+        let component = env
+            .defs
+            .get(name)
+            .ok_or_else(|| NodeError::UnknownComponent(name.to_string()))?;
+        if env.stack.iter().any(|x| x == name) {
+            return Err(NodeError::RecursiveComponent(name.to_string()));
+        }
+        if let Some(report) = env.report {
+            report.borrow_mut().used.insert(name.to_string());
+        }
+        Self::spend(env, 1)
+            .map_err(|_| NodeError::TooManyInstances(name.to_string(), Repeat::MAX_CELLS))?;
+        let x = src.f64_val("x").unwrap_or(0.0);
+        let y = src.f64_val("y").unwrap_or(0.0);
+
+        let mut stack = env.stack.clone();
+        stack.push(name.to_string());
+        let env = Env {
+            scope: component.scope(&env.scope, src.hash_val("params"))?,
+            defs: env.defs,
+            styles: env.styles,
+            stack,
+            path: vec![key("defs"), key(name), key("layers")],
+            report: env.report,
+            instances: env.instances,
+        };
+        let layers = Self::parse_layers(&component.layers, &env)?;
+
+        Ok(Group { x, y, layers })
+    }
+
+    // Only the options are resolved up front; the child layers are resolved
+    // once per cell with the index variables in scope.
+    fn parse_repeat(src: &Yaml, env: &Env, path: &[Segment]) -> Result<Group, NodeError> {
+        let mut options = src.as_hash().ok_or(NodeError::InvalidLayerDefine)?.clone();
+        let layers = options
+            .remove(&Yaml::from_str("layers"))
+            .ok_or_else(|| NodeError::Required("repeat".to_string(), "layers".to_string()))?;
+        let layers = layers
+            .as_vec()
+            .ok_or_else(|| NodeError::Required("repeat".to_string(), "layers".to_string()))?;
+        let options = schema::resolve(&env.scope, &Yaml::Hash(options), schema::REPEAT)?;
+        let mut path = path.to_vec();
+        path.push(key("repeat"));
+        let strict = env.scope.strict();
+        lint_options(
+            env.report,
+            strict,
+            "repeat",
+            schema::REPEAT,
+            src,
+            &options,
+            &path,
+        )?;
+        let repeat = Repeat::parse(&options)?;
+        Self::spend(env, repeat.rows * repeat.cols).map_err(|x| repeat.too_many(x))?;
+        path.push(key("layers"));
+
+        let mut ret = Vec::new();
+        for cell in repeat.cells() {
+            let mut scope = env.scope.clone();
+            scope.set(&repeat.index, Value::Number(cell.index as f64));
+            scope.set("row", Value::Number(cell.row as f64));
+            scope.set("col", Value::Number(cell.col as f64));
+            let env = Env {
+                scope,
+                defs: env.defs,
+                styles: env.styles,
+                stack: env.stack.clone(),
+                path: path.clone(),
+                report: env.report,
+                instances: env.instances,
+            };
+            ret.push(Layer::Group(Group {
+                x: cell.x,
+                y: cell.y,
+                layers: Self::parse_layers(layers, &env)?,
+            }));
+        }
+
+        Ok(Group {
+            x: 0.0,
+            y: 0.0,
+            layers: ret,
+        })
+    }
+}
+
+impl Root {
+    // Counts `n` more instances against those the document may make, or
+    // returns how many were left.
+    fn spend(env: &Env, n: u32) -> Result<(), u32> {
+        let used = env.instances.get();
+        match used.checked_add(n) {
+            Some(x) if x <= Repeat::MAX_CELLS => {
+                env.instances.set(x);
+                Ok(())
+            }
+            _ => Err(Repeat::MAX_CELLS - used),
+        }
+    }
+}
+
+fn key(name: &str) -> Segment {
+    Segment::Key(name.to_string())
 }
 
 #[cfg(test)]
 mod tests {
     use super::Root;
-    use crate::node::Layer;
+    use crate::expr::{Scope, Value};
+    use crate::node::{Color, Layer, Severity};
     use crate::parse_yaml;
+    use crate::source::Segment;
     use float_cmp::approx_eq;
     use yaml_rust::YamlLoader;
 
     macro_rules! parse {
         ($x:expr) => {{
             let src = parse_yaml!($x);
-            match Root::parse(&src) {
+            match Root::parse(&src, &Scope::new()) {
                 Ok(x) => x,
                 Err(e) => panic!(e.to_string()),
             }
@@ -328,6 +722,25 @@ layers:
         assert!(matches!(subject.layers[0], Layer::Curve(_)));
     }
 
+    #[test]
+    fn has_ellipse_layer() {
+        let s = "---
+width: 400
+height: 300
+color: '#AABBCC'
+layers:
+  - ellipse:
+      x: 10
+      y: 10
+      color: '#AABBCC'
+      rx: 20
+      ry: 10
+";
+        let subject = parse!(s);
+        assert_eq!(subject.layers.len(), 1);
+        assert!(matches!(subject.layers[0], Layer::Ellipse(_)));
+    }
+
     #[test]
     fn has_line_layer() {
         let s = "---
@@ -422,6 +835,45 @@ layers:
         assert!(matches!(subject.layers[0], Layer::Triangle(_)));
     }
 
+    #[test]
+    fn has_regular_polygon_layer() {
+        let s = "---
+width: 400
+height: 300
+color: '#AABBCC'
+layers:
+  - regular_polygon:
+      x: 10
+      y: 10
+      color: '#AABBCC'
+      radius: 10
+      sides: 6
+";
+        let subject = parse!(s);
+        assert_eq!(subject.layers.len(), 1);
+        assert!(matches!(subject.layers[0], Layer::RegularPolygon(_)));
+    }
+
+    #[test]
+    fn has_star_layer() {
+        let s = "---
+width: 400
+height: 300
+color: '#AABBCC'
+layers:
+  - star:
+      x: 10
+      y: 10
+      color: '#AABBCC'
+      points: 5
+      outer_radius: 20
+      inner_radius: 10
+";
+        let subject = parse!(s);
+        assert_eq!(subject.layers.len(), 1);
+        assert!(matches!(subject.layers[0], Layer::Star(_)));
+    }
+
     #[test]
     fn has_many_layer() {
         let s = "---
@@ -453,4 +905,834 @@ layers:
         assert!(matches!(subject.layers[1], Layer::Text(_)));
         assert!(matches!(subject.layers[2], Layer::Triangle(_)));
     }
+
+    #[test]
+    fn has_variables() {
+        let s = "---
+width: 400
+height: 300
+vars:
+  r: 20
+  cx: ${width / 2}
+  bg: '#AABBCC'
+color: $bg
+layers:
+  - circle:
+      x: $cx
+      y: ${height / 2 - r}
+      radius: $r * 1.5
+      color: $bg
+";
+        let subject = parse!(s);
+        assert_eq!(subject.color.r, 170);
+        match &subject.layers[0] {
+            Layer::Circle(x) => {
+                assert!(approx_eq!(f64, x.x, 200.0));
+                assert!(approx_eq!(f64, x.y, 130.0));
+                assert!(approx_eq!(f64, x.radius, 30.0));
+                assert_eq!(x.color.b, 204);
+            }
+            _ => panic!("not a circle"),
+        }
+    }
+
+    #[test]
+    fn size_is_expression() {
+        let s = "---
+vars:
+  size: 100
+width: $size * 2
+height: ${size + 50}
+layers:
+  - text:
+      x: $width - 10
+      y: 10
+      text: ${width}x${height}
+      color: '#AABBCC'
+";
+        let subject = parse!(s);
+        assert_eq!(subject.width, 200);
+        assert_eq!(subject.height, 150);
+        match &subject.layers[0] {
+            Layer::Text(x) => {
+                assert!(approx_eq!(f64, x.x, 190.0));
+                assert_eq!(x.text, "200x150");
+            }
+            _ => panic!("not a text"),
+        }
+    }
+
+    #[test]
+    fn text_is_free_text() {
+        let s = "---
+width: 400
+height: 300
+vars:
+  size: 20
+themes:
+  light: {primary: '#3366FF'}
+layers:
+  - text:
+      x: 0
+      y: 10
+      size: $size
+      text: $HOME is set
+      family: '@primary'
+      color: '@primary'
+      spans:
+        - text: $5 off ${size}
+";
+        let src = parse_yaml!(s);
+        let mut external = Scope::new();
+        external.set_theme("light");
+        let subject = Root::parse(&src, &external).unwrap();
+        match &subject.layers[0] {
+            Layer::Text(x) => {
+                assert!(approx_eq!(f64, x.size, 20.0));
+                assert_eq!(x.family, "@primary");
+                assert_eq!((x.color.r, x.color.g, x.color.b), (0x33, 0x66, 0xFF));
+                assert_eq!(x.spans[0].text, "$5 off 20");
+            }
+            _ => panic!("not a text"),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid expression '$radius * 2': undefined variable 'radius'")]
+    fn has_undefined_variable() {
+        let s = "---
+width: 400
+height: 300
+layers:
+  - circle:
+      x: 10
+      y: 10
+      radius: $radius * 2
+      color: '#AABBCC'
+";
+        parse!(s);
+    }
+
+    #[test]
+    fn has_use_layer() {
+        let s = "---
+width: 400
+height: 300
+vars:
+  accent: '#112233'
+defs:
+  badge:
+    params:
+      color: '#AABBCC'
+      size: 10
+    layers:
+      - circle:
+          x: 0
+          y: 0
+          radius: $size
+          color: $color
+      - circle:
+          x: 0
+          y: 0
+          radius: $size / 2
+          color: $accent
+layers:
+  - use:
+      component: badge
+      x: 20
+      y: 30
+  - use:
+      component: badge
+      x: ${width - 20}
+      y: 30
+      params:
+        color: $accent
+        size: 16
+";
+        let subject = parse!(s);
+        assert_eq!(subject.layers.len(), 2);
+        match &subject.layers[1] {
+            Layer::Group(x) => {
+                assert!(approx_eq!(f64, x.x, 380.0));
+                assert!(approx_eq!(f64, x.y, 30.0));
+                assert_eq!(x.layers.len(), 2);
+                match &x.layers[0] {
+                    Layer::Circle(c) => {
+                        assert!(approx_eq!(f64, c.radius, 16.0));
+                        assert_eq!(c.color.r, 17);
+                    }
+                    _ => panic!("not a circle"),
+                }
+            }
+            _ => panic!("not a group"),
+        }
+        match &subject.layers[0] {
+            Layer::Group(x) => match &x.layers[1] {
+                Layer::Circle(c) => assert!(approx_eq!(f64, c.radius, 5.0)),
+                _ => panic!("not a circle"),
+            },
+            _ => panic!("not a group"),
+        }
+    }
+
+    #[test]
+    fn has_nested_use_layer() {
+        let s = "---
+width: 400
+height: 300
+defs:
+  dot:
+    params:
+      r: 2
+    layers:
+      - circle: {x: 0, y: 0, radius: $r, color: '#AABBCC'}
+  pair:
+    layers:
+      - use: {component: dot}
+      - use: {component: dot, x: 10, params: {r: 4}}
+layers:
+  - use:
+      component: pair
+";
+        let subject = parse!(s);
+        match &subject.layers[0] {
+            Layer::Group(x) => {
+                assert_eq!(x.layers.len(), 2);
+                assert!(matches!(x.layers[1], Layer::Group(_)));
+            }
+            _ => panic!("not a group"),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown component 'badge'")]
+    fn has_unknown_component() {
+        let s = "---
+width: 400
+height: 300
+layers:
+  - use:
+      component: badge
+";
+        parse!(s);
+    }
+
+    #[test]
+    fn has_boolean_component_param() {
+        let s = "---
+width: 400
+height: 300
+defs:
+  dot:
+    params:
+      fill: false
+    layers:
+      - circle: {x: 0, y: 0, radius: 1, color: '#AABBCC', fill: $fill}
+layers:
+  - use:
+      component: dot
+      params:
+        fill: true
+  - use:
+      component: dot
+";
+        let subject = parse!(s);
+        let fills: Vec<bool> = subject
+            .layers
+            .iter()
+            .map(|x| match x {
+                Layer::Group(x) => match &x.layers[0] {
+                    Layer::Circle(x) => x.fill,
+                    _ => panic!("not a circle"),
+                },
+                _ => panic!("not a group"),
+            })
+            .collect();
+        assert_eq!(fills, vec![true, false]);
+    }
+
+    #[test]
+    #[should_panic(expected = "component 'a' uses itself")]
+    fn has_recursive_component() {
+        let s = "---
+width: 400
+height: 300
+defs:
+  a:
+    layers:
+      - use: {component: b}
+  b:
+    layers:
+      - use: {component: a}
+layers:
+  - use:
+      component: a
+";
+        parse!(s);
+    }
+
+    #[test]
+    fn has_repeat_layer() {
+        let s = "---
+width: 400
+height: 300
+layers:
+  - repeat:
+      rows: 2
+      cols: 3
+      x: 10
+      dx: 20
+      dy: 30
+      layers:
+        - circle:
+            x: 0
+            y: 0
+            radius: ${i + 1}
+            color: '#AABBCC'
+        - text:
+            x: 0
+            y: 0
+            text: ${row}-${col}
+            color: '#AABBCC'
+";
+        let subject = parse!(s);
+        let cells = match &subject.layers[0] {
+            Layer::Group(x) => &x.layers,
+            _ => panic!("not a group"),
+        };
+        assert_eq!(cells.len(), 6);
+        match &cells[5] {
+            Layer::Group(x) => {
+                assert!(approx_eq!(f64, x.x, 50.0));
+                assert!(approx_eq!(f64, x.y, 30.0));
+                match &x.layers[0] {
+                    Layer::Circle(c) => assert!(approx_eq!(f64, c.radius, 6.0)),
+                    _ => panic!("not a circle"),
+                }
+                match &x.layers[1] {
+                    Layer::Text(t) => assert_eq!(t.text, "1-2"),
+                    _ => panic!("not a text"),
+                }
+            }
+            _ => panic!("not a group"),
+        }
+    }
+
+    #[test]
+    fn has_nested_repeat_layer() {
+        let s = "---
+width: 400
+height: 300
+vars:
+  n: 2
+layers:
+  - repeat:
+      count: $n + 1
+      index: star
+      layers:
+        - repeat:
+            count: $star
+            layers:
+              - circle: {x: 0, y: 0, radius: '${star * 10 + i}', color: '#AABBCC'}
+";
+        let subject = parse!(s);
+        let outer = match &subject.layers[0] {
+            Layer::Group(x) => &x.layers,
+            _ => panic!("not a group"),
+        };
+        assert_eq!(outer.len(), 3);
+        match &outer[2] {
+            Layer::Group(x) => match &x.layers[0] {
+                Layer::Group(inner) => {
+                    assert_eq!(inner.layers.len(), 2);
+                    match &inner.layers[1] {
+                        Layer::Group(cell) => match &cell.layers[0] {
+                            Layer::Circle(c) => assert!(approx_eq!(f64, c.radius, 21.0)),
+                            _ => panic!("not a circle"),
+                        },
+                        _ => panic!("not a group"),
+                    }
+                }
+                _ => panic!("not a group"),
+            },
+            _ => panic!("not a group"),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "'repeat' option 'count' must be at most 0")]
+    fn has_too_many_nested_instances() {
+        let s = "---
+width: 400
+height: 300
+layers:
+  - repeat:
+      count: 100000
+      layers:
+        - repeat:
+            count: 2
+            layers:
+              - circle: {x: 0, y: 0, radius: 1, color: '#AABBCC'}
+";
+        parse!(s);
+    }
+
+    #[test]
+    #[should_panic(expected = "component 'dot' makes more than 100000 instances")]
+    fn has_too_many_component_instances() {
+        let s = "---
+width: 400
+height: 300
+defs:
+  dot:
+    layers:
+      - circle: {x: 0, y: 0, radius: 1, color: '#AABBCC'}
+layers:
+  - repeat:
+      count: 100000
+      layers:
+        - use: {component: dot}
+";
+        parse!(s);
+    }
+
+    #[test]
+    #[should_panic(expected = "'repeat' is required 'layers' option")]
+    fn has_repeat_layer_without_layers() {
+        let s = "---
+width: 400
+height: 300
+layers:
+  - repeat:
+      count: 2
+";
+        parse!(s);
+    }
+
+    #[test]
+    fn has_external_scope() {
+        let s = "---
+width: 400
+height: 300
+vars:
+  name: sample
+  x: 10
+  label: Hello ${name}
+layers:
+  - text:
+      x: $x
+      y: 10
+      text: ${label}
+      color: '#AABBCC'
+";
+        let src = parse_yaml!(s);
+        let mut external = Scope::new();
+        external.set("name", Value::Str("alice".to_string()));
+        let subject = Root::parse(&src, &external).unwrap();
+        match &subject.layers[0] {
+            Layer::Text(x) => {
+                assert!(approx_eq!(f64, x.x, 10.0));
+                assert_eq!(x.text, "Hello alice");
+            }
+            _ => panic!("not a text"),
+        }
+    }
+
+    #[test]
+    fn has_styles() {
+        let s = "---
+width: 400
+height: 300
+vars:
+  brand: '#112233'
+styles:
+  primary:
+    color: $brand
+    alpha: 0.5
+  filled:
+    fill: true
+layers:
+  - circle:
+      class: primary filled
+      x: 10
+      y: 10
+      radius: 10
+  - rectangle:
+      class: [primary]
+      x: 10
+      y: 10
+      width: 10
+      height: 10
+      alpha: 1
+";
+        let subject = parse!(s);
+        match &subject.layers[0] {
+            Layer::Circle(x) => {
+                assert_eq!(x.color.r, 17);
+                assert!(approx_eq!(f32, x.color.a, 0.5));
+                assert!(x.fill);
+            }
+            _ => panic!("not a circle"),
+        }
+        match &subject.layers[1] {
+            Layer::Rectangle(x) => {
+                assert!(approx_eq!(f32, x.color.a, 1.0));
+                assert!(!x.fill);
+            }
+            _ => panic!("not a rectangle"),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown class 'primary'")]
+    fn has_unknown_class() {
+        let s = "---
+width: 400
+height: 300
+layers:
+  - circle:
+      class: primary
+      x: 10
+      y: 10
+      radius: 10
+";
+        parse!(s);
+    }
+
+    #[test]
+    fn has_themes() {
+        let s = "---
+width: 400
+height: 300
+color: '@bg'
+themes:
+  light:
+    bg: '#FFFFFF'
+    primary: '#3366FF'
+  dark:
+    bg: '#111111'
+    primary: '#99BBFF'
+layers:
+  - circle:
+      x: 10
+      y: 10
+      radius: 10
+      color: '@primary'
+";
+        let src = parse_yaml!(s);
+        let subject = Root::parse(&src, &Scope::new()).unwrap();
+        assert_eq!(subject.color.r, 255);
+        match &subject.layers[0] {
+            Layer::Circle(x) => assert_eq!(x.color.r, 51),
+            _ => panic!("not a circle"),
+        }
+
+        let mut external = Scope::new();
+        external.set_theme("dark");
+        let subject = Root::parse(&src, &external).unwrap();
+        assert_eq!(subject.color.r, 17);
+        match &subject.layers[0] {
+            Layer::Circle(x) => assert_eq!(x.color.r, 153),
+            _ => panic!("not a circle"),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown theme 'sepia'")]
+    fn has_unknown_theme() {
+        let s = "---
+width: 400
+height: 300
+themes:
+  light:
+    bg: '#FFFFFF'
+layers: []
+";
+        let src = parse_yaml!(s);
+        let mut external = Scope::new();
+        external.set_theme("sepia");
+        if let Err(e) = Root::parse(&src, &external) {
+            panic!(e.to_string());
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid color format '@primary'")]
+    fn has_unknown_palette_color() {
+        let s = "---
+width: 400
+height: 300
+layers:
+  - circle:
+      x: 10
+      y: 10
+      radius: 10
+      color: '@primary'
+";
+        parse!(s);
+    }
+
+    #[test]
+    fn has_color_functions() {
+        let s = "---
+width: 400
+height: 300
+themes:
+  light:
+    primary: '#3366FF'
+layers:
+  - circle:
+      x: 10
+      y: 10
+      radius: 10
+      color: alpha(@primary, 0.5)
+      alpha: 0.5
+  - circle:
+      x: 10
+      y: 10
+      radius: 10
+      color: lighten(@primary, 20%)
+";
+        let subject = parse!(s);
+        match &subject.layers[0] {
+            Layer::Circle(x) => {
+                assert_eq!(x.color.b, 255);
+                assert!(approx_eq!(f32, x.color.a, 0.25));
+            }
+            _ => panic!("not a circle"),
+        }
+        match &subject.layers[1] {
+            Layer::Circle(x) => assert_eq!((x.color.r, x.color.g), (153, 179)),
+            _ => panic!("not a circle"),
+        }
+    }
+
+    #[test]
+    fn has_translucent_ellipse_with_alpha() {
+        let s = "---
+width: 400
+height: 300
+layers:
+  - ellipse: {x: 10, y: 10, rx: 20, ry: 10, color: '#FF000080', alpha: 0.5}
+";
+        let subject = parse!(s);
+        match &subject.layers[0] {
+            Layer::Ellipse(x) => assert!(approx_eq!(f32, x.color.a, 64.0 / 255.0)),
+            _ => panic!("not an ellipse"),
+        }
+    }
+
+    #[test]
+    fn has_translucent_colors_with_alpha() {
+        let s = "---
+width: 400
+height: 300
+layers:
+  - circle: {x: 10, y: 10, radius: 10, color: '#FF000080', alpha: 0.5}
+  - rectangle: {x: 10, y: 10, width: 20, height: 10, color: '#FF000080', alpha: 0.5}
+  - triangle:
+      vertex: [[10, 10], [30, 30], [10, 30]]
+      color: '#FF000080'
+      alpha: 0.5
+  - text:
+      x: 10
+      y: 10
+      color: '#FF000080'
+      alpha: 0.5
+      spans:
+        - {text: a, color: '#00FF0080', alpha: 0.5}
+";
+        let subject = parse!(s);
+        let expected = 64.0 / 255.0;
+        let colors = subject.layers.iter().map(|x| -> Color {
+            match x {
+                Layer::Circle(x) => x.color,
+                Layer::Rectangle(x) => x.color,
+                Layer::Triangle(x) => x.color,
+                Layer::Text(x) => x.color,
+                _ => panic!("unexpected layer"),
+            }
+        });
+        for color in colors {
+            assert!(approx_eq!(f32, color.a, expected));
+        }
+        match &subject.layers[3] {
+            Layer::Text(x) => assert!(approx_eq!(f32, x.spans[0].color.a, expected)),
+            _ => panic!("not a text"),
+        }
+    }
+
+    #[test]
+    fn error_path() {
+        let s = "---
+width: 400
+height: 300
+defs:
+  dot:
+    layers:
+      - circle: {x: 1, y: 1, color: red}
+layers:
+  - rectangle: {x: 0, y: 0, width: 1, height: 1, color: red}
+  - repeat:
+      count: 2
+      layers:
+        - use: {component: dot}
+";
+        let src = parse_yaml!(s);
+        let subject = Root::parse(&src, &Scope::new()).unwrap_err();
+        let (path, e) = subject.location();
+        assert_eq!(
+            path,
+            &[
+                Segment::Key("defs".to_string()),
+                Segment::Key("dot".to_string()),
+                Segment::Key("layers".to_string()),
+                Segment::Index(0),
+            ][..]
+        );
+        assert_eq!(e.to_string(), "'circle' is required 'radius' option");
+
+        let s = s.replace("component: dot}", "component: dots}");
+        let src = parse_yaml!(&s);
+        let subject = Root::parse(&src, &Scope::new()).unwrap_err();
+        assert_eq!(subject.subject(), Some("dots"));
+        assert_eq!(
+            subject.location().0,
+            &[
+                Segment::Key("layers".to_string()),
+                Segment::Index(1),
+                Segment::Key("repeat".to_string()),
+                Segment::Key("layers".to_string()),
+                Segment::Index(0),
+            ][..]
+        );
+    }
+
+    #[test]
+    fn validate_collects_errors() {
+        let s = "---
+width: 400
+height: 300
+defs:
+  dot:
+    layers:
+      - circle: {x: 1, y: 1, color: red}
+  spare:
+    layers: []
+layers:
+  - rectangle: {x: 0, y: 0, width: 1, height: 1, color: '#GG0000'}
+  - repeat:
+      count: 3
+      layers:
+        - use: {component: dot}
+  - blob: {}
+";
+        let src = parse_yaml!(s);
+        let subject: Vec<(Severity, String)> = Root::validate(&src, &Scope::new())
+            .iter()
+            .map(|x| (x.severity, x.error.to_string()))
+            .collect();
+        assert_eq!(
+            subject,
+            vec![
+                (
+                    Severity::Error,
+                    "invalid color format '#GG0000'".to_string()
+                ),
+                (
+                    Severity::Error,
+                    "'circle' is required 'radius' option".to_string()
+                ),
+                (Severity::Error, "unknown layer type 'blob'".to_string()),
+                (
+                    Severity::Warning,
+                    "component 'spare' is never used".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_valid_document() {
+        let s = "---
+width: 400
+height: 300
+layers:
+  - circle: {x: 1, y: 1, radius: 1, color: red}
+";
+        let src = parse_yaml!(s);
+        assert!(Root::validate(&src, &Scope::new()).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "'circle' has no option 'raduis', did you mean 'radius'?")]
+    fn strict_unknown_option() {
+        let s = "---
+width: 400
+height: 300
+layers:
+  - circle: {x: 1, y: 1, radius: 1, raduis: 1, color: red}
+";
+        let src = parse_yaml!(s);
+        let mut scope = Scope::new();
+        scope.set_strict(true);
+        if let Err(e) = Root::parse(&src, &scope) {
+            panic!(e.to_string());
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid color format '#GGG'")]
+    fn strict_invalid_color() {
+        let s = "---
+width: 400
+height: 300
+color: '#GGG'
+layers: []
+";
+        let src = parse_yaml!(s);
+        let mut scope = Scope::new();
+        scope.set_strict(true);
+        if let Err(e) = Root::parse(&src, &scope) {
+            panic!(e.to_string());
+        }
+    }
+
+    #[test]
+    fn lenient_invalid_values() {
+        let s = "---
+width: 400
+height: 300
+color: '#GGG'
+styles:
+  big:
+    sides: 6
+layers:
+  - circle:
+      class: big
+      x: 1
+      y: 1
+      radius: 1
+      color: red
+      stroke: {cap: rund}
+";
+        let subject = parse!(s);
+        assert_eq!(subject.color.a, 0.0);
+
+        let src = parse_yaml!(s);
+        let subject: Vec<(Severity, String)> = Root::validate(&src, &Scope::new())
+            .iter()
+            .map(|x| (x.severity, x.error.to_string()))
+            .collect();
+        assert_eq!(
+            subject,
+            vec![
+                (Severity::Warning, "invalid color format '#GGG'".to_string()),
+                (
+                    Severity::Warning,
+                    "invalid 'cap' value 'rund', expected one of butt, round, square".to_string()
+                ),
+            ]
+        );
+    }
 }
@@ -0,0 +1,938 @@
+use serde_json::{json, Map, Value as Json};
+use yaml_rust::yaml::Hash;
+use yaml_rust::Yaml;
+
+use crate::expr::Scope;
+use crate::node::error::NodeError;
+use crate::source::Segment;
+
+/// The values an option accepts.
+#[derive(Debug, Clone, Copy)]
+pub enum Kind {
+    Number,
+    Bool,
+    Text,
+    Texts,
+    Color,
+    Point,
+    Points,
+    Enum(&'static [&'static str]),
+    Either(&'static [Kind]),
+    Options(&'static [Field]),
+    ListOf(&'static [Field]),
+    Layers,
+    Map(&'static Kind),
+    Any,
+}
+
+/// The value an option takes when it is left out.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DefaultValue {
+    Number(f64),
+    Bool(bool),
+    Text(&'static str),
+}
+
+/// An option of a node.
+#[derive(Debug, Clone, Copy)]
+pub struct Field {
+    pub name: &'static str,
+    pub kind: Kind,
+    pub required: bool,
+    pub default: Option<DefaultValue>,
+}
+
+const fn opt(name: &'static str, kind: Kind) -> Field {
+    Field {
+        name,
+        kind,
+        required: false,
+        default: None,
+    }
+}
+
+const fn req(name: &'static str, kind: Kind) -> Field {
+    Field {
+        name,
+        kind,
+        required: true,
+        default: None,
+    }
+}
+
+const fn def(name: &'static str, kind: Kind, value: DefaultValue) -> Field {
+    Field {
+        name,
+        kind,
+        required: false,
+        default: Some(value),
+    }
+}
+
+const fn num(name: &'static str, value: f64) -> Field {
+    def(name, Kind::Number, DefaultValue::Number(value))
+}
+
+const fn flag(name: &'static str, value: bool) -> Field {
+    def(name, Kind::Bool, DefaultValue::Bool(value))
+}
+
+const STROKE: &[Field] = &[
+    num("width", 1.0),
+    def(
+        "cap",
+        Kind::Enum(&["butt", "round", "square"]),
+        DefaultValue::Text("butt"),
+    ),
+];
+const SCALE: &[Field] = &[num("x", 1.0), num("y", 1.0)];
+const WEIGHT: Kind = Kind::Enum(&["normal", "bold"]);
+const SLANT: Kind = Kind::Enum(&["normal", "italic", "oblique"]);
+const SPAN: &[Field] = &[
+    req("text", Kind::Text),
+    opt("alpha", Kind::Number),
+    opt("color", Kind::Color),
+    opt("family", Kind::Text),
+    opt("size", Kind::Number),
+    opt("weight", WEIGHT),
+    opt("slant", SLANT),
+    opt("underline", Kind::Bool),
+    opt("strikethrough", Kind::Bool),
+];
+const FIT: &[Field] = &[
+    req("width", Kind::Number),
+    req("height", Kind::Number),
+    num("min_size", 1.0),
+    opt("max_size", Kind::Number),
+    flag("wrap", false),
+];
+const OUTLINE: &[Field] = &[
+    num("width", 1.0),
+    num("alpha", 1.0),
+    req("color", Kind::Color),
+];
+const BACKGROUND: &[Field] = &[
+    num("padding", 0.0),
+    num("radius", 0.0),
+    num("alpha", 1.0),
+    req("color", Kind::Color),
+];
+const PATH: &[Field] = &[
+    opt("points", Kind::Points),
+    opt(
+        "curve",
+        Kind::Options(&[
+            req("start", Kind::Point),
+            req("mid", Kind::Point),
+            req("end", Kind::Point),
+        ]),
+    ),
+    opt(
+        "arc",
+        Kind::Options(&[
+            req("x", Kind::Number),
+            req("y", Kind::Number),
+            req("radius", Kind::Number),
+            req("start", Kind::Number),
+            req("end", Kind::Number),
+        ]),
+    ),
+    num("offset", 0.0),
+    def(
+        "align",
+        Kind::Enum(&["start", "center", "end"]),
+        DefaultValue::Text("start"),
+    ),
+];
+
+// Consumed by styles.
+const CLASS: Field = opt("class", Kind::Either(&[Kind::Text, Kind::Texts]));
+// Options of every shape.
+const SHAPE: &[Field] = &[
+    num("alpha", 1.0),
+    req("color", Kind::Color),
+    opt("stroke", Kind::Options(STROKE)),
+];
+const FILL: Field = flag("fill", false);
+const SCALED: Field = opt("scale", Kind::Options(SCALE));
+
+const RECTANGLE: &[Field] = &[
+    req("x", Kind::Number),
+    req("y", Kind::Number),
+    req("width", Kind::Number),
+    req("height", Kind::Number),
+    num("radius", 0.0),
+];
+const CIRCLE: &[Field] = &[
+    req("x", Kind::Number),
+    req("y", Kind::Number),
+    req("radius", Kind::Number),
+];
+const ELLIPSE: &[Field] = &[
+    req("x", Kind::Number),
+    req("y", Kind::Number),
+    req("rx", Kind::Number),
+    req("ry", Kind::Number),
+    num("rotation", 0.0),
+];
+const ARC: &[Field] = &[
+    req("x", Kind::Number),
+    req("y", Kind::Number),
+    req("start", Kind::Number),
+    req("end", Kind::Number),
+    def(
+        "close",
+        Kind::Either(&[Kind::Bool, Kind::Enum(&["open", "pie", "chord"])]),
+        DefaultValue::Text("open"),
+    ),
+    def(
+        "direction",
+        Kind::Enum(&["cw", "ccw"]),
+        DefaultValue::Text("cw"),
+    ),
+    req("radius", Kind::Number),
+    num("inner_radius", 0.0),
+];
+const POLYGON: &[Field] = &[req("vertex", Kind::Points)];
+const LINE: &[Field] = &[req("points", Kind::Points)];
+const CURVE: &[Field] = &[
+    req("start", Kind::Point),
+    req("mid", Kind::Point),
+    req("end", Kind::Point),
+];
+const REGULAR_POLYGON: &[Field] = &[
+    req("x", Kind::Number),
+    req("y", Kind::Number),
+    req("radius", Kind::Number),
+    req("sides", Kind::Number),
+    num("rotation", 0.0),
+];
+const STAR: &[Field] = &[
+    req("x", Kind::Number),
+    req("y", Kind::Number),
+    req("points", Kind::Number),
+    req("outer_radius", Kind::Number),
+    req("inner_radius", Kind::Number),
+    num("rotation", 0.0),
+];
+// `text` is required unless `spans` is given.
+const TEXT: &[Field] = &[
+    CLASS,
+    req("x", Kind::Number),
+    req("y", Kind::Number),
+    opt("text", Kind::Text),
+    opt("spans", Kind::ListOf(SPAN)),
+    opt("width", Kind::Number),
+    opt("height", Kind::Number),
+    num("alpha", 1.0),
+    req("color", Kind::Color),
+    def("family", Kind::Text, DefaultValue::Text("serif")),
+    num("size", 14.0),
+    def("weight", WEIGHT, DefaultValue::Text("normal")),
+    def("slant", SLANT, DefaultValue::Text("normal")),
+    flag("underline", false),
+    flag("strikethrough", false),
+    num("line_height", 1.2),
+    def(
+        "direction",
+        Kind::Enum(&["horizontal", "vertical"]),
+        DefaultValue::Text("horizontal"),
+    ),
+    opt("fit", Kind::Options(FIT)),
+    opt("outline", Kind::Options(OUTLINE)),
+    opt("background", Kind::Options(BACKGROUND)),
+    opt("path", Kind::Options(PATH)),
+    SCALED,
+];
+const USE: &[Field] = &[
+    CLASS,
+    req("component", Kind::Text),
+    num("x", 0.0),
+    num("y", 0.0),
+    opt("params", Kind::Map(&Kind::Any)),
+];
+
+/// Options of `repeat` layers. Either `count` or `rows` and `cols` is
+/// required.
+pub const REPEAT: &[Field] = &[
+    opt("count", Kind::Number),
+    opt("rows", Kind::Number),
+    opt("cols", Kind::Number),
+    num("x", 0.0),
+    num("y", 0.0),
+    num("dx", 0.0),
+    num("dy", 0.0),
+    def("index", Kind::Text, DefaultValue::Text("i")),
+    req("layers", Kind::Layers),
+];
+
+/// Keys of an entry of `defs:`.
+pub const COMPONENT: &[Field] = &[
+    opt("params", Kind::Map(&Kind::Any)),
+    req("layers", Kind::Layers),
+];
+
+/// Top level keys of a document.
+pub const ROOT: &[Field] = &[
+    req("width", Kind::Number),
+    req("height", Kind::Number),
+    opt("color", Kind::Color),
+    opt("vars", Kind::Map(&Kind::Any)),
+    opt("defs", Kind::Map(&Kind::Options(COMPONENT))),
+    opt("styles", Kind::Map(&Kind::Map(&Kind::Any))),
+    opt("themes", Kind::Map(&Kind::Map(&Kind::Color))),
+    req("layers", Kind::Layers),
+];
+
+/// Top level keys of a source file, with the `imports` the loader consumes.
+pub fn root() -> Vec<Field> {
+    let mut ret = vec![opt("imports", Kind::Any)];
+    ret.extend_from_slice(ROOT);
+    ret
+}
+
+/// Layer types in the order they are documented.
+pub const LAYERS: &[&str] = &[
+    "rectangle",
+    "circle",
+    "ellipse",
+    "arc",
+    "triangle",
+    "polygon",
+    "line",
+    "curve",
+    "regular_polygon",
+    "star",
+    "text",
+    "use",
+];
+
+/// Returns the options of a layer type, or of `repeat`, in their canonical
+/// order: `class`, its own options, then the options of every shape, `fill`
+/// and `scale`.
+pub fn layer(name: &str) -> Option<Vec<Field>> {
+    let (own, fill, scale) = match name {
+        "rectangle" => (RECTANGLE, true, true),
+        "circle" => (CIRCLE, true, true),
+        "ellipse" => (ELLIPSE, true, false),
+        "arc" => (ARC, true, true),
+        "triangle" | "polygon" => (POLYGON, true, true),
+        "line" => (LINE, false, true),
+        "curve" => (CURVE, false, true),
+        "regular_polygon" => (REGULAR_POLYGON, true, true),
+        "star" => (STAR, true, true),
+        "text" => return Some(TEXT.to_vec()),
+        "repeat" => return Some(REPEAT.to_vec()),
+        "use" => return Some(USE.to_vec()),
+        _ => return None,
+    };
+    let mut ret = vec![CLASS];
+    ret.extend_from_slice(own);
+    ret.extend_from_slice(SHAPE);
+    if fill {
+        ret.push(FILL);
+    }
+    if scale {
+        ret.push(SCALED);
+    }
+    Some(ret)
+}
+
+/// Describes documents as a JSON Schema (draft-07) for editors: the root
+/// keys, every layer type and their options with required options, defaults
+/// and enums.
+pub fn json_schema() -> Json {
+    let mut definitions = Map::new();
+    definitions.insert(
+        "expression".to_string(),
+        json!({
+            "type": "string",
+            "pattern": "\\$",
+            "description": "An expression of variables, such as \"$width / 2\"."
+        }),
+    );
+    definitions.insert(
+        "color".to_string(),
+        json!({
+            "type": "string",
+            "description": "A CSS color, a color function such as \"lighten(red, 20%)\" or \"mix(@primary, white, 0.5)\", a theme color such as \"@primary\" or an expression."
+        }),
+    );
+    definitions.insert(
+        "point".to_string(),
+        json!({
+            "type": "array",
+            "items": number(),
+            "minItems": 2,
+            "maxItems": 2,
+            "description": "A point as [x, y]."
+        }),
+    );
+    definitions.insert(
+        "points".to_string(),
+        json!({"type": "array", "items": {"$ref": "#/definitions/point"}}),
+    );
+    for (name, fields) in &[
+        ("stroke", STROKE),
+        ("scale", SCALE),
+        ("span", SPAN),
+        ("fit", FIT),
+        ("outline", OUTLINE),
+        ("background", BACKGROUND),
+        ("path", PATH),
+        ("component", COMPONENT),
+    ] {
+        definitions.insert(name.to_string(), object(fields));
+    }
+
+    let mut layers = Vec::new();
+    for name in LAYERS {
+        let mut definition = object(&layer(name).unwrap_or_default());
+        // A class may supply any option, so none is required alongside it.
+        let mut required = json!({});
+        if let Some(x) = definition.as_object_mut() {
+            required["required"] = x.remove("required").unwrap_or_default();
+        }
+        if *name == "text" {
+            required["anyOf"] = json!([{"required": ["text"]}, {"required": ["spans"]}]);
+        }
+        definition["if"] = json!({"required": ["class"]});
+        definition["else"] = required;
+        definitions.insert(name.to_string(), definition);
+        layers.push(single(
+            name,
+            json!({"$ref": format!("#/definitions/{}", name)}),
+        ));
+    }
+    let mut repeat = object(REPEAT);
+    repeat["anyOf"] = json!([
+        {"required": ["count"]},
+        {"required": ["rows"]},
+        {"required": ["cols"]}
+    ]);
+    definitions.insert("repeat".to_string(), repeat);
+    layers.push(single("repeat", json!({"$ref": "#/definitions/repeat"})));
+    layers.push(single("include", json!({"type": "string"})));
+    definitions.insert("layer".to_string(), json!({ "oneOf": layers }));
+
+    let mut ret = object(ROOT);
+    ret["properties"]["imports"] = json!({"type": "array", "items": {"type": "string"}});
+    let mut schema = json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "claris document",
+    });
+    if let (Json::Object(schema), Json::Object(root)) = (&mut schema, ret) {
+        schema.extend(root);
+        schema.insert("definitions".to_string(), Json::Object(definitions));
+    }
+    schema
+}
+
+fn object(fields: &[Field]) -> Json {
+    let mut properties = Map::new();
+    for field in fields {
+        let mut property = kind_schema(field.name, field.kind);
+        if let Some(x) = field.default {
+            property["default"] = match x {
+                DefaultValue::Number(x) => json!(x),
+                DefaultValue::Bool(x) => json!(x),
+                DefaultValue::Text(x) => json!(x),
+            };
+        }
+        properties.insert(field.name.to_string(), property);
+    }
+    let required: Vec<&str> = fields
+        .iter()
+        .filter(|x| x.required)
+        .map(|x| x.name)
+        .collect();
+
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false
+    })
+}
+
+// Options with their own definition are referred to by name.
+fn kind_schema(name: &str, kind: Kind) -> Json {
+    match kind {
+        Kind::Number => number(),
+        Kind::Bool => json!({"type": "boolean"}),
+        Kind::Text => json!({"type": "string"}),
+        Kind::Texts => json!({"type": "array", "items": {"type": "string"}}),
+        Kind::Color => json!({"$ref": "#/definitions/color"}),
+        Kind::Point => json!({"$ref": "#/definitions/point"}),
+        Kind::Points => json!({"$ref": "#/definitions/points"}),
+        Kind::Enum(values) => json!({"type": "string", "enum": values}),
+        Kind::Either(kinds) => json!({
+            "anyOf": kinds.iter().map(|x| kind_schema(name, *x)).collect::<Vec<Json>>()
+        }),
+        Kind::Options(fields) => match name {
+            "stroke" | "scale" | "fit" | "outline" | "background" | "path" => {
+                json!({ "$ref": format!("#/definitions/{}", name) })
+            }
+            _ => object(fields),
+        },
+        Kind::ListOf(fields) => match name {
+            "spans" => json!({"type": "array", "items": {"$ref": "#/definitions/span"}}),
+            _ => json!({"type": "array", "items": object(fields)}),
+        },
+        Kind::Layers => json!({"type": "array", "items": {"$ref": "#/definitions/layer"}}),
+        Kind::Map(inner) => match name {
+            "defs" => json!({
+                "type": "object",
+                "additionalProperties": {"$ref": "#/definitions/component"}
+            }),
+            _ => json!({"type": "object", "additionalProperties": kind_schema("", *inner)}),
+        },
+        Kind::Any => json!({}),
+    }
+}
+
+// Numbers are inlined so a default can sit next to them.
+fn number() -> Json {
+    json!({"anyOf": [{"type": "number"}, {"$ref": "#/definitions/expression"}]})
+}
+
+fn single(name: &str, value: Json) -> Json {
+    json!({
+        "type": "object",
+        "properties": { name: value },
+        "required": [name],
+        "additionalProperties": false
+    })
+}
+
+/// Resolves the expressions in the options of a node. Text options are
+/// free text, where only `${expr}` is replaced, so a `$word` or `@name`
+/// written in them is kept.
+pub fn resolve(scope: &Scope, src: &Yaml, fields: &[Field]) -> Result<Yaml, NodeError> {
+    let x = match src {
+        Yaml::Hash(x) => x,
+        _ => return scope.resolve(src),
+    };
+    let mut ret = Hash::new();
+    for (key, value) in x {
+        let value = match fields.iter().find(|x| key.as_str() == Some(x.name)) {
+            Some(field) => resolve_kind(scope, value, field.kind)?,
+            None => scope.resolve(value)?,
+        };
+        ret.insert(key.clone(), value);
+    }
+    Ok(Yaml::Hash(ret))
+}
+
+fn resolve_kind(scope: &Scope, src: &Yaml, kind: Kind) -> Result<Yaml, NodeError> {
+    match (kind, src) {
+        (Kind::Text, Yaml::String(x)) => scope.interpolate(x).map(Yaml::String),
+        (Kind::Options(fields), Yaml::Hash(_)) => resolve(scope, src, fields),
+        (Kind::ListOf(fields), Yaml::Array(x)) => x
+            .iter()
+            .map(|x| resolve(scope, x, fields))
+            .collect::<Result<Vec<Yaml>, NodeError>>()
+            .map(Yaml::Array),
+        _ => scope.resolve(src),
+    }
+}
+
+/// Reports the keys of `src` that `fields` does not know and the values of
+/// the wrong kind, with the path of each below `src`. Only keys written in
+/// `raw` are reported as unknown, so options merged from styles for other
+/// layer types are allowed.
+pub fn check(
+    owner: &str,
+    fields: &[Field],
+    raw: Option<&Hash>,
+    src: &Hash,
+) -> Vec<(Vec<Segment>, NodeError)> {
+    let mut ret = Vec::new();
+
+    for (key, value) in src {
+        let name = match key.as_str() {
+            Some(x) => x,
+            None => continue,
+        };
+        let path = vec![Segment::Key(name.to_string())];
+        let raw_value = raw.and_then(|x| x.get(key));
+        match fields.iter().find(|x| x.name == name) {
+            Some(field) => {
+                for (mut inner, e) in check_value(name, field.kind, raw_value, value) {
+                    let mut path = path.clone();
+                    path.append(&mut inner);
+                    ret.push((path, e));
+                }
+            }
+            None if raw_value.is_some() => {
+                let suggestion = suggest(name, fields.iter().map(|x| x.name));
+                ret.push((
+                    path,
+                    NodeError::UnknownOption(owner.to_string(), name.to_string(), suggestion),
+                ));
+            }
+            None => {}
+        }
+    }
+
+    ret
+}
+
+fn check_value(
+    name: &str,
+    kind: Kind,
+    raw: Option<&Yaml>,
+    src: &Yaml,
+) -> Vec<(Vec<Segment>, NodeError)> {
+    if !accepts(kind, src) {
+        return vec![(
+            Vec::new(),
+            NodeError::InvalidValue(name.to_string(), describe(src), expected(kind)),
+        )];
+    }
+
+    match (kind, src) {
+        (Kind::Options(fields), Yaml::Hash(x)) => {
+            check(name, fields, raw.and_then(Yaml::as_hash), x)
+        }
+        (Kind::ListOf(fields), Yaml::Array(items)) => {
+            let mut ret = Vec::new();
+            for (i, item) in items.iter().enumerate() {
+                if let Yaml::Hash(x) = item {
+                    let raw = raw.and_then(|r| r[i].as_hash());
+                    for (mut inner, e) in check(name, fields, raw, x) {
+                        let mut path = vec![Segment::Index(i)];
+                        path.append(&mut inner);
+                        ret.push((path, e));
+                    }
+                }
+            }
+            ret
+        }
+        (Kind::Map(inner), Yaml::Hash(x)) => {
+            let mut ret = Vec::new();
+            for (key, value) in x {
+                let key = match key.as_str() {
+                    Some(k) => k,
+                    None => continue,
+                };
+                let raw = raw
+                    .and_then(|r| r.as_hash())
+                    .and_then(|r| r.get(&Yaml::from_str(key)));
+                for (mut path, e) in check_value(key, *inner, raw, value) {
+                    path.insert(0, Segment::Key(key.to_string()));
+                    ret.push((path, e));
+                }
+            }
+            ret
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn accepts(kind: Kind, src: &Yaml) -> bool {
+    match (kind, src) {
+        (Kind::Number, Yaml::Integer(_)) | (Kind::Number, Yaml::Real(_)) => true,
+        // Only top level keys are checked before they are resolved.
+        (Kind::Number, Yaml::String(x)) => x.contains('$'),
+        (Kind::Bool, Yaml::Boolean(_)) => true,
+        (Kind::Text, Yaml::String(_)) | (Kind::Color, Yaml::String(_)) => true,
+        (Kind::Texts, Yaml::Array(x)) => x.iter().all(|x| x.as_str().is_some()),
+        (Kind::Point, Yaml::Array(x)) => x.len() == 2 && x.iter().all(|x| accepts(Kind::Number, x)),
+        (Kind::Points, Yaml::Array(x)) => x.iter().all(|x| accepts(Kind::Point, x)),
+        (Kind::Enum(values), Yaml::String(x)) => values.contains(&x.as_str()),
+        (Kind::Either(kinds), _) => kinds.iter().any(|x| accepts(*x, src)),
+        (Kind::Options(_), Yaml::Hash(_)) => true,
+        (Kind::ListOf(_), Yaml::Array(_)) => true,
+        (Kind::Layers, Yaml::Array(_)) | (Kind::Map(_), Yaml::Hash(_)) => true,
+        (Kind::Any, _) => true,
+        _ => false,
+    }
+}
+
+pub fn expected(kind: Kind) -> String {
+    match kind {
+        Kind::Number => "a number".to_string(),
+        Kind::Bool => "true or false".to_string(),
+        Kind::Text => "a string".to_string(),
+        Kind::Texts => "a list of strings".to_string(),
+        Kind::Color => "a color".to_string(),
+        Kind::Point => "a point [x, y]".to_string(),
+        Kind::Points => "a list of points".to_string(),
+        Kind::Enum(values) => format!("one of {}", values.join(", ")),
+        Kind::Either(kinds) => kinds
+            .iter()
+            .map(|x| expected(*x))
+            .collect::<Vec<String>>()
+            .join(" or "),
+        Kind::Options(_) | Kind::Map(_) => "options".to_string(),
+        Kind::ListOf(_) => "a list".to_string(),
+        Kind::Layers => "a list of layers".to_string(),
+        Kind::Any => "anything".to_string(),
+    }
+}
+
+pub fn describe(src: &Yaml) -> String {
+    match src {
+        Yaml::String(x) | Yaml::Real(x) => x.clone(),
+        Yaml::Integer(x) => x.to_string(),
+        Yaml::Boolean(x) => x.to_string(),
+        Yaml::Array(_) => "[...]".to_string(),
+        Yaml::Hash(_) => "{...}".to_string(),
+        _ => "null".to_string(),
+    }
+}
+
+/// Returns the candidate closest to `name` when it is likely a typo of it.
+pub fn suggest<'a>(name: &str, candidates: impl Iterator<Item = &'a str>) -> Option<String> {
+    candidates
+        .map(|x| (distance(name, x), x))
+        .filter(|(d, _)| *d <= 2 && *d < name.chars().count())
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, x)| x.to_string())
+}
+
+// Levenshtein distance.
+fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, x) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, y) in b.iter().enumerate() {
+            let cost = if x == *y { prev } else { prev + 1 };
+            prev = row[j + 1];
+            row[j + 1] = cost.min(row[j] + 1).min(prev + 1);
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check, json_schema, layer, suggest};
+    use crate::parse_yaml;
+    use crate::source::Segment;
+    use yaml_rust::YamlLoader;
+
+    fn errors(name: &str, s: &str) -> Vec<(Vec<Segment>, String)> {
+        let src = parse_yaml!(s);
+        let src = src.as_hash().unwrap();
+        check(name, &layer(name).unwrap(), Some(src), src)
+            .into_iter()
+            .map(|(path, e)| (path, e.to_string()))
+            .collect()
+    }
+
+    fn key(x: &str) -> Segment {
+        Segment::Key(x.to_string())
+    }
+
+    #[test]
+    fn valid_options() {
+        let s = "---
+x: 1
+y: 2.5
+raduis: 3
+radius: 3
+color: red
+stroke: {width: 2, cap: round}
+";
+        assert_eq!(
+            errors("circle", s),
+            vec![(
+                vec![key("raduis")],
+                "'circle' has no option 'raduis', did you mean 'radius'?".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn nested_options() {
+        let s = "---
+x: 1
+y: 1
+text: hi
+weight: heavy
+spans:
+  - {text: a, sizee: 3}
+path:
+  arc: {x: 1, y: 1, radius: 1, start: 0, end: 90}
+  align: middle
+";
+        assert_eq!(
+            errors("text", s),
+            vec![
+                (
+                    vec![key("weight")],
+                    "invalid 'weight' value 'heavy', expected one of normal, bold".to_string()
+                ),
+                (
+                    vec![key("spans"), Segment::Index(0), key("sizee")],
+                    "'spans' has no option 'sizee', did you mean 'size'?".to_string()
+                ),
+                (
+                    vec![key("path"), key("align")],
+                    "invalid 'align' value 'middle', expected one of start, center, end"
+                        .to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn wrong_types() {
+        let s = "---
+x: '1'
+fill: 1
+close: maybe
+stroke: 2
+";
+        assert_eq!(
+            errors("arc", s),
+            vec![
+                (
+                    vec![key("x")],
+                    "invalid 'x' value '1', expected a number".to_string()
+                ),
+                (
+                    vec![key("fill")],
+                    "invalid 'fill' value '1', expected true or false".to_string()
+                ),
+                (
+                    vec![key("close")],
+                    "invalid 'close' value 'maybe', expected true or false or one of open, pie, chord"
+                        .to_string()
+                ),
+                (
+                    vec![key("stroke")],
+                    "invalid 'stroke' value '2', expected options".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn classes() {
+        assert!(errors("circle", "---\nclass: primary filled\n").is_empty());
+        assert!(errors("circle", "---\nclass: [primary, filled]\n").is_empty());
+        assert_eq!(
+            errors("circle", "---\nclass: [primary, 1]\n"),
+            vec![(
+                vec![key("class")],
+                "invalid 'class' value '[...]', expected a string or a list of strings".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn style_options_are_allowed() {
+        let raw = parse_yaml!("---\n{x: 1}");
+        let src = parse_yaml!("---\n{x: 1, sides: 5}");
+        let subject = check(
+            "circle",
+            &layer("circle").unwrap(),
+            raw.as_hash(),
+            src.as_hash().unwrap(),
+        );
+        assert!(subject.is_empty());
+    }
+
+    #[test]
+    fn suggestion() {
+        let candidates = ["radius", "rotation", "x"];
+        assert_eq!(
+            suggest("raduis", candidates.iter().copied()),
+            Some("radius".to_string())
+        );
+        assert_eq!(suggest("colour", candidates.iter().copied()), None);
+        assert_eq!(suggest("y", candidates.iter().copied()), None);
+    }
+
+    #[test]
+    fn points() {
+        let s = "---
+vertex: [[0, 0], [10, 0], [5]]
+color: red
+";
+        assert_eq!(
+            errors("polygon", s),
+            vec![(
+                vec![key("vertex")],
+                "invalid 'vertex' value '[...]', expected a list of points".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn schema_of_root() {
+        let subject = json_schema();
+        assert_eq!(subject["type"], "object");
+        assert_eq!(
+            subject["required"],
+            serde_json::json!(["width", "height", "layers"])
+        );
+        assert_eq!(
+            subject["properties"]["layers"]["items"]["$ref"],
+            "#/definitions/layer"
+        );
+        assert_eq!(
+            subject["properties"]["defs"]["additionalProperties"]["$ref"],
+            "#/definitions/component"
+        );
+    }
+
+    #[test]
+    fn schema_of_layers() {
+        let subject = json_schema();
+        let definitions = &subject["definitions"];
+        // Every layer type, `repeat` and `include`.
+        assert_eq!(definitions["layer"]["oneOf"].as_array().unwrap().len(), 14);
+        assert_eq!(
+            definitions["circle"]["else"]["required"],
+            serde_json::json!(["x", "y", "radius", "color"])
+        );
+        assert_eq!(
+            definitions["circle"]["if"],
+            serde_json::json!({"required": ["class"]})
+        );
+        assert_eq!(
+            definitions["circle"]["properties"]["class"]["anyOf"][1],
+            serde_json::json!({"type": "array", "items": {"type": "string"}})
+        );
+        assert_eq!(
+            definitions["circle"]["properties"]["fill"]["default"],
+            false
+        );
+        assert_eq!(
+            definitions["circle"]["properties"]["stroke"]["$ref"],
+            "#/definitions/stroke"
+        );
+        assert_eq!(
+            definitions["curve"]["properties"]["fill"],
+            serde_json::Value::Null
+        );
+        assert_eq!(
+            definitions["line"]["properties"]["points"]["$ref"],
+            "#/definitions/points"
+        );
+        assert_eq!(definitions["text"]["properties"]["size"]["default"], 14.0);
+    }
+
+    #[test]
+    fn schema_of_options() {
+        let subject = json_schema();
+        let definitions = &subject["definitions"];
+        assert_eq!(
+            definitions["stroke"]["properties"]["cap"],
+            serde_json::json!({
+                "type": "string",
+                "enum": ["butt", "round", "square"],
+                "default": "butt"
+            })
+        );
+        assert_eq!(definitions["scale"]["properties"]["x"]["default"], 1.0);
+        assert_eq!(definitions["point"]["minItems"], 2);
+        assert_eq!(definitions["span"]["required"], serde_json::json!(["text"]));
+    }
+}
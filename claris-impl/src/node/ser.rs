@@ -0,0 +1,681 @@
+use yaml_rust::yaml::Hash;
+use yaml_rust::{Yaml, YamlLoader};
+
+use crate::node::schema::{self, DefaultValue, Field, Kind};
+use crate::source::{trivia, Entry, Segment};
+
+// How the children of a node are ordered and written.
+#[derive(Clone, Copy)]
+enum Context<'a> {
+    Kind(Kind),
+    Fields(&'a [Field]),
+    // An entry of `layers:`, named by its only key.
+    Layer,
+}
+
+// Comments and empty lines kept with a node.
+#[derive(Debug, Default)]
+struct Notes {
+    leading: Vec<Option<String>>,
+    trailing: Option<String>,
+}
+
+type Attached = Vec<(Vec<Segment>, Notes)>;
+
+struct Writer {
+    out: String,
+    notes: Attached,
+}
+
+/// Writes a source document as canonical YAML: options in the order they
+/// are documented, points as `[x, y]`, layer options equal to their default
+/// left out, along with options such as `stroke` that are left with none,
+/// and strings quoted only when needed. The comments and empty
+/// lines of `text`, the source `src` was read from with the positions in
+/// `entries`, stay with the node they precede or follow.
+///
+/// Options of layers with a `class` are kept as written, since a style may
+/// override their default.
+pub fn to_canonical_yaml(src: &Yaml, text: &str, entries: &[Entry]) -> String {
+    let (notes, end) = attach(text, entries);
+    let mut writer = Writer {
+        out: "---\n".to_string(),
+        notes,
+    };
+
+    let root = Vec::new();
+    writer.leading(&root, 0, true);
+    match src {
+        Yaml::Hash(x) if !x.is_empty() => {
+            let fields = schema::root();
+            writer.pairs(x, Context::Fields(&fields), false, 0, &root, false);
+        }
+        // An included list of layers.
+        Yaml::Array(x) if !x.is_empty() => {
+            writer.items(x, Context::Kind(Kind::Layers), false, 0, &root)
+        }
+        _ => {
+            writer.out.push_str(&inline(src));
+            writer.out.push('\n');
+        }
+    }
+    for note in collapse(end, false) {
+        writer.note(note, 0);
+    }
+
+    writer.out
+}
+
+// Gives each comment or empty line to the node written next, and each
+// inline comment to the outermost node starting before it on its line. A
+// comment after a bare `-` belongs to that sequence item.
+fn attach(text: &str, entries: &[Entry]) -> (Attached, Vec<Option<String>>) {
+    let start = |x: &Entry| {
+        let mark = x.key.unwrap_or(x.value);
+        (mark.line, mark.col)
+    };
+    let mut notes: Attached = Vec::new();
+    let mut end = Vec::new();
+
+    for item in trivia(text) {
+        let at = (item.line, item.col);
+        let owner = match item.inline {
+            true => entries
+                .iter()
+                .filter(|x| start(x).0 == item.line && start(x) < at)
+                .min_by_key(|x| (start(x).1, usize::MAX - x.path.len())),
+            false => None,
+        };
+        let next = || {
+            entries
+                .iter()
+                .filter(|x| start(x) > at)
+                .min_by_key(|x| (start(x), x.path.len()))
+        };
+        let (entry, trailing) = match owner {
+            Some(x) => (Some(x), true),
+            None => (next(), item.inline),
+        };
+
+        match entry {
+            Some(x) => {
+                let i = match notes.iter().position(|(path, _)| *path == x.path) {
+                    Some(i) => i,
+                    None => {
+                        notes.push((x.path.clone(), Notes::default()));
+                        notes.len() - 1
+                    }
+                };
+                let found = &mut notes[i].1;
+                match (trailing, item.text) {
+                    (true, Some(text)) => {
+                        found.trailing = Some(match found.trailing.take() {
+                            Some(x) => format!("{} {}", x, text),
+                            None => text,
+                        })
+                    }
+                    (_, text) => found.leading.push(text),
+                }
+            }
+            None => end.push(item.text),
+        }
+    }
+
+    (notes, end)
+}
+
+// Drops repeated empty lines, and every empty line at the start of a block.
+fn collapse(notes: Vec<Option<String>>, first: bool) -> Vec<Option<String>> {
+    let mut ret: Vec<Option<String>> = Vec::new();
+    for note in notes {
+        let blank = note.is_none();
+        if blank && ((first && ret.is_empty()) || ret.last().is_some_and(Option::is_none)) {
+            continue;
+        }
+        ret.push(note);
+    }
+    if first && ret.iter().all(Option::is_none) {
+        ret.clear();
+    }
+    ret
+}
+
+impl Writer {
+    fn notes(&self, path: &[Segment]) -> Option<&Notes> {
+        self.notes
+            .iter()
+            .find(|(x, _)| x.as_slice() == path)
+            .map(|(_, x)| x)
+    }
+
+    fn trailing(&self, path: &[Segment]) -> Option<String> {
+        self.notes(path).and_then(|x| x.trailing.clone())
+    }
+
+    fn has_leading(&self, path: &[Segment]) -> bool {
+        self.notes(path)
+            .is_some_and(|x| x.leading.iter().any(Option::is_some))
+    }
+
+    fn leading(&mut self, path: &[Segment], indent: usize, first: bool) {
+        let notes = self
+            .notes(path)
+            .map(|x| x.leading.clone())
+            .unwrap_or_default();
+        for note in collapse(notes, first) {
+            self.note(note, indent);
+        }
+    }
+
+    fn note(&mut self, note: Option<String>, indent: usize) {
+        if let Some(x) = note {
+            self.out.push_str(&" ".repeat(indent));
+            self.out.push_str(&x);
+        }
+        self.out.push('\n');
+    }
+
+    fn comment(&mut self, comment: Option<String>) {
+        if let Some(x) = comment {
+            self.out.push(' ');
+            self.out.push_str(&x);
+        }
+    }
+
+    // Writes the entries of a mapping at `indent`. The first one continues
+    // the current line after a `-` when `continued`.
+    fn pairs(
+        &mut self,
+        src: &Hash,
+        context: Context,
+        omit: bool,
+        indent: usize,
+        path: &[Segment],
+        continued: bool,
+    ) {
+        let fields: &[Field] = match context {
+            Context::Fields(x) => x,
+            Context::Kind(Kind::Options(x)) => x,
+            _ => &[],
+        };
+        let layers: Vec<Vec<Field>> = match context {
+            Context::Layer => src
+                .keys()
+                .map(|x| x.as_str().and_then(schema::layer).unwrap_or_default())
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        let empty = Yaml::Hash(Hash::new());
+        let mut children: Vec<(&Yaml, &Yaml, Context, bool)> = Vec::new();
+        for field in fields {
+            if let Some((key, value)) = src.iter().find(|(x, _)| x.as_str() == Some(field.name)) {
+                if omit && is_omitted(value, field) {
+                    continue;
+                }
+                // Written as `{}` rather than as an empty value.
+                let value = match omit && is_emptied(value, field.kind) {
+                    true => &empty,
+                    false => value,
+                };
+                children.push((key, value, Context::Kind(field.kind), omit));
+            }
+        }
+        for (i, (key, value)) in src.iter().enumerate() {
+            if fields.iter().any(|x| key.as_str() == Some(x.name)) {
+                continue;
+            }
+            let child = match context {
+                Context::Layer if !layers[i].is_empty() => Context::Fields(&layers[i]),
+                Context::Layer if key.as_str() == Some("include") => Context::Kind(Kind::Any),
+                Context::Kind(Kind::Map(x)) => Context::Kind(*x),
+                _ => Context::Kind(Kind::Any),
+            };
+            // A style may override any option of a layer with a class.
+            let omit = match context {
+                Context::Layer => !value
+                    .as_hash()
+                    .is_some_and(|x| x.contains_key(&Yaml::from_str("class"))),
+                _ => false,
+            };
+            children.push((key, value, child, omit));
+        }
+
+        for (i, (key, value, child, omit)) in children.into_iter().enumerate() {
+            let mut path = path.to_vec();
+            path.push(Segment::Key(key.as_str().unwrap_or("").to_string()));
+            if i == 0 && continued && !self.has_leading(&path) {
+                self.out.push(' ');
+            } else {
+                if i == 0 && continued {
+                    self.out.push('\n');
+                }
+                self.leading(&path, indent, i == 0);
+                self.out.push_str(&" ".repeat(indent));
+            }
+            self.out.push_str(&inline(key));
+            self.out.push(':');
+            let trailing = self.trailing(&path);
+            self.node(value, child, omit, indent + 2, &path, trailing);
+        }
+    }
+
+    fn items(
+        &mut self,
+        src: &[Yaml],
+        context: Context,
+        omit: bool,
+        indent: usize,
+        path: &[Segment],
+    ) {
+        let child = match context {
+            Context::Kind(Kind::Layers) => Context::Layer,
+            Context::Kind(Kind::ListOf(x)) => Context::Fields(x),
+            Context::Kind(Kind::Points) => Context::Kind(Kind::Point),
+            _ => Context::Kind(Kind::Any),
+        };
+
+        for (i, item) in src.iter().enumerate() {
+            let point = match child {
+                Context::Kind(Kind::Point) => as_array(item),
+                _ => None,
+            };
+            let item = point.as_ref().unwrap_or(item);
+            let mut path = path.to_vec();
+            path.push(Segment::Index(i));
+            self.leading(&path, indent, i == 0);
+            self.out.push_str(&" ".repeat(indent));
+            self.out.push('-');
+            let trailing = self.trailing(&path);
+            match item {
+                Yaml::Hash(x) if !x.is_empty() => match trailing {
+                    Some(_) => {
+                        self.comment(trailing);
+                        self.out.push('\n');
+                        self.pairs(x, child, omit, indent + 2, &path, false);
+                    }
+                    None => self.pairs(x, child, omit, indent + 2, &path, true),
+                },
+                _ => self.node(item, child, omit, indent + 2, &path, trailing),
+            }
+        }
+    }
+
+    // Writes a value after its `key:` or `-`.
+    fn node(
+        &mut self,
+        src: &Yaml,
+        context: Context,
+        omit: bool,
+        indent: usize,
+        path: &[Segment],
+        trailing: Option<String>,
+    ) {
+        let point = match context {
+            Context::Kind(Kind::Point) => as_array(src),
+            _ => None,
+        };
+        let src = point.as_ref().unwrap_or(src);
+        match src {
+            Yaml::Hash(x) if !x.is_empty() => {
+                self.comment(trailing);
+                self.out.push('\n');
+                self.pairs(x, context, omit, indent, path, false);
+            }
+            Yaml::Array(x) if !x.is_empty() && !is_flow(x) => {
+                self.comment(trailing);
+                self.out.push('\n');
+                self.items(x, context, omit, indent, path);
+            }
+            _ => {
+                self.out.push(' ');
+                self.out.push_str(&inline(src));
+                self.comment(trailing);
+                self.out.push('\n');
+            }
+        }
+    }
+}
+
+// Lists of numbers, such as points, are written on one line.
+fn is_flow(src: &[Yaml]) -> bool {
+    src.iter()
+        .all(|x| matches!(x, Yaml::Integer(_) | Yaml::Real(_)))
+}
+
+// Options left out: those equal to their default, and those such as
+// `stroke` whose options are all left out, as they mean the same as none.
+fn is_omitted(src: &Yaml, field: &Field) -> bool {
+    match (field.default, field.kind) {
+        (Some(x), _) => is_default(src, x),
+        (None, Kind::Options(fields)) => {
+            !fields.iter().any(|x| x.required) && is_emptied(src, field.kind)
+        }
+        _ => false,
+    }
+}
+
+// Whether every option of `src` is left out.
+fn is_emptied(src: &Yaml, kind: Kind) -> bool {
+    match (kind, src) {
+        (Kind::Options(fields), Yaml::Hash(x)) => x.iter().all(|(key, value)| {
+            fields
+                .iter()
+                .find(|x| key.as_str() == Some(x.name))
+                .is_some_and(|x| is_omitted(value, x))
+        }),
+        _ => false,
+    }
+}
+
+// A point written as `{x: 1, y: 2}`.
+fn as_array(src: &Yaml) -> Option<Yaml> {
+    let x = src.as_hash()?;
+    let keys = [Yaml::from_str("x"), Yaml::from_str("y")];
+    if x.len() != 2 || !keys.iter().all(|k| x.contains_key(k)) {
+        return None;
+    }
+    Some(Yaml::Array(keys.iter().map(|k| x[k].clone()).collect()))
+}
+
+fn is_default(src: &Yaml, default: DefaultValue) -> bool {
+    match (src, default) {
+        (Yaml::Integer(x), DefaultValue::Number(y)) => (*x as f64 - y).abs() < f64::EPSILON,
+        (Yaml::Real(x), DefaultValue::Number(y)) => {
+            x.parse::<f64>().is_ok_and(|x| (x - y).abs() < f64::EPSILON)
+        }
+        (Yaml::Boolean(x), DefaultValue::Bool(y)) => *x == y,
+        (Yaml::String(x), DefaultValue::Text(y)) => x == y,
+        _ => false,
+    }
+}
+
+fn inline(src: &Yaml) -> String {
+    match src {
+        Yaml::Real(x) => x.clone(),
+        Yaml::Integer(x) => x.to_string(),
+        Yaml::Boolean(x) => x.to_string(),
+        Yaml::String(x) => scalar(x),
+        Yaml::Array(x) => format!(
+            "[{}]",
+            x.iter().map(inline).collect::<Vec<String>>().join(", ")
+        ),
+        Yaml::Hash(_) => "{}".to_string(),
+        _ => "null".to_string(),
+    }
+}
+
+// Strings are left plain when they read back as the same string, and
+// double quoted otherwise.
+fn scalar(src: &str) -> String {
+    let plain = !src.is_empty()
+        && src.trim() == src
+        && !src.contains(char::is_control)
+        && match YamlLoader::load_from_str(&format!("- {}", src)) {
+            Ok(x) => {
+                x.first().and_then(|x| x.as_vec()).map(Vec::as_slice)
+                    == Some(&[Yaml::String(src.to_string())][..])
+            }
+            Err(_) => false,
+        };
+    if plain {
+        return src.to_string();
+    }
+
+    let mut ret = String::from("\"");
+    for c in src.chars() {
+        match c {
+            '"' => ret.push_str("\\\""),
+            '\\' => ret.push_str("\\\\"),
+            '\n' => ret.push_str("\\n"),
+            '\t' => ret.push_str("\\t"),
+            '\r' => ret.push_str("\\r"),
+            c if c.is_control() => ret.push_str(&format!("\\u{:04X}", c as u32)),
+            c => ret.push(c),
+        }
+    }
+    ret.push('"');
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_canonical_yaml;
+    use crate::expr::Scope;
+    use crate::node::Root;
+    use crate::source::SourceMap;
+    use yaml_rust::YamlLoader;
+
+    fn format(src: &str) -> String {
+        let doc = YamlLoader::load_from_str(src).unwrap().remove(0);
+        let entries = SourceMap::new().add_file("main.yml", src);
+        to_canonical_yaml(&doc, src, &entries)
+    }
+
+    #[test]
+    fn canonical_order_and_defaults() {
+        let src = "width: 400
+layers:
+  - circle: {radius: 10, color: '#FF0000', y: 20, x: 10, fill: false, alpha: 1.0}
+  - line:
+      stroke: {cap: round, width: 1}
+      points: [[0, 0], [10, 10]]
+      color: blue
+height: 300
+";
+        assert_eq!(
+            format(src),
+            "---
+width: 400
+height: 300
+layers:
+  - circle:
+      x: 10
+      y: 20
+      radius: 10
+      color: \"#FF0000\"
+  - line:
+      points:
+        - [0, 0]
+        - [10, 10]
+      color: blue
+      stroke:
+        cap: round
+"
+        );
+    }
+
+    #[test]
+    fn comments_and_empty_lines() {
+        let src = "---
+# A sample.
+width: 10 # wide
+height: 10
+layers:
+  - # background
+    rectangle:
+      color: red
+      x: 0
+
+
+  # a dot
+  - circle: {x: 1, y: 1, radius: 1, color: red}  # small
+# end
+";
+        assert_eq!(
+            format(src),
+            "---
+# A sample.
+width: 10 # wide
+height: 10
+layers:
+  - # background
+    rectangle:
+      x: 0
+      color: red
+
+  # a dot
+  - circle: # small
+      x: 1
+      y: 1
+      radius: 1
+      color: red
+# end
+"
+        );
+    }
+
+    #[test]
+    fn defaults_of_classes_are_kept() {
+        let src = "---
+width: 10
+height: 10
+styles:
+  thick: {fill: true}
+layers:
+  - circle: {class: thick, x: 1, y: 1, radius: 1, color: red, fill: false}
+  - repeat:
+      count: 2
+      x: 0
+      layers:
+        - use: {component: dot, x: 0}
+";
+        assert_eq!(
+            format(src),
+            "---
+width: 10
+height: 10
+styles:
+  thick:
+    fill: true
+layers:
+  - circle:
+      class: thick
+      x: 1
+      y: 1
+      radius: 1
+      color: red
+      fill: false
+  - repeat:
+      count: 2
+      layers:
+        - use:
+            component: dot
+"
+        );
+    }
+
+    #[test]
+    fn quoting() {
+        let src = "---
+width: '10'
+height: 10
+vars: {a: '@primary', b: 'true', c: \"$w * 2\", d: \"two\\nlines\", e: 'it''s'}
+layers: []
+";
+        assert_eq!(
+            format(src),
+            "---
+width: \"10\"
+height: 10
+vars:
+  a: \"@primary\"
+  b: \"true\"
+  c: $w * 2
+  d: \"two\\nlines\"
+  e: it's
+layers: []
+"
+        );
+    }
+
+    #[test]
+    fn idempotent() {
+        let src = "---
+width: 10
+height: 10
+defs:
+  dot:
+    layers:
+      - text:
+          spans:
+            - {size: 3, text: a}
+          y: 1
+          x: 1 # here
+          color: red
+layers: # all
+  - use: {component: dot}
+";
+        let once = format(src);
+        assert_eq!(format(&once), once);
+        assert!(once.contains("      - text:\n          x: 1 # here\n          y: 1\n"));
+        assert!(once.contains("            - text: a\n              size: 3\n"));
+    }
+
+    #[test]
+    fn emptied_options_and_points() {
+        let src = "---
+width: 10
+height: 10
+layers:
+  - line:
+      points: [{x: 0, y: 0}, {y: 10, x: 5}]
+      color: red
+      stroke: {width: 1, cap: butt}
+      scale: {x: 1}
+  - curve:
+      start: {x: 0, y: 0}
+      mid: [5, 5]
+      end: [10, 0]
+      color: red
+  - text:
+      x: 0
+      y: 0
+      text: a
+      color: red
+      outline: {width: 1, color: blue}
+";
+        let once = format(src);
+        assert_eq!(
+            once,
+            "---
+width: 10
+height: 10
+layers:
+  - line:
+      points:
+        - [0, 0]
+        - [5, 10]
+      color: red
+  - curve:
+      start: [0, 0]
+      mid: [5, 5]
+      end: [10, 0]
+      color: red
+  - text:
+      x: 0
+      y: 0
+      text: a
+      color: red
+      outline:
+        color: blue
+"
+        );
+        assert_eq!(format(&once), once);
+
+        let mut scope = Scope::new();
+        scope.set_strict(true);
+        let doc = YamlLoader::load_from_str(&once).unwrap().remove(0);
+        if let Err(e) = Root::parse(&doc, &scope) {
+            panic!(e.to_string());
+        }
+    }
+
+    #[test]
+    fn options_without_any_left_are_kept_as_empty() {
+        let src = "---\nlayers:\n  - text: {text: a, outline: {width: 1}}\n";
+        let once = format(src);
+        assert!(once.contains("      outline: {}\n"));
+        assert_eq!(format(&once), once);
+    }
+}
@@ -0,0 +1,186 @@
+use cairo::FontSlant;
+use cairo::FontWeight;
+use yaml_rust::Yaml;
+
+use crate::ext::YamlExt;
+use crate::node::color::Color;
+use crate::node::error::NodeError;
+
+/// A run of text with its own style. Unset fields fall back to the style of
+/// the enclosing text layer.
+#[derive(Debug, Clone)]
+pub struct Span {
+    pub text: String,
+    pub color: Color,
+    pub family: String,
+    pub size: f64,
+    pub weight: FontWeight,
+    pub slant: FontSlant,
+    pub underline: bool,
+    pub strikethrough: bool,
+}
+
+impl Span {
+    pub fn parse(src: &Yaml, base: &Span) -> Result<Span, NodeError> {
+        let text = src
+            .string_val("text")
+            .ok_or_else(|| NodeError::Required("span".to_string(), "text".to_string()))?;
+        let alpha = src.f32_val("alpha");
+        let color = match src.string_val("color") {
+            Some(x) => Color::parse(x)
+                .map(|c| -> Color { Color::new(c.r, c.g, c.b, c.a * alpha.unwrap_or(1.0)) })?,
+            None => {
+                let c = base.color;
+                Color::new(c.r, c.g, c.b, alpha.unwrap_or(c.a))
+            }
+        };
+        let family = src
+            .string_val("family")
+            .unwrap_or_else(|| base.family.clone());
+        let size = src.f64_val("size").unwrap_or(base.size);
+        if size <= 0.0 || !size.is_finite() {
+            return Err(NodeError::InvalidValue(
+                "size".to_string(),
+                size.to_string(),
+                "a positive number".to_string(),
+            ));
+        }
+        let weight = match src.str_val("weight") {
+            Some("bold") => FontWeight::Bold,
+            Some("normal") => FontWeight::Normal,
+            _ => base.weight,
+        };
+        let slant = match src.str_val("slant") {
+            Some("italic") => FontSlant::Italic,
+            Some("oblique") => FontSlant::Oblique,
+            Some("normal") => FontSlant::Normal,
+            _ => base.slant,
+        };
+        let underline = src.bool_val("underline").unwrap_or(base.underline);
+        let strikethrough = src.bool_val("strikethrough").unwrap_or(base.strikethrough);
+
+        Ok(Span {
+            text,
+            color,
+            family,
+            size,
+            weight,
+            slant,
+            underline,
+            strikethrough,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Span;
+    use crate::node::Color;
+    use crate::parse_yaml;
+    use cairo::{FontSlant, FontWeight};
+    use float_cmp::approx_eq;
+    use yaml_rust::YamlLoader;
+
+    fn base() -> Span {
+        Span {
+            text: String::new(),
+            color: Color::new(170, 187, 204, 0.5),
+            family: "serif".to_string(),
+            size: 14.0,
+            weight: FontWeight::Normal,
+            slant: FontSlant::Normal,
+            underline: false,
+            strikethrough: false,
+        }
+    }
+
+    macro_rules! parse {
+        ($x:expr) => {{
+            let src = parse_yaml!($x);
+            match Span::parse(&src, &base()) {
+                Ok(x) => x,
+                Err(e) => panic!(e.to_string()),
+            }
+        }};
+    }
+
+    #[test]
+    fn inherits_base_style() {
+        let s = "---
+text: test
+";
+        let subject = parse!(s);
+        assert_eq!(subject.text, "test");
+        assert_eq!(subject.color.r, 170);
+        assert!(approx_eq!(f32, subject.color.a, 0.5));
+        assert_eq!(subject.family, "serif");
+        assert!(approx_eq!(f64, subject.size, 14.0));
+        assert_eq!(subject.weight, FontWeight::Normal);
+        assert_eq!(subject.slant, FontSlant::Normal);
+        assert!(!subject.underline);
+        assert!(!subject.strikethrough);
+    }
+
+    #[test]
+    fn overrides_base_style() {
+        let s = "---
+text: test
+color: '#FF0000'
+family: sans-serif
+size: 20
+weight: bold
+slant: italic
+underline: true
+strikethrough: true
+";
+        let subject = parse!(s);
+        assert_eq!(subject.color.r, 255);
+        assert!(approx_eq!(f32, subject.color.a, 1.0));
+        assert_eq!(subject.family, "sans-serif");
+        assert!(approx_eq!(f64, subject.size, 20.0));
+        assert_eq!(subject.weight, FontWeight::Bold);
+        assert_eq!(subject.slant, FontSlant::Italic);
+        assert!(subject.underline);
+        assert!(subject.strikethrough);
+    }
+
+    #[test]
+    fn alpha_without_color() {
+        let s = "---
+text: test
+alpha: 0.25
+";
+        let subject = parse!(s);
+        assert_eq!(subject.color.r, 170);
+        assert!(approx_eq!(f32, subject.color.a, 0.25));
+    }
+
+    #[test]
+    #[should_panic(expected = "'span' is required 'text' option")]
+    fn text_is_blank() {
+        let s = "---
+weight: bold
+";
+        parse!(s);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid color format 'foo'")]
+    fn color_is_invalid() {
+        let s = "---
+text: test
+color: foo
+";
+        parse!(s);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid 'size' value '0', expected a positive number")]
+    fn size_is_not_positive() {
+        let s = "---
+text: test
+size: 0
+";
+        parse!(s);
+    }
+}
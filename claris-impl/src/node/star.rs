@@ -0,0 +1,270 @@
+use std::f64::consts::PI;
+use yaml_rust::Yaml;
+
+use crate::ext::YamlExt;
+use crate::node::color::Color;
+use crate::node::count::parse_count;
+use crate::node::error::NodeError;
+use crate::node::point::Point;
+use crate::node::scale::Scale;
+use crate::node::stroke::Stroke;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Star {
+    pub x: f64,
+    pub y: f64,
+    pub points: u32,
+    pub outer_radius: f64,
+    pub inner_radius: f64,
+    pub rotation: f64,
+    pub fill: bool,
+    pub color: Color,
+    pub stroke: Stroke,
+    pub scale: Scale,
+}
+
+impl Star {
+    const MIN_POINTS: u32 = 2;
+    const MAX_POINTS: u32 = 10_000;
+
+    pub fn parse(src: &Yaml) -> Result<Star, NodeError> {
+        let x = src
+            .f64_val("x")
+            .ok_or_else(|| NodeError::Required("star".to_string(), "x".to_string()))?;
+        let y = src
+            .f64_val("y")
+            .ok_or_else(|| NodeError::Required("star".to_string(), "y".to_string()))?;
+        let points = parse_count(src, "star", "points", Self::MIN_POINTS, Self::MAX_POINTS)?
+            .ok_or_else(|| NodeError::Required("star".to_string(), "points".to_string()))?;
+        let outer_radius = src
+            .f64_val("outer_radius")
+            .ok_or_else(|| NodeError::Required("star".to_string(), "outer_radius".to_string()))?;
+        let inner_radius = src
+            .f64_val("inner_radius")
+            .ok_or_else(|| NodeError::Required("star".to_string(), "inner_radius".to_string()))?;
+        let rotation = src.f64_val("rotation").unwrap_or(0.0);
+        let fill = src.bool_val("fill").unwrap_or(false);
+        let alpha = src.f32_val("alpha").unwrap_or(1.0);
+        let color = src
+            .string_val("color")
+            .ok_or_else(|| NodeError::Required("star".to_string(), "color".to_string()))
+            .and_then(|x| -> Result<Color, NodeError> {
+                Color::parse(x).map(|c| -> Color { Color::new(c.r, c.g, c.b, c.a * alpha) })
+            })?;
+        let stroke = src
+            .hash_val("stroke")
+            .map_or(Stroke::default(), |x| -> Stroke { Stroke::parse(x) });
+        let scale = src
+            .hash_val("scale")
+            .map_or(Scale::default(), |x| -> Scale { Scale::parse(x) });
+
+        Ok(Star {
+            x,
+            y,
+            points,
+            outer_radius,
+            inner_radius,
+            rotation,
+            fill,
+            color,
+            stroke,
+            scale,
+        })
+    }
+
+    /// Vertices relative to the centre, alternating between the outer and
+    /// inner radius, starting with the top point and going clockwise.
+    pub fn vertex(&self) -> Vec<Point> {
+        let offset = self.rotation * (PI / 180.0) - PI / 2.0;
+        let step = PI / self.points as f64;
+        (0..self.points * 2)
+            .map(|i| -> Point {
+                let angle = offset + step * i as f64;
+                let radius = if i % 2 == 0 {
+                    self.outer_radius
+                } else {
+                    self.inner_radius
+                };
+                Point {
+                    x: radius * angle.cos(),
+                    y: radius * angle.sin(),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Star;
+    use crate::parse_yaml;
+    use float_cmp::approx_eq;
+    use yaml_rust::YamlLoader;
+
+    macro_rules! parse {
+        ($x:expr) => {{
+            let src = parse_yaml!($x);
+            match Star::parse(&src) {
+                Ok(x) => x,
+                Err(e) => panic!(e.to_string()),
+            }
+        }};
+    }
+
+    #[test]
+    fn parse_x_y() {
+        let s = "---
+x: 10
+y: 20.5
+color: '#AABBCC'
+points: 5
+outer_radius: 20
+inner_radius: 10
+";
+        let subject = parse!(s);
+        assert!(approx_eq!(f64, subject.x, 10.0));
+        assert!(approx_eq!(f64, subject.y, 20.5));
+    }
+
+    #[test]
+    #[should_panic(expected = "'star' is required 'x' option")]
+    fn x_is_blank() {
+        let s = "---
+y: 20
+color: '#AABBCC'
+points: 5
+outer_radius: 20
+inner_radius: 10
+";
+        parse!(s);
+    }
+
+    #[test]
+    #[should_panic(expected = "'star' is required 'y' option")]
+    fn y_is_blank() {
+        let s = "---
+x: 10
+color: '#AABBCC'
+points: 5
+outer_radius: 20
+inner_radius: 10
+";
+        parse!(s);
+    }
+
+    #[test]
+    fn points_and_radii_are_not_blank() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+points: 5
+outer_radius: 20
+inner_radius: 10.5
+";
+        let subject = parse!(s);
+        assert_eq!(subject.points, 5);
+        assert!(approx_eq!(f64, subject.outer_radius, 20.0));
+        assert!(approx_eq!(f64, subject.inner_radius, 10.5));
+    }
+
+    #[test]
+    #[should_panic(expected = "'star' is required 'points' option")]
+    fn points_is_blank() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+outer_radius: 20
+inner_radius: 10
+";
+        parse!(s);
+    }
+
+    #[test]
+    #[should_panic(expected = "'star' option 'points' must be at least 2")]
+    fn points_is_too_few() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+points: 1
+outer_radius: 20
+inner_radius: 10
+";
+        parse!(s);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid 'points' value '5.5', expected a whole number")]
+    fn points_is_fractional() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+points: 5.5
+outer_radius: 20
+inner_radius: 10
+";
+        parse!(s);
+    }
+
+    #[test]
+    #[should_panic(expected = "'star' is required 'outer_radius' option")]
+    fn outer_radius_is_blank() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+points: 5
+inner_radius: 10
+";
+        parse!(s);
+    }
+
+    #[test]
+    #[should_panic(expected = "'star' is required 'inner_radius' option")]
+    fn inner_radius_is_blank() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+points: 5
+outer_radius: 20
+";
+        parse!(s);
+    }
+
+    #[test]
+    #[should_panic(expected = "'star' is required 'color' option")]
+    fn color_is_blank() {
+        let s = "---
+x: 10
+y: 20
+points: 5
+outer_radius: 20
+inner_radius: 10
+";
+        parse!(s);
+    }
+
+    #[test]
+    fn vertex() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+points: 4
+outer_radius: 20
+inner_radius: 10
+";
+        let subject = parse!(s).vertex();
+        assert_eq!(subject.len(), 8);
+        assert!(approx_eq!(f64, subject[0].x, 0.0, epsilon = 1e-9));
+        assert!(approx_eq!(f64, subject[0].y, -20.0, epsilon = 1e-9));
+        assert!(approx_eq!(f64, subject[2].x, 20.0, epsilon = 1e-9));
+        assert!(approx_eq!(f64, subject[2].y, 0.0, epsilon = 1e-9));
+        let inner = (subject[1].x.powi(2) + subject[1].y.powi(2)).sqrt();
+        assert!(approx_eq!(f64, inner, 10.0, epsilon = 1e-9));
+    }
+}
@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+
+use yaml_rust::yaml::Hash;
+use yaml_rust::Yaml;
+
+use crate::ext::YamlExt;
+use crate::node::error::NodeError;
+
+/// Named sets of layer options declared under `styles:`.
+#[derive(Debug, Clone, Default)]
+pub struct Styles {
+    styles: HashMap<String, Hash>,
+}
+
+impl Styles {
+    pub fn parse(src: &Yaml) -> Result<Styles, NodeError> {
+        let mut styles = HashMap::new();
+
+        if let Some(x) = src.hash_val("styles") {
+            for (key, value) in x {
+                let name = key.as_str().ok_or(NodeError::InvalidStyle)?;
+                let style = value.as_hash().ok_or(NodeError::InvalidStyle)?;
+                styles.insert(name.to_string(), style.clone());
+            }
+        }
+
+        Ok(Styles { styles })
+    }
+
+    /// Returns the layer options of `src` on top of the styles named by its
+    /// `class`, which may list several names. Later classes override earlier
+    /// ones and explicit options override every class.
+    pub fn apply(&self, src: &Yaml) -> Result<Yaml, NodeError> {
+        let entry = match src {
+            Yaml::Hash(x) => x,
+            _ => return Ok(src.clone()),
+        };
+        let classes = match entry.get(&Yaml::from_str("class")) {
+            Some(x) => Self::parse_class(x)?,
+            None => return Ok(src.clone()),
+        };
+
+        let mut ret = Hash::new();
+        for class in classes {
+            let style = self
+                .styles
+                .get(class)
+                .ok_or_else(|| NodeError::UnknownClass(class.to_string()))?;
+            merge(&mut ret, style);
+        }
+        merge(&mut ret, entry);
+        ret.remove(&Yaml::from_str("class"));
+
+        Ok(Yaml::Hash(ret))
+    }
+
+    fn parse_class(src: &Yaml) -> Result<Vec<&str>, NodeError> {
+        match src {
+            Yaml::String(x) => Ok(x.split_whitespace().collect()),
+            Yaml::Array(x) => x
+                .iter()
+                .map(|x| x.as_str().ok_or(NodeError::InvalidStyle))
+                .collect(),
+            _ => Err(NodeError::InvalidStyle),
+        }
+    }
+}
+
+// Nested options such as `stroke` are merged key by key.
+fn merge(dst: &mut Hash, src: &Hash) {
+    for (key, value) in src {
+        match (dst.get_mut(key), value) {
+            (Some(Yaml::Hash(d)), Yaml::Hash(s)) => merge(d, s),
+            _ => {
+                dst.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Styles;
+    use crate::parse_yaml;
+    use yaml_rust::{Yaml, YamlLoader};
+
+    fn styles() -> Styles {
+        let src = parse_yaml!(
+            "---
+styles:
+  primary:
+    color: '#3366FF'
+    alpha: 0.5
+    stroke:
+      width: 3
+      cap: round
+  filled:
+    fill: true
+    alpha: 0.8
+"
+        );
+        Styles::parse(&src).unwrap()
+    }
+
+    #[test]
+    fn apply_classes() {
+        let src = parse_yaml!(
+            "---
+class: primary filled
+x: 10
+stroke:
+  width: 1
+"
+        );
+        let subject = styles().apply(&src).unwrap();
+        assert_eq!(subject["color"], Yaml::String("#3366FF".to_string()));
+        assert_eq!(subject["alpha"], Yaml::Real("0.8".to_string()));
+        assert_eq!(subject["fill"], Yaml::Boolean(true));
+        assert_eq!(subject["x"], Yaml::Integer(10));
+        assert_eq!(subject["stroke"]["width"], Yaml::Integer(1));
+        assert_eq!(subject["stroke"]["cap"], Yaml::String("round".to_string()));
+        assert!(subject["class"].is_badvalue());
+    }
+
+    #[test]
+    fn apply_class_list() {
+        let src = parse_yaml!(
+            "---
+class: [filled, primary]
+color: '#000000'
+"
+        );
+        let subject = styles().apply(&src).unwrap();
+        assert_eq!(subject["alpha"], Yaml::Real("0.5".to_string()));
+        assert_eq!(subject["color"], Yaml::String("#000000".to_string()));
+    }
+
+    #[test]
+    fn without_class() {
+        let src = parse_yaml!("---\nx: 10\n");
+        let subject = styles().apply(&src).unwrap();
+        assert_eq!(subject, src);
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown class 'secondary'")]
+    fn unknown_class() {
+        let src = parse_yaml!("---\nclass: secondary\n");
+        if let Err(e) = styles().apply(&src) {
+            panic!(e.to_string());
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid style")]
+    fn invalid_style() {
+        let src = parse_yaml!("---\nstyles:\n  primary: 1\n");
+        if let Err(e) = Styles::parse(&src) {
+            panic!(e.to_string());
+        }
+    }
+}
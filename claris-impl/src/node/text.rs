@@ -6,6 +6,13 @@ use crate::ext::YamlExt;
 use crate::node::color::Color;
 use crate::node::error::NodeError;
 use crate::node::scale::Scale;
+use crate::node::{Background, Fit, Outline, Span, TextPath};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextDirection {
+    Horizontal,
+    Vertical,
+}
 
 #[derive(Debug, Clone)]
 pub struct Text {
@@ -18,11 +25,23 @@ pub struct Text {
     pub size: f64,
     pub weight: FontWeight,
     pub slant: FontSlant,
+    pub underline: bool,
+    pub strikethrough: bool,
+    pub spans: Vec<Span>,
+    pub width: Option<f64>,
+    pub height: Option<f64>,
+    pub line_height: f64,
+    pub direction: TextDirection,
+    pub fit: Option<Fit>,
+    pub outline: Option<Outline>,
+    pub background: Option<Background>,
+    pub path: Option<TextPath>,
 }
 
 impl Text {
     const DEFAULT_FAMILY: &'static str = "serif";
     const DEFAULT_SIZE: f64 = 14.0;
+    const DEFAULT_LINE_HEIGHT: f64 = 1.2;
 
     pub fn parse(src: &Yaml) -> Result<Text, NodeError> {
         let x = src
@@ -37,19 +56,23 @@ impl Text {
             .ok_or_else(|| NodeError::Required("text".to_string(), "color".to_string()))
             .and_then(|x| -> Result<Color, NodeError> {
                 Color::parse(x).and_then(|c| -> Result<Color, NodeError> {
-                    Ok(Color::new(c.r, c.g, c.b, alpha))
+                    Ok(Color::new(c.r, c.g, c.b, c.a * alpha))
                 })
             })?;
         let scale = src
             .hash_val("scale")
             .map_or(Scale::default(), |x| -> Scale { Scale::parse(x) });
-        let text = src
-            .string_val("text")
-            .ok_or_else(|| NodeError::Required("text".to_string(), "text".to_string()))?;
         let family = src
             .string_val("family")
             .unwrap_or_else(|| Self::DEFAULT_FAMILY.to_string());
         let size = src.f64_val("size").unwrap_or(Self::DEFAULT_SIZE);
+        if size <= 0.0 || !size.is_finite() {
+            return Err(NodeError::InvalidValue(
+                "size".to_string(),
+                size.to_string(),
+                "a positive number".to_string(),
+            ));
+        }
         let weight = match src.str_val("weight") {
             Some("bold") => FontWeight::Bold,
             _ => FontWeight::Normal,
@@ -59,6 +82,53 @@ impl Text {
             Some("oblique") => FontSlant::Oblique,
             _ => FontSlant::Normal,
         };
+        let underline = src.bool_val("underline").unwrap_or(false);
+        let strikethrough = src.bool_val("strikethrough").unwrap_or(false);
+        let base = Span {
+            text: String::new(),
+            color,
+            family: family.clone(),
+            size,
+            weight,
+            slant,
+            underline,
+            strikethrough,
+        };
+        let spans = match src.array_val("spans") {
+            Some(x) => Self::parse_spans(x, &base)?,
+            None => Vec::new(),
+        };
+        let text = if spans.is_empty() {
+            src.string_val("text")
+                .ok_or_else(|| NodeError::Required("text".to_string(), "text".to_string()))?
+        } else {
+            spans.iter().map(|x| -> &str { x.text.as_str() }).collect()
+        };
+        let width = src.f64_val("width");
+        let height = src.f64_val("height");
+        let line_height = src
+            .f64_val("line_height")
+            .unwrap_or(Self::DEFAULT_LINE_HEIGHT);
+        let direction = match src.str_val("direction") {
+            Some("vertical") => TextDirection::Vertical,
+            _ => TextDirection::Horizontal,
+        };
+        let fit = match src.hash_val("fit") {
+            Some(x) => Some(Fit::parse(x)?),
+            None => None,
+        };
+        let outline = match src.hash_val("outline") {
+            Some(x) => Some(Outline::parse(x)?),
+            None => None,
+        };
+        let background = match src.hash_val("background") {
+            Some(x) => Some(Background::parse(x)?),
+            None => None,
+        };
+        let path = match src.hash_val("path") {
+            Some(x) => Some(TextPath::parse(x)?),
+            None => None,
+        };
 
         Ok(Text {
             x,
@@ -70,13 +140,53 @@ impl Text {
             family,
             weight,
             slant,
+            underline,
+            strikethrough,
+            spans,
+            width,
+            height,
+            line_height,
+            direction,
+            fit,
+            outline,
+            background,
+            path,
         })
     }
+
+    fn parse_spans(src: &[Yaml], base: &Span) -> Result<Vec<Span>, NodeError> {
+        let mut spans = Vec::new();
+
+        for x in src {
+            spans.push(Span::parse(x, base)?);
+        }
+
+        Ok(spans)
+    }
+
+    /// The styled runs to lay out. A layer without `spans` is a single run
+    /// in its own style.
+    pub fn runs(&self) -> Vec<Span> {
+        if !self.spans.is_empty() {
+            return self.spans.clone();
+        }
+
+        vec![Span {
+            text: self.text.clone(),
+            color: self.color,
+            family: self.family.clone(),
+            size: self.size,
+            weight: self.weight,
+            slant: self.slant,
+            underline: self.underline,
+            strikethrough: self.strikethrough,
+        }]
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Text;
+    use super::{Text, TextDirection};
     use crate::parse_yaml;
     use cairo::{FontSlant, FontWeight};
     use float_cmp::approx_eq;
@@ -288,6 +398,19 @@ text: test
         assert!(approx_eq!(f64, subject.size, 14.0));
     }
 
+    #[test]
+    #[should_panic(expected = "invalid 'size' value '0', expected a positive number")]
+    fn size_is_zero() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+text: test
+size: 0
+";
+        parse!(s);
+    }
+
     #[test]
     fn weight_is_normal() {
         let s = "---
@@ -376,4 +499,270 @@ text: test
         let subject = parse!(s);
         assert_eq!(subject.slant, FontSlant::Normal);
     }
+
+    #[test]
+    fn outline_is_blank() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+text: test
+";
+        let subject = parse!(s);
+        assert!(subject.outline.is_none());
+    }
+
+    #[test]
+    fn outline_is_not_blank() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+text: test
+outline:
+  color: '#000000'
+  width: 3
+";
+        let subject = parse!(s);
+        let outline = subject.outline.unwrap();
+        assert_eq!(outline.color.r, 0);
+        assert!(approx_eq!(f64, outline.width, 3.0));
+    }
+
+    #[test]
+    #[should_panic(expected = "'outline' is required 'color' option")]
+    fn outline_without_color() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+text: test
+outline:
+  width: 3
+";
+        parse!(s);
+    }
+
+    #[test]
+    fn background_is_blank() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+text: test
+";
+        let subject = parse!(s);
+        assert!(subject.background.is_none());
+    }
+
+    #[test]
+    fn background_is_not_blank() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+text: test
+background:
+  color: '#FFFFFF'
+  padding: 4
+  radius: 2
+";
+        let subject = parse!(s);
+        let background = subject.background.unwrap();
+        assert_eq!(background.color.r, 255);
+        assert!(approx_eq!(f64, background.padding, 4.0));
+        assert!(approx_eq!(f64, background.radius, 2.0));
+    }
+
+    #[test]
+    fn path_is_blank() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+text: test
+";
+        let subject = parse!(s);
+        assert!(subject.path.is_none());
+    }
+
+    #[test]
+    fn path_is_not_blank() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+text: test
+path:
+  points:
+    - [0, 0]
+    - [100, 0]
+";
+        let subject = parse!(s);
+        assert_eq!(subject.path.unwrap().points.len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid path")]
+    fn path_is_invalid() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+text: test
+path:
+  offset: 10
+";
+        parse!(s);
+    }
+
+    #[test]
+    fn spans_are_blank() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+text: test
+";
+        let subject = parse!(s);
+        assert!(subject.spans.is_empty());
+        let runs = subject.runs();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].text, "test");
+    }
+
+    #[test]
+    fn spans_are_not_blank() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+weight: normal
+spans:
+  - text: 'one '
+  - text: bold
+    weight: bold
+  - text: ' word'
+";
+        let subject = parse!(s);
+        assert_eq!(subject.spans.len(), 3);
+        assert_eq!(subject.text, "one bold word");
+        assert_eq!(subject.spans[0].weight, FontWeight::Normal);
+        assert_eq!(subject.spans[1].weight, FontWeight::Bold);
+        assert_eq!(subject.spans[2].color.r, 170);
+        assert_eq!(subject.runs().len(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "'span' is required 'text' option")]
+    fn span_without_text() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+spans:
+  - weight: bold
+";
+        parse!(s);
+    }
+
+    #[test]
+    fn underline_and_strikethrough() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+text: test
+underline: true
+strikethrough: true
+";
+        let subject = parse!(s);
+        assert!(subject.underline);
+        assert!(subject.strikethrough);
+    }
+
+    #[test]
+    fn width_and_line_height() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+text: test
+width: 200
+line_height: 1.5
+";
+        let subject = parse!(s);
+        assert!(approx_eq!(f64, subject.width.unwrap(), 200.0));
+        assert!(approx_eq!(f64, subject.line_height, 1.5));
+    }
+
+    #[test]
+    fn width_and_line_height_are_blank() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+text: test
+";
+        let subject = parse!(s);
+        assert!(subject.width.is_none());
+        assert!(approx_eq!(f64, subject.line_height, 1.2));
+    }
+
+    #[test]
+    fn fit_is_blank() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+text: test
+";
+        let subject = parse!(s);
+        assert!(subject.fit.is_none());
+    }
+
+    #[test]
+    fn fit_is_not_blank() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+text: test
+fit:
+  width: 200
+  height: 40
+";
+        let subject = parse!(s);
+        let fit = subject.fit.unwrap();
+        assert!(approx_eq!(f64, fit.width, 200.0));
+        assert!(approx_eq!(f64, fit.height, 40.0));
+    }
+
+    #[test]
+    fn direction_is_vertical() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+text: test
+direction: vertical
+height: 300
+";
+        let subject = parse!(s);
+        assert_eq!(subject.direction, TextDirection::Vertical);
+        assert!(approx_eq!(f64, subject.height.unwrap(), 300.0));
+    }
+
+    #[test]
+    fn direction_is_blank() {
+        let s = "---
+x: 10
+y: 20
+color: '#AABBCC'
+text: test
+";
+        let subject = parse!(s);
+        assert_eq!(subject.direction, TextDirection::Horizontal);
+        assert!(subject.height.is_none());
+    }
 }
@@ -0,0 +1,329 @@
+use std::f64::consts::PI;
+use yaml_rust::yaml::Hash;
+use yaml_rust::Yaml;
+
+use crate::ext::YamlExt;
+use crate::node::error::NodeError;
+use crate::node::point::{DefPoint, Point};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextAlign {
+    Start,
+    Center,
+    End,
+}
+
+/// A path that glyphs are laid along, flattened into a polyline.
+#[derive(Debug, Clone)]
+pub struct TextPath {
+    pub points: Vec<Point>,
+    pub offset: f64,
+    pub align: TextAlign,
+}
+
+impl TextPath {
+    const SEGMENTS: usize = 64;
+
+    pub fn parse(src: &Hash) -> Result<TextPath, NodeError> {
+        let points = if let Some(x) = src.array_val("points") {
+            Self::parse_points(x)?
+        } else if let Some(x) = src.hash_val("curve") {
+            Self::parse_curve(x)?
+        } else if let Some(x) = src.hash_val("arc") {
+            Self::parse_arc(x)?
+        } else {
+            return Err(NodeError::InvalidPath);
+        };
+        let offset = src.f64_val("offset").unwrap_or(0.0);
+        let align = match src.str_val("align") {
+            Some("center") => TextAlign::Center,
+            Some("end") => TextAlign::End,
+            _ => TextAlign::Start,
+        };
+
+        Ok(TextPath {
+            points,
+            offset,
+            align,
+        })
+    }
+
+    fn parse_points(src: &[Yaml]) -> Result<Vec<Point>, NodeError> {
+        if src.len() < 2 {
+            return Err(NodeError::InvalidPath);
+        }
+
+        let mut points = Vec::new();
+
+        for p in src {
+            let point = p.as_point().ok_or(NodeError::InvalidPoint)?;
+            points.push(point);
+        }
+
+        Ok(points)
+    }
+
+    // Matches `renderer::curve`, which starts the cubic at `start` and uses
+    // `start` and `mid` as its control points.
+    fn parse_curve(src: &Hash) -> Result<Vec<Point>, NodeError> {
+        let mut control = Vec::new();
+        for key in &["start", "mid", "end"] {
+            let point = src
+                .array_val(key)
+                .ok_or_else(|| NodeError::Required("path".to_string(), key.to_string()))
+                .and_then(|x| -> Result<Point, NodeError> {
+                    x.as_point().ok_or(NodeError::InvalidPoint)
+                })?;
+            control.push(point);
+        }
+        let (p0, p2, p3) = (control[0], control[1], control[2]);
+
+        Ok((0..=Self::SEGMENTS)
+            .map(|i| -> Point {
+                let t = i as f64 / Self::SEGMENTS as f64;
+                let u = 1.0 - t;
+                let a = u * u * u + 3.0 * u * u * t;
+                let b = 3.0 * u * t * t;
+                let c = t * t * t;
+                Point {
+                    x: a * p0.x + b * p2.x + c * p3.x,
+                    y: a * p0.y + b * p2.y + c * p3.y,
+                }
+            })
+            .collect())
+    }
+
+    fn parse_arc(src: &Hash) -> Result<Vec<Point>, NodeError> {
+        let x = src
+            .f64_val("x")
+            .ok_or_else(|| NodeError::Required("path".to_string(), "x".to_string()))?;
+        let y = src
+            .f64_val("y")
+            .ok_or_else(|| NodeError::Required("path".to_string(), "y".to_string()))?;
+        let radius = src
+            .f64_val("radius")
+            .ok_or_else(|| NodeError::Required("path".to_string(), "radius".to_string()))?;
+        let start = src
+            .f64_val("start")
+            .ok_or_else(|| NodeError::Required("path".to_string(), "start".to_string()))?;
+        let end = src
+            .f64_val("end")
+            .ok_or_else(|| NodeError::Required("path".to_string(), "end".to_string()))?;
+
+        Ok((0..=Self::SEGMENTS)
+            .map(|i| -> Point {
+                let t = i as f64 / Self::SEGMENTS as f64;
+                let angle = (start + (end - start) * t) * (PI / 180.0);
+                Point {
+                    x: x + radius * angle.cos(),
+                    y: y + radius * angle.sin(),
+                }
+            })
+            .collect())
+    }
+
+    pub fn length(&self) -> f64 {
+        self.points
+            .windows(2)
+            .map(|w| -> f64 { (w[1].x - w[0].x).hypot(w[1].y - w[0].y) })
+            .sum()
+    }
+
+    /// Position and tangent angle at `distance` along the path. Distances
+    /// beyond either end are extrapolated along the first or last segment.
+    /// A path of less than two points has no direction, so its first point
+    /// or the origin is returned.
+    pub fn point_at(&self, distance: f64) -> (Point, f64) {
+        if self.points.len() < 2 {
+            let point = self.points.first().copied();
+            return (point.unwrap_or(Point { x: 0.0, y: 0.0 }), 0.0);
+        }
+        let mut rest = distance;
+        let last = self.points.len() - 2;
+        for (i, w) in self.points.windows(2).enumerate() {
+            let length = (w[1].x - w[0].x).hypot(w[1].y - w[0].y);
+            if (rest <= length && length > 0.0) || i == last {
+                let angle = (w[1].y - w[0].y).atan2(w[1].x - w[0].x);
+                let point = Point {
+                    x: w[0].x + rest * angle.cos(),
+                    y: w[0].y + rest * angle.sin(),
+                };
+                return (point, angle);
+            }
+            rest -= length;
+        }
+        (self.points[0], 0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TextAlign, TextPath};
+    use crate::node::point::Point;
+    use crate::parse_yaml;
+    use float_cmp::approx_eq;
+    use std::f64::consts::PI;
+    use yaml_rust::Yaml;
+    use yaml_rust::YamlLoader;
+
+    macro_rules! parse {
+        ($x:expr) => {{
+            let src = parse_yaml!($x);
+            match src {
+                Yaml::Hash(x) => match TextPath::parse(&x) {
+                    Ok(x) => x,
+                    Err(e) => panic!(e.to_string()),
+                },
+                _ => panic!("invalid yaml"),
+            }
+        }};
+    }
+
+    #[test]
+    fn points() {
+        let s = "---
+points:
+  - [0, 0]
+  - [30, 40]
+  - [30, 50]
+";
+        let subject = parse!(s);
+        assert_eq!(subject.points.len(), 3);
+        assert!(approx_eq!(f64, subject.length(), 60.0));
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid path")]
+    fn points_are_too_few() {
+        let s = "---
+points:
+  - [0, 0]
+";
+        parse!(s);
+    }
+
+    #[test]
+    fn curve() {
+        let s = "---
+curve:
+  start: [0, 0]
+  mid: [50, 0]
+  end: [100, 0]
+";
+        let subject = parse!(s);
+        let last = subject.points[subject.points.len() - 1];
+        assert!(approx_eq!(f64, subject.points[0].x, 0.0));
+        assert!(approx_eq!(f64, last.x, 100.0));
+        assert!(approx_eq!(f64, subject.length(), 100.0, epsilon = 1e-9));
+    }
+
+    #[test]
+    #[should_panic(expected = "'path' is required 'mid' option")]
+    fn curve_without_mid() {
+        let s = "---
+curve:
+  start: [0, 0]
+  end: [100, 0]
+";
+        parse!(s);
+    }
+
+    #[test]
+    fn arc() {
+        let s = "---
+arc:
+  x: 50
+  y: 50
+  radius: 10
+  start: 180
+  end: 360
+";
+        let subject = parse!(s);
+        assert!(approx_eq!(f64, subject.points[0].x, 40.0, epsilon = 1e-9));
+        assert!(approx_eq!(f64, subject.length(), 10.0 * PI, epsilon = 0.01));
+    }
+
+    #[test]
+    #[should_panic(expected = "'path' is required 'radius' option")]
+    fn arc_without_radius() {
+        let s = "---
+arc:
+  x: 50
+  y: 50
+  start: 180
+  end: 360
+";
+        parse!(s);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid path")]
+    fn path_is_blank() {
+        let s = "---
+offset: 10
+";
+        parse!(s);
+    }
+
+    #[test]
+    fn offset_and_align() {
+        let s = "---
+points:
+  - [0, 0]
+  - [100, 0]
+offset: 10
+align: center
+";
+        let subject = parse!(s);
+        assert!(approx_eq!(f64, subject.offset, 10.0));
+        assert_eq!(subject.align, TextAlign::Center);
+    }
+
+    #[test]
+    fn offset_and_align_are_blank() {
+        let s = "---
+points:
+  - [0, 0]
+  - [100, 0]
+";
+        let subject = parse!(s);
+        assert!(approx_eq!(f64, subject.offset, 0.0));
+        assert_eq!(subject.align, TextAlign::Start);
+    }
+
+    #[test]
+    fn point_at() {
+        let s = "---
+points:
+  - [0, 0]
+  - [10, 0]
+  - [10, 10]
+";
+        let subject = parse!(s);
+        let (point, angle) = subject.point_at(15.0);
+        assert!(approx_eq!(f64, point.x, 10.0, epsilon = 1e-9));
+        assert!(approx_eq!(f64, point.y, 5.0, epsilon = 1e-9));
+        assert!(approx_eq!(f64, angle, PI / 2.0));
+        let (point, _) = subject.point_at(25.0);
+        assert!(approx_eq!(f64, point.y, 15.0, epsilon = 1e-9));
+    }
+
+    #[test]
+    fn point_at_too_few_points() {
+        let subject = TextPath {
+            points: vec![],
+            offset: 0.0,
+            align: TextAlign::Start,
+        };
+        let (point, angle) = subject.point_at(10.0);
+        assert!(approx_eq!(f64, point.x, 0.0));
+        assert!(approx_eq!(f64, angle, 0.0));
+        let subject = TextPath {
+            points: vec![Point { x: 5.0, y: 6.0 }],
+            ..subject
+        };
+        let (point, _) = subject.point_at(10.0);
+        assert!(approx_eq!(f64, point.x, 5.0));
+        assert!(approx_eq!(f64, point.y, 6.0));
+    }
+}
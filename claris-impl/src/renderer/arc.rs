@@ -1,4 +1,5 @@
 use crate::node::Arc as Node;
+use crate::node::{ArcClose, ArcDirection};
 use crate::Context;
 use std::f64::consts::PI;
 
@@ -14,18 +15,12 @@ impl Arc {
             node.color.a as f64,
         );
         context.scale(node.scale.x, node.scale.y);
-        if node.close {
-            context.move_to(0.0, 0.0)
-        }
-        context.arc(
-            0.0,
-            0.0,
-            node.radius,
-            node.start * (PI / 180.0),
-            node.end * (PI / 180.0),
-        );
-        if node.close {
-            context.line_to(0.0, 0.0)
+        let start = node.start * (PI / 180.0);
+        let end = node.end * (PI / 180.0);
+        if node.inner_radius > 0.0 {
+            Self::ring_path(context, &node, start, end);
+        } else {
+            Self::arc_path(context, &node, start, end);
         }
         if node.fill {
             context.fill();
@@ -35,12 +30,46 @@ impl Arc {
             context.stroke();
         }
     }
+
+    fn arc_path(context: &mut dyn Context, node: &Node, start: f64, end: f64) {
+        if node.close == ArcClose::Pie {
+            context.move_to(0.0, 0.0)
+        }
+        match node.direction {
+            ArcDirection::Clockwise => context.arc(0.0, 0.0, node.radius, start, end),
+            ArcDirection::CounterClockwise => {
+                context.arc_negative(0.0, 0.0, node.radius, start, end)
+            }
+        }
+        match node.close {
+            ArcClose::Pie => context.line_to(0.0, 0.0),
+            ArcClose::Chord => context.close_path(),
+            ArcClose::Open => {}
+        }
+    }
+
+    // A ring segment walks the outer edge in the requested direction and
+    // comes back along the inner edge the opposite way.
+    fn ring_path(context: &mut dyn Context, node: &Node, start: f64, end: f64) {
+        match node.direction {
+            ArcDirection::Clockwise => {
+                context.arc(0.0, 0.0, node.radius, start, end);
+                context.arc_negative(0.0, 0.0, node.inner_radius, end, start);
+            }
+            ArcDirection::CounterClockwise => {
+                context.arc_negative(0.0, 0.0, node.radius, start, end);
+                context.arc(0.0, 0.0, node.inner_radius, end, start);
+            }
+        }
+        context.close_path();
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::Arc;
     use crate::node::Arc as Node;
+    use crate::node::{ArcClose, ArcDirection};
     use crate::testing_helpers::stub::ContextImpl;
 
     #[test]
@@ -48,7 +77,7 @@ mod tests {
         let mut context = ContextImpl::new();
         let node = Node {
             fill: true,
-            close: true,
+            close: ArcClose::Pie,
             ..Default::default()
         };
         Arc::render(&mut context, node);
@@ -69,7 +98,7 @@ mod tests {
         let mut context = ContextImpl::new();
         let node = Node {
             fill: true,
-            close: false,
+            close: ArcClose::Open,
             ..Default::default()
         };
         Arc::render(&mut context, node);
@@ -90,7 +119,7 @@ mod tests {
         let mut context = ContextImpl::new();
         let node = Node {
             fill: false,
-            close: true,
+            close: ArcClose::Pie,
             ..Default::default()
         };
         Arc::render(&mut context, node);
@@ -111,7 +140,7 @@ mod tests {
         let mut context = ContextImpl::new();
         let node = Node {
             fill: false,
-            close: false,
+            close: ArcClose::Open,
             ..Default::default()
         };
         Arc::render(&mut context, node);
@@ -126,4 +155,68 @@ mod tests {
         assert_eq!(context.set_line_cap_received, 1);
         assert_eq!(context.stroke_received, 1);
     }
+
+    #[test]
+    fn stroke_and_chord() {
+        let mut context = ContextImpl::new();
+        let node = Node {
+            fill: false,
+            close: ArcClose::Chord,
+            ..Default::default()
+        };
+        Arc::render(&mut context, node);
+        assert_eq!(context.move_to_received, 0);
+        assert_eq!(context.line_to_received, 0);
+        assert_eq!(context.arc_received, 1);
+        assert_eq!(context.close_path_received, 1);
+        assert_eq!(context.stroke_received, 1);
+    }
+
+    #[test]
+    fn counter_clockwise() {
+        let mut context = ContextImpl::new();
+        let node = Node {
+            direction: ArcDirection::CounterClockwise,
+            ..Default::default()
+        };
+        Arc::render(&mut context, node);
+        assert_eq!(context.move_to_received, 1);
+        assert_eq!(context.line_to_received, 1);
+        assert_eq!(context.arc_received, 0);
+        assert_eq!(context.arc_negative_received, 1);
+    }
+
+    #[test]
+    fn ring_segment() {
+        let mut context = ContextImpl::new();
+        let node = Node {
+            fill: true,
+            radius: 20.0,
+            inner_radius: 10.0,
+            ..Default::default()
+        };
+        Arc::render(&mut context, node);
+        assert_eq!(context.move_to_received, 0);
+        assert_eq!(context.line_to_received, 0);
+        assert_eq!(context.arc_received, 1);
+        assert_eq!(context.arc_negative_received, 1);
+        assert_eq!(context.close_path_received, 1);
+        assert_eq!(context.fill_received, 1);
+    }
+
+    #[test]
+    fn counter_clockwise_ring_segment() {
+        let mut context = ContextImpl::new();
+        let node = Node {
+            direction: ArcDirection::CounterClockwise,
+            radius: 20.0,
+            inner_radius: 10.0,
+            ..Default::default()
+        };
+        Arc::render(&mut context, node);
+        assert_eq!(context.arc_received, 1);
+        assert_eq!(context.arc_negative_received, 1);
+        assert_eq!(context.close_path_received, 1);
+        assert_eq!(context.stroke_received, 1);
+    }
 }
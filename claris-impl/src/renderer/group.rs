@@ -0,0 +1,49 @@
+use crate::node::Group as Node;
+use crate::Context;
+
+pub struct Group {}
+
+impl Group {
+    pub fn render(context: &mut dyn Context, node: Node) {
+        context.translate(node.x, node.y);
+        for layer in node.layers {
+            super::render(context, layer);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Group;
+    use crate::node::Circle;
+    use crate::node::Group as Node;
+    use crate::node::Layer;
+    use crate::testing_helpers::stub::ContextImpl;
+
+    #[test]
+    fn render_layers() {
+        let mut context = ContextImpl::new();
+        let node = Node {
+            x: 10.0,
+            y: 20.0,
+            layers: vec![
+                Layer::Circle(Circle::default()),
+                Layer::Circle(Circle::default()),
+            ],
+        };
+        Group::render(&mut context, node);
+        assert_eq!(context.translate_received, 3);
+        assert_eq!(context.save_received, 2);
+        assert_eq!(context.restore_received, 2);
+        assert_eq!(context.arc_received, 2);
+    }
+
+    #[test]
+    fn render_empty() {
+        let mut context = ContextImpl::new();
+        let node = Node::default();
+        Group::render(&mut context, node);
+        assert_eq!(context.translate_received, 1);
+        assert_eq!(context.save_received, 0);
+    }
+}
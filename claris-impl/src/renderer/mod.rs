@@ -5,19 +5,28 @@ use self::super::node::Layer;
 mod arc;
 mod circle;
 mod curve;
+mod ellipse;
+mod group;
 mod line;
 mod polygon;
 mod rectangle;
+mod regular_polygon;
+mod star;
 mod text;
+mod text_layout;
 mod triangle;
 
 use crate::Context;
 use arc::Arc as ArcRenderer;
 use circle::Circle as CircleRenderer;
 use curve::Curve as CurveRenderer;
+use ellipse::Ellipse as EllipseRenderer;
+use group::Group as GroupRenderer;
 use line::Line as LineRenderer;
 use polygon::Polygon as PolygonRenderer;
 use rectangle::Rectangle as RectangleRenderer;
+use regular_polygon::RegularPolygon as RegularPolygonRenderer;
+use star::Star as StarRenderer;
 use text::Text as TextRenderer;
 use triangle::Triangle as TriangleRenderer;
 
@@ -31,6 +40,9 @@ pub fn render(context: &mut dyn Context, layer: Layer) {
         Layer::Circle(x) => {
             CircleRenderer::render(context, x);
         }
+        Layer::Ellipse(x) => {
+            EllipseRenderer::render(context, x);
+        }
         Layer::Arc(x) => {
             ArcRenderer::render(context, x);
         }
@@ -47,7 +59,16 @@ pub fn render(context: &mut dyn Context, layer: Layer) {
             CurveRenderer::render(context, x);
         }
         Layer::Text(x) => {
-            TextRenderer::render(context, x);
+            TextRenderer::render(context, *x);
+        }
+        Layer::RegularPolygon(x) => {
+            RegularPolygonRenderer::render(context, x);
+        }
+        Layer::Star(x) => {
+            StarRenderer::render(context, x);
+        }
+        Layer::Group(x) => {
+            GroupRenderer::render(context, x);
         }
     };
     context.restore();
@@ -59,10 +80,14 @@ mod tests {
     use crate::node::Arc;
     use crate::node::Circle;
     use crate::node::Curve;
+    use crate::node::Ellipse;
+    use crate::node::Group;
     use crate::node::Layer;
     use crate::node::Line;
     use crate::node::Polygon;
     use crate::node::Rectangle;
+    use crate::node::RegularPolygon;
+    use crate::node::Star;
     use crate::node::Text;
     use crate::node::Triangle;
     use crate::testing_helpers::stub::ContextImpl;
@@ -91,6 +116,14 @@ mod tests {
         render(&mut context, layer);
     }
 
+    #[test]
+    fn ellipse() {
+        let mut context = ContextImpl::new();
+        let node = Ellipse::default();
+        let layer = Layer::Ellipse(node);
+        render(&mut context, layer);
+    }
+
     #[test]
     fn line() {
         let mut context = ContextImpl::new();
@@ -119,7 +152,7 @@ mod tests {
     fn text() {
         let mut context = ContextImpl::new();
         let node = Text::default();
-        let layer = Layer::Text(node);
+        let layer = Layer::Text(Box::new(node));
         render(&mut context, layer);
     }
 
@@ -130,4 +163,28 @@ mod tests {
         let layer = Layer::Triangle(node);
         render(&mut context, layer);
     }
+
+    #[test]
+    fn regular_polygon() {
+        let mut context = ContextImpl::new();
+        let node = RegularPolygon::default();
+        let layer = Layer::RegularPolygon(node);
+        render(&mut context, layer);
+    }
+
+    #[test]
+    fn star() {
+        let mut context = ContextImpl::new();
+        let node = Star::default();
+        let layer = Layer::Star(node);
+        render(&mut context, layer);
+    }
+
+    #[test]
+    fn group() {
+        let mut context = ContextImpl::new();
+        let node = Group::default();
+        let layer = Layer::Group(node);
+        render(&mut context, layer);
+    }
 }
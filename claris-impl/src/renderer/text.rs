@@ -1,30 +1,235 @@
 use crate::node::Text as Node;
+use crate::node::{Background, Color, Fit, Span, TextAlign, TextDirection, TextPath};
+use crate::renderer::text_layout::{bounds, layout, select_font, vertical_layout, Fragment};
 use crate::Context;
+use std::f64::consts::PI;
 
 pub struct Text {}
 
 impl Text {
+    const FIT_PRECISION: f64 = 0.5;
+
     pub fn render(context: &mut dyn Context, node: Node) {
         context.translate(node.x, node.y);
-        context.move_to(0.0, 0.0);
+        context.scale(node.scale.x, node.scale.y);
+        let fragments = match (&node.path, node.fit) {
+            (Some(path), _) => Self::along_path(context, &node, path),
+            (None, Some(fit)) => Self::fit(context, &node, fit),
+            (None, None) => Self::layout(context, &node, &node.runs(), None),
+        };
+        if let Some(background) = node.background {
+            Self::render_background(context, &fragments, background);
+        }
+        for fragment in fragments {
+            select_font(context, &fragment.span);
+            if fragment.angle == 0.0 {
+                context.move_to(fragment.x, fragment.y);
+                context.text_path(fragment.text.as_str());
+                Self::decorate(context, &fragment, fragment.x, fragment.y);
+            } else {
+                context.save();
+                context.translate(fragment.x, fragment.y);
+                context.rotate(fragment.angle);
+                context.move_to(0.0, 0.0);
+                context.text_path(fragment.text.as_str());
+                Self::decorate(context, &fragment, 0.0, 0.0);
+                context.restore();
+            }
+            Self::paint(context, &node, fragment.span.color);
+        }
+    }
+
+    // The outline is stroked under the fill, so only its outer half shows
+    // and the glyphs keep their full weight.
+    fn paint(context: &mut dyn Context, node: &Node, color: Color) {
+        if let Some(outline) = node.outline {
+            context.set_source_rgba(
+                outline.color.r as f64 / 255.0,
+                outline.color.g as f64 / 255.0,
+                outline.color.b as f64 / 255.0,
+                outline.color.a as f64,
+            );
+            context.set_line_width(outline.width);
+            context.stroke_preserve();
+        }
         context.set_source_rgba(
-            node.color.r as f64 / 255.0,
-            node.color.g as f64 / 255.0,
-            node.color.b as f64 / 255.0,
-            node.color.a as f64,
+            color.r as f64 / 255.0,
+            color.g as f64 / 255.0,
+            color.b as f64 / 255.0,
+            color.a as f64,
         );
-        context.scale(node.scale.x, node.scale.y);
-        context.select_font_face(node.family.as_str(), node.slant, node.weight);
-        context.set_font_size(node.size);
-        context.show_text(node.text.as_str());
-        context.stroke();
+        context.fill();
+    }
+
+    // Lays out horizontally or in vertical columns. `wrap` overrides the
+    // layer's own line length.
+    fn layout(
+        context: &mut dyn Context,
+        node: &Node,
+        runs: &[Span],
+        wrap: Option<f64>,
+    ) -> Vec<Fragment> {
+        match node.direction {
+            TextDirection::Horizontal => {
+                layout(context, runs, wrap.or(node.width), node.line_height)
+            }
+            TextDirection::Vertical => {
+                vertical_layout(context, runs, wrap.or(node.height), node.line_height)
+            }
+        }
+    }
+
+    fn decorate(context: &mut dyn Context, fragment: &Fragment, x: f64, y: f64) {
+        let size = fragment.span.size;
+        let thickness = size / 14.0;
+        if fragment.span.underline {
+            context.rectangle(x, y + size * 0.15, fragment.width, thickness);
+        }
+        if fragment.span.strikethrough {
+            context.rectangle(x, y - size * 0.3, fragment.width, thickness);
+        }
+    }
+
+    // Each glyph is centred on its slot along the path and rotated to the
+    // path tangent there.
+    fn along_path(context: &mut dyn Context, node: &Node, path: &TextPath) -> Vec<Fragment> {
+        let mut glyphs: Vec<(Span, String, f64)> = Vec::new();
+        for span in node.runs() {
+            select_font(context, &span);
+            for c in span.text.chars() {
+                let glyph = c.to_string();
+                let advance = context.text_extents(glyph.as_str()).x_advance;
+                glyphs.push((span.clone(), glyph, advance));
+            }
+        }
+        let width: f64 = glyphs.iter().map(|(_, _, advance)| advance).sum();
+        let mut distance = match path.align {
+            TextAlign::Start => path.offset,
+            TextAlign::Center => (path.length() - width) / 2.0 + path.offset,
+            TextAlign::End => path.length() - width - path.offset,
+        };
+        let mut ret = Vec::new();
+        for (span, text, advance) in glyphs {
+            let (point, angle) = path.point_at(distance + advance / 2.0);
+            let (sin, cos) = angle.sin_cos();
+            ret.push(Fragment {
+                span,
+                text,
+                x: point.x - advance / 2.0 * cos,
+                y: point.y - advance / 2.0 * sin,
+                width: advance,
+                angle,
+            });
+            distance += advance;
+        }
+        ret
+    }
+
+    // Picks the largest size between `min_size` and `max_size` whose layout
+    // fits the box, scaling every run by the same factor, and moves the
+    // result so its ink box starts at the layer origin.
+    fn fit(context: &mut dyn Context, node: &Node, fit: Fit) -> Vec<Fragment> {
+        let runs = node.runs();
+        let wrap = match (fit.wrap, node.direction) {
+            (false, _) => None,
+            (true, TextDirection::Horizontal) => Some(fit.width),
+            (true, TextDirection::Vertical) => Some(fit.height),
+        };
+        // Runs are scaled relative to the layer's size.
+        if node.size <= 0.0 {
+            return Self::layout(context, node, &runs, wrap);
+        }
+        let try_size = |context: &mut dyn Context, size: f64| -> (bool, Vec<Fragment>) {
+            let scaled: Vec<Span> = runs
+                .iter()
+                .map(|x| -> Span {
+                    Span {
+                        size: x.size * size / node.size,
+                        ..x.clone()
+                    }
+                })
+                .collect();
+            let fragments = Self::layout(context, node, &scaled, wrap);
+            let fits = match bounds(context, &fragments) {
+                Some((l, t, r, b)) => r - l <= fit.width && b - t <= fit.height,
+                None => true,
+            };
+            (fits, fragments)
+        };
+
+        let (fits, mut best) = try_size(context, fit.max_size);
+        if !fits {
+            let mut low = fit.min_size;
+            let mut high = fit.max_size;
+            best = try_size(context, low).1;
+            while high - low > Self::FIT_PRECISION {
+                let mid = (low + high) / 2.0;
+                let (fits, fragments) = try_size(context, mid);
+                if fits {
+                    low = mid;
+                    best = fragments;
+                } else {
+                    high = mid;
+                }
+            }
+        }
+
+        if let Some((left, top, _, _)) = bounds(context, &best) {
+            context.translate(-left, -top);
+        }
+        best
+    }
+
+    fn render_background(
+        context: &mut dyn Context,
+        fragments: &[Fragment],
+        background: Background,
+    ) {
+        let (left, top, right, bottom) = match bounds(context, fragments) {
+            Some(x) => x,
+            None => return,
+        };
+        let x = left - background.padding;
+        let y = top - background.padding;
+        let width = right - left + background.padding * 2.0;
+        let height = bottom - top + background.padding * 2.0;
+        let radius = background.radius.min(width / 2.0).min(height / 2.0);
+        context.set_source_rgba(
+            background.color.r as f64 / 255.0,
+            background.color.g as f64 / 255.0,
+            background.color.b as f64 / 255.0,
+            background.color.a as f64,
+        );
+        if radius > 0.0 {
+            context.arc(x + width - radius, y + radius, radius, -PI / 2.0, 0.0);
+            context.arc(
+                x + width - radius,
+                y + height - radius,
+                radius,
+                0.0,
+                PI / 2.0,
+            );
+            context.arc(x + radius, y + height - radius, radius, PI / 2.0, PI);
+            context.arc(x + radius, y + radius, radius, PI, PI * 1.5);
+            context.close_path();
+        } else {
+            context.rectangle(x, y, width, height);
+        }
+        context.fill();
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::Text;
+    use crate::node::Background;
+    use crate::node::Color;
+    use crate::node::Fit;
+    use crate::node::Outline;
+    use crate::node::Point;
+    use crate::node::Span;
     use crate::node::Text as Node;
+    use crate::node::{TextAlign, TextDirection, TextPath};
     use crate::testing_helpers::stub::ContextImpl;
 
     #[test]
@@ -35,9 +240,223 @@ mod tests {
         assert_eq!(context.translate_received, 1);
         assert_eq!(context.set_source_rgba_received, 1);
         assert_eq!(context.scale_received, 1);
-        assert_eq!(context.select_font_face_received, 1);
-        assert_eq!(context.set_font_size_received, 1);
-        assert_eq!(context.show_text_received, 1);
-        assert_eq!(context.stroke_received, 1);
+        assert_eq!(context.select_font_face_received, 2);
+        assert_eq!(context.set_font_size_received, 2);
+        assert_eq!(context.text_path_received, 1);
+        assert_eq!(context.fill_received, 1);
+        assert_eq!(context.stroke_received, 0);
+    }
+
+    #[test]
+    fn render_with_outline() {
+        let mut context = ContextImpl::new();
+        let node = Node {
+            outline: Some(Outline {
+                color: Color::default(),
+                width: 2.0,
+            }),
+            ..Default::default()
+        };
+        Text::render(&mut context, node);
+        assert_eq!(context.set_source_rgba_received, 2);
+        assert_eq!(context.text_path_received, 1);
+        assert_eq!(context.stroke_preserve_received, 1);
+        assert_eq!(context.fill_received, 1);
+        assert_eq!(context.set_line_width_received, 1);
+        assert_eq!(context.stroke_received, 0);
+    }
+
+    #[test]
+    fn render_with_background() {
+        let mut context = ContextImpl::new();
+        let node = Node {
+            background: Some(Background {
+                color: Color::default(),
+                padding: 4.0,
+                radius: 0.0,
+            }),
+            ..Default::default()
+        };
+        Text::render(&mut context, node);
+        assert_eq!(context.set_source_rgba_received, 2);
+        assert_eq!(context.text_extents_received, 5);
+        assert_eq!(context.rectangle_received, 1);
+        assert_eq!(context.arc_received, 0);
+        assert_eq!(context.fill_received, 2);
+    }
+
+    #[test]
+    fn render_with_rounded_background() {
+        let mut context = ContextImpl::new();
+        let node = Node {
+            background: Some(Background {
+                color: Color::default(),
+                padding: 4.0,
+                radius: 3.0,
+            }),
+            ..Default::default()
+        };
+        Text::render(&mut context, node);
+        assert_eq!(context.rectangle_received, 0);
+        assert_eq!(context.arc_received, 4);
+        assert_eq!(context.close_path_received, 1);
+        assert_eq!(context.fill_received, 2);
+    }
+
+    #[test]
+    fn render_along_path() {
+        let mut context = ContextImpl::new();
+        let node = Node {
+            text: "abc".to_string(),
+            background: Some(Background {
+                color: Color::default(),
+                padding: 4.0,
+                radius: 0.0,
+            }),
+            path: Some(TextPath {
+                points: vec![Point { x: 0.0, y: 0.0 }, Point { x: 100.0, y: 100.0 }],
+                offset: 0.0,
+                align: TextAlign::Center,
+            }),
+            ..Default::default()
+        };
+        Text::render(&mut context, node);
+        assert_eq!(context.text_extents_received, 6);
+        assert_eq!(context.text_path_received, 3);
+        assert_eq!(context.translate_received, 4);
+        assert_eq!(context.rotate_received, 3);
+        assert_eq!(context.rectangle_received, 1);
+        assert_eq!(context.fill_received, 4);
+    }
+
+    #[test]
+    fn render_spans() {
+        let mut context = ContextImpl::new();
+        let mut runs = Node::default().runs();
+        let mut bold = runs[0].clone();
+        bold.text = " bold".to_string();
+        bold.underline = true;
+        bold.strikethrough = true;
+        runs.push(bold);
+        let node = Node {
+            spans: runs,
+            ..Default::default()
+        };
+        Text::render(&mut context, node);
+        assert_eq!(context.text_path_received, 2);
+        assert_eq!(context.rectangle_received, 2);
+        assert_eq!(context.set_source_rgba_received, 2);
+        assert_eq!(context.fill_received, 2);
+    }
+
+    #[test]
+    fn render_wrapped_spans() {
+        let mut context = ContextImpl::new();
+        let node = Node {
+            spans: vec![Span {
+                text: "one two three".to_string(),
+                ..Node::default().runs()[0].clone()
+            }],
+            width: Some(40.0),
+            ..Default::default()
+        };
+        Text::render(&mut context, node);
+        assert_eq!(context.text_path_received, 3);
+        assert_eq!(context.move_to_received, 3);
+        assert_eq!(context.fill_received, 3);
+    }
+
+    #[test]
+    fn render_fit() {
+        let mut context = ContextImpl::new();
+        let node = Node {
+            text: "abcd".to_string(),
+            fit: Some(Fit {
+                width: 50.0,
+                height: 40.0,
+                min_size: 1.0,
+                max_size: 40.0,
+                wrap: false,
+            }),
+            ..Default::default()
+        };
+        Text::render(&mut context, node);
+        assert_eq!(context.translate_received, 2);
+        assert_eq!(context.text_path_received, 1);
+        assert!(context.font_size <= 25.0);
+        assert!(context.font_size > 24.0);
+    }
+
+    #[test]
+    fn render_fit_with_wrap() {
+        let mut context = ContextImpl::new();
+        let node = Node {
+            text: "ab cd".to_string(),
+            fit: Some(Fit {
+                width: 20.0,
+                height: 100.0,
+                min_size: 1.0,
+                max_size: 40.0,
+                wrap: true,
+            }),
+            ..Default::default()
+        };
+        Text::render(&mut context, node);
+        assert_eq!(context.text_path_received, 2);
+        assert!(context.font_size <= 20.0);
+        assert!(context.font_size > 19.0);
+    }
+
+    #[test]
+    fn render_fit_max_size() {
+        let mut context = ContextImpl::new();
+        let node = Node {
+            text: "a".to_string(),
+            fit: Some(Fit {
+                width: 100.0,
+                height: 100.0,
+                min_size: 1.0,
+                max_size: 30.0,
+                wrap: false,
+            }),
+            ..Default::default()
+        };
+        Text::render(&mut context, node);
+        assert!((context.font_size - 30.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn render_fit_zero_size() {
+        let mut context = ContextImpl::new();
+        let node = Node {
+            text: "a".to_string(),
+            size: 0.0,
+            fit: Some(Fit {
+                width: 100.0,
+                height: 100.0,
+                min_size: 1.0,
+                max_size: 30.0,
+                wrap: false,
+            }),
+            ..Default::default()
+        };
+        Text::render(&mut context, node);
+        assert!(context.font_size.is_finite());
+    }
+
+    #[test]
+    fn render_vertical() {
+        let mut context = ContextImpl::new();
+        let node = Node {
+            text: "縦書きABC".to_string(),
+            direction: TextDirection::Vertical,
+            ..Default::default()
+        };
+        Text::render(&mut context, node);
+        assert_eq!(context.text_path_received, 4);
+        assert_eq!(context.save_received, 1);
+        assert_eq!(context.rotate_received, 1);
+        assert_eq!(context.restore_received, 1);
+        assert_eq!(context.fill_received, 4);
     }
 }
@@ -0,0 +1,415 @@
+use crate::node::Span;
+use crate::Context;
+use std::f64::consts::PI;
+
+/// A piece of a single run placed on a line. (`x`, `y`) is the pen position
+/// on the baseline, `angle` the rotation of the baseline around it, and
+/// `width` runs up to the end of the last word, excluding trailing spaces.
+#[derive(Debug, Clone)]
+pub struct Fragment {
+    pub span: Span,
+    pub text: String,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub angle: f64,
+}
+
+pub fn select_font(context: &mut dyn Context, span: &Span) {
+    context.select_font_face(span.family.as_str(), span.slant, span.weight);
+    context.set_font_size(span.size);
+}
+
+/// Flows `runs` onto lines starting at the origin baseline. Lines break on
+/// `\n`, and between words once `width` would be exceeded.
+pub fn layout(
+    context: &mut dyn Context,
+    runs: &[Span],
+    width: Option<f64>,
+    line_height: f64,
+) -> Vec<Fragment> {
+    let mut lines: Vec<(f64, Vec<Fragment>)> = Vec::new();
+    let mut x = 0.0;
+
+    for span in runs {
+        select_font(context, span);
+        if lines.is_empty() {
+            lines.push((span.size, Vec::new()));
+        }
+        // Consecutive words of the same run on the same line share a fragment.
+        let mut merge = false;
+        for piece in split_words(span.text.as_str()) {
+            if piece == "\n" {
+                lines.push((span.size, Vec::new()));
+                x = 0.0;
+                merge = false;
+                continue;
+            }
+            let word = context.text_extents(piece.trim_end()).x_advance;
+            let advance = context.text_extents(piece).x_advance;
+            if let Some(w) = width {
+                if x > 0.0 && x + word > w {
+                    lines.push((span.size, Vec::new()));
+                    x = 0.0;
+                    merge = false;
+                }
+            }
+            let line = lines.last_mut().unwrap();
+            line.0 = line.0.max(span.size);
+            match line.1.last_mut() {
+                Some(f) if merge => {
+                    f.text.push_str(piece);
+                    f.width = x + word - f.x;
+                }
+                _ => line.1.push(Fragment {
+                    span: span.clone(),
+                    text: piece.to_string(),
+                    x,
+                    y: 0.0,
+                    width: word,
+                    angle: 0.0,
+                }),
+            }
+            merge = true;
+            x += advance;
+        }
+    }
+
+    let mut y = 0.0;
+    let mut ret = Vec::new();
+    for (i, (size, fragments)) in lines.into_iter().enumerate() {
+        if i > 0 {
+            y += size * line_height;
+        }
+        for mut f in fragments {
+            f.y = y;
+            ret.push(f);
+        }
+    }
+    ret
+}
+
+// Ascent and descent used to place glyphs in a vertical column, as a
+// fraction of the font size.
+const VERTICAL_ASCENT: f64 = 0.88;
+const VERTICAL_BASELINE: f64 = 0.35;
+
+/// Flows `runs` into columns running top to bottom, right to left, with
+/// the first column centred on the origin. CJK glyphs stand upright, other
+/// runs are turned 90 degrees clockwise. Columns break on `\n`, and once
+/// `height` would be exceeded.
+pub fn vertical_layout(
+    context: &mut dyn Context,
+    runs: &[Span],
+    height: Option<f64>,
+    line_height: f64,
+) -> Vec<Fragment> {
+    let column = runs.iter().map(|x| -> f64 { x.size }).fold(0.0, f64::max) * line_height;
+    let mut ret = Vec::new();
+    let mut x = 0.0;
+    let mut y = 0.0;
+
+    for span in runs {
+        select_font(context, span);
+        for (text, upright) in split_vertical(span.text.as_str()) {
+            if text == "\n" {
+                x -= column;
+                y = 0.0;
+                continue;
+            }
+            let pieces = if upright {
+                vec![text]
+            } else {
+                split_words(text)
+            };
+            for piece in pieces {
+                let width = context.text_extents(piece).x_advance;
+                let advance = if upright { span.size } else { width };
+                if let Some(h) = height {
+                    if y > 0.0 && y + advance > h {
+                        x -= column;
+                        y = 0.0;
+                    }
+                }
+                let fragment = if upright {
+                    let (dx, dy) = if is_vertical_punctuation(piece) {
+                        (span.size * 0.6, -span.size * 0.6)
+                    } else {
+                        (0.0, 0.0)
+                    };
+                    Fragment {
+                        span: span.clone(),
+                        text: piece.to_string(),
+                        x: x - width / 2.0 + dx,
+                        y: y + span.size * VERTICAL_ASCENT + dy,
+                        width,
+                        angle: 0.0,
+                    }
+                } else {
+                    Fragment {
+                        span: span.clone(),
+                        text: piece.to_string(),
+                        x: x - span.size * VERTICAL_BASELINE,
+                        y,
+                        width: context.text_extents(piece.trim_end()).x_advance,
+                        angle: PI / 2.0,
+                    }
+                };
+                ret.push(fragment);
+                y += advance;
+            }
+        }
+    }
+    ret
+}
+
+// Splits into upright CJK glyphs, runs of sideways text and line breaks.
+fn split_vertical(text: &str) -> Vec<(&str, bool)> {
+    let mut ret = Vec::new();
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        let end = i + c.len_utf8();
+        if c == '\n' || is_upright(c) {
+            if start < i {
+                ret.push((&text[start..i], false));
+            }
+            ret.push((&text[i..end], c != '\n'));
+            start = end;
+        }
+    }
+    if start < text.len() {
+        ret.push((&text[start..], false));
+    }
+    ret
+}
+
+fn is_upright(c: char) -> bool {
+    match c {
+        // Long vowel marks, wave dashes and brackets turn with the column.
+        '\u{30FC}'
+        | '\u{301C}'
+        | '\u{FF5E}'
+        | '\u{3008}'..='\u{3011}'
+        | '\u{3014}'..='\u{301B}' => false,
+        '\u{FF08}' | '\u{FF09}' | '\u{FF3B}' | '\u{FF3D}' | '\u{FF5B}' | '\u{FF5D}' => false,
+        '\u{3000}'..='\u{30FF}'
+        | '\u{3400}'..='\u{4DBF}'
+        | '\u{4E00}'..='\u{9FFF}'
+        | '\u{AC00}'..='\u{D7AF}'
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{FF01}'..='\u{FF60}' => true,
+        _ => false,
+    }
+}
+
+// Commas and full stops sit in the upper right of a vertical cell.
+fn is_vertical_punctuation(text: &str) -> bool {
+    matches!(text, "\u{3001}" | "\u{3002}" | "\u{FF0C}" | "\u{FF0E}")
+}
+
+/// The ink bounding box of laid out fragments as (left, top, right, bottom).
+pub fn bounds(context: &mut dyn Context, fragments: &[Fragment]) -> Option<(f64, f64, f64, f64)> {
+    if fragments.is_empty() {
+        return None;
+    }
+
+    let mut left = f64::MAX;
+    let mut top = f64::MAX;
+    let mut right = f64::MIN;
+    let mut bottom = f64::MIN;
+    for fragment in fragments {
+        select_font(context, &fragment.span);
+        let extents = context.text_extents(fragment.text.trim_end());
+        let (sin, cos) = fragment.angle.sin_cos();
+        for (dx, dy) in &[
+            (extents.x_bearing, extents.y_bearing),
+            (extents.x_bearing + extents.width, extents.y_bearing),
+            (extents.x_bearing, extents.y_bearing + extents.height),
+            (
+                extents.x_bearing + extents.width,
+                extents.y_bearing + extents.height,
+            ),
+        ] {
+            let x = fragment.x + dx * cos - dy * sin;
+            let y = fragment.y + dx * sin + dy * cos;
+            left = left.min(x);
+            top = top.min(y);
+            right = right.max(x);
+            bottom = bottom.max(y);
+        }
+    }
+    Some((left, top, right, bottom))
+}
+
+// Splits into words that keep their trailing spaces, with each line break
+// as a piece of its own.
+fn split_words(text: &str) -> Vec<&str> {
+    let mut ret = Vec::new();
+    let mut start = 0;
+    let mut in_space = false;
+    for (i, c) in text.char_indices() {
+        if c == '\n' {
+            if start < i {
+                ret.push(&text[start..i]);
+            }
+            ret.push(&text[i..i + 1]);
+            start = i + 1;
+            in_space = false;
+        } else if c.is_whitespace() {
+            in_space = true;
+        } else if in_space {
+            ret.push(&text[start..i]);
+            start = i;
+            in_space = false;
+        }
+    }
+    if start < text.len() {
+        ret.push(&text[start..]);
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bounds, layout, split_vertical, split_words, vertical_layout};
+    use crate::node::Text;
+    use crate::testing_helpers::stub::ContextImpl;
+    use float_cmp::approx_eq;
+
+    #[test]
+    fn split() {
+        assert_eq!(split_words("a bc  d"), vec!["a ", "bc  ", "d"]);
+        assert_eq!(split_words("a\nb "), vec!["a", "\n", "b "]);
+        assert_eq!(split_words(" a"), vec![" ", "a"]);
+    }
+
+    #[test]
+    fn single_line() {
+        let mut context = ContextImpl::new();
+        let runs = Text::default().runs();
+        let subject = layout(&mut context, &runs, None, 1.2);
+        assert_eq!(subject.len(), 1);
+        assert_eq!(subject[0].text, "Hello World!");
+        assert!(approx_eq!(f64, subject[0].width, 84.0));
+    }
+
+    #[test]
+    fn wraps_at_width() {
+        let mut context = ContextImpl::new();
+        let runs = Text::default().runs();
+        let subject = layout(&mut context, &runs, Some(50.0), 1.5);
+        assert_eq!(subject.len(), 2);
+        assert_eq!(subject[0].text, "Hello ");
+        assert!(approx_eq!(f64, subject[0].width, 35.0));
+        assert_eq!(subject[1].text, "World!");
+        assert!(approx_eq!(f64, subject[1].x, 0.0));
+        assert!(approx_eq!(f64, subject[1].y, 21.0));
+    }
+
+    #[test]
+    fn breaks_lines() {
+        let mut context = ContextImpl::new();
+        let text = Text {
+            text: "a\n\nb".to_string(),
+            ..Default::default()
+        };
+        let subject = layout(&mut context, &text.runs(), None, 1.0);
+        assert_eq!(subject.len(), 2);
+        assert!(approx_eq!(f64, subject[1].y, 28.0));
+    }
+
+    #[test]
+    fn flows_runs() {
+        let mut context = ContextImpl::new();
+        let mut runs = Text::default().runs();
+        let mut bold = runs[0].clone();
+        bold.text = "bold".to_string();
+        bold.size = 20.0;
+        runs.push(bold);
+        let subject = layout(&mut context, &runs, None, 1.2);
+        assert_eq!(subject.len(), 2);
+        assert!(approx_eq!(f64, subject[1].x, 84.0));
+        assert!(approx_eq!(f64, subject[1].y, 0.0));
+        assert!(approx_eq!(f64, subject[1].width, 40.0));
+    }
+
+    #[test]
+    fn bounds_of_lines() {
+        let mut context = ContextImpl::new();
+        let runs = Text::default().runs();
+        let fragments = layout(&mut context, &runs, Some(50.0), 1.5);
+        let (left, top, right, bottom) = bounds(&mut context, &fragments).unwrap();
+        assert!(approx_eq!(f64, left, 0.0));
+        assert!(approx_eq!(f64, top, -14.0));
+        assert!(approx_eq!(f64, right, 42.0));
+        assert!(approx_eq!(f64, bottom, 21.0));
+    }
+
+    #[test]
+    fn bounds_of_nothing() {
+        let mut context = ContextImpl::new();
+        assert!(bounds(&mut context, &[]).is_none());
+    }
+
+    #[test]
+    fn split_into_upright_and_sideways() {
+        assert_eq!(
+            split_vertical("縦書きABC。\nー"),
+            vec![
+                ("縦", true),
+                ("書", true),
+                ("き", true),
+                ("ABC", false),
+                ("。", true),
+                ("\n", false),
+                ("ー", false)
+            ]
+        );
+    }
+
+    #[test]
+    fn vertical_columns() {
+        let mut context = ContextImpl::new();
+        let text = Text {
+            text: "縦書き\nAB".to_string(),
+            ..Default::default()
+        };
+        let subject = vertical_layout(&mut context, &text.runs(), None, 1.5);
+        assert_eq!(subject.len(), 4);
+        assert!(approx_eq!(f64, subject[0].x, -3.5));
+        assert!(approx_eq!(f64, subject[1].y, 14.0 + 14.0 * 0.88));
+        assert!(approx_eq!(f64, subject[1].angle, 0.0));
+        assert_eq!(subject[3].text, "AB");
+        assert!(approx_eq!(f64, subject[3].x, -21.0 - 14.0 * 0.35));
+        assert!(approx_eq!(f64, subject[3].y, 0.0));
+        assert!(subject[3].angle > 0.0);
+    }
+
+    #[test]
+    fn vertical_wraps_at_height() {
+        let mut context = ContextImpl::new();
+        let text = Text {
+            text: "縦書きです".to_string(),
+            ..Default::default()
+        };
+        let subject = vertical_layout(&mut context, &text.runs(), Some(30.0), 1.0);
+        assert_eq!(subject.len(), 5);
+        assert!(approx_eq!(f64, subject[2].x, -14.0 - 3.5));
+        assert!(approx_eq!(f64, subject[4].x, -28.0 - 3.5));
+    }
+
+    #[test]
+    fn bounds_of_sideways_text() {
+        let mut context = ContextImpl::new();
+        let text = Text {
+            text: "AB".to_string(),
+            ..Default::default()
+        };
+        let fragments = vertical_layout(&mut context, &text.runs(), None, 1.0);
+        let (left, top, right, bottom) = bounds(&mut context, &fragments).unwrap();
+        assert!(approx_eq!(f64, left, -4.9, epsilon = 1e-9));
+        assert!(approx_eq!(f64, top, 0.0, epsilon = 1e-9));
+        assert!(approx_eq!(f64, right, 9.1, epsilon = 1e-9));
+        assert!(approx_eq!(f64, bottom, 14.0, epsilon = 1e-9));
+    }
+}
@@ -0,0 +1,559 @@
+use std::fmt;
+
+use yaml_rust::parser::{Event, MarkedEventReceiver, Parser};
+use yaml_rust::scanner::Marker;
+
+/// One step of the path from the document root to a YAML node.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// A position in one of the loaded files. `line` and `col` start at 1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mark {
+    pub file: usize,
+    pub line: usize,
+    pub col: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub path: Vec<Segment>,
+    pub key: Option<Mark>,
+    pub value: Mark,
+    pub scalar: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct SourceFile {
+    name: String,
+    text: String,
+}
+
+/// Positions of the nodes of a loaded document, including the nodes spliced
+/// in from other files.
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+    pub entries: Vec<Entry>,
+}
+
+/// A resolved position with the source line it points into.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Location {
+    pub file: String,
+    pub line: usize,
+    pub col: usize,
+    pub excerpt: String,
+}
+
+impl Location {
+    pub fn new(file: &str, text: &str, line: usize, col: usize) -> Location {
+        Location {
+            file: file.to_string(),
+            line,
+            col,
+            excerpt: text
+                .lines()
+                .nth(line.saturating_sub(1))
+                .unwrap_or("")
+                .to_string(),
+        }
+    }
+
+    /// Formats `message` as `file:line:col: message` followed by the source
+    /// line and a caret under the column.
+    pub fn render(&self, message: &str) -> String {
+        let number = self.line.to_string();
+        let pad = " ".repeat(number.len());
+        let indent: String = self
+            .excerpt
+            .chars()
+            .take(self.col.saturating_sub(1))
+            .map(|x| if x == '\t' { '\t' } else { ' ' })
+            .collect();
+        format!(
+            "{}: {}\n{} |\n{} | {}\n{} | {}^",
+            self, message, pad, number, self.excerpt, pad, indent
+        )
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_fmt(format_args!("{}:{}:{}", self.file, self.line, self.col))
+    }
+}
+
+impl SourceMap {
+    pub fn new() -> SourceMap {
+        SourceMap {
+            files: Vec::new(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Registers a file and returns the positions of its nodes. Syntax
+    /// errors are left to the YAML loader.
+    pub fn add_file(&mut self, name: &str, text: &str) -> Vec<Entry> {
+        let file = self.add_text(name, text);
+
+        let mut recorder = Recorder {
+            file,
+            entries: Vec::new(),
+            path: Vec::new(),
+            frames: Vec::new(),
+        };
+        let mut parser = Parser::new(text.chars());
+        let _ = parser.load(&mut recorder, false);
+        recorder.entries
+    }
+
+    /// Registers a file whose positions are read by the caller, and returns
+    /// the index its marks refer to.
+    pub fn add_text(&mut self, name: &str, text: &str) -> usize {
+        self.files.push(SourceFile {
+            name: name.to_string(),
+            text: text.to_string(),
+        });
+        self.files.len() - 1
+    }
+
+    /// Returns the paths of the loaded files, the document first.
+    pub fn files(&self) -> Vec<&str> {
+        let mut ret: Vec<&str> = Vec::new();
+        for x in &self.files {
+            if !ret.contains(&x.name.as_str()) {
+                ret.push(&x.name);
+            }
+        }
+        ret
+    }
+
+    pub fn location(&self, mark: Mark) -> Location {
+        match self.files.get(mark.file) {
+            Some(x) => Location::new(&x.name, &x.text, mark.line, mark.col),
+            None => Location::new("", "", mark.line, mark.col),
+        }
+    }
+
+    /// Finds the best position for an error raised while parsing the node at
+    /// `path`: a scalar or key under it equal to `subject`, then one
+    /// containing it, then the node itself or its closest recorded parent.
+    pub fn locate(&self, path: &[Segment], subject: Option<&str>) -> Option<Location> {
+        let within: Vec<&Entry> = self
+            .entries
+            .iter()
+            .filter(|x| x.path.starts_with(path))
+            .collect();
+
+        if let Some(subject) = subject {
+            let key_of = |x: &Entry| match x.path.last() {
+                Some(Segment::Key(k)) => Some(k.clone()),
+                _ => None,
+            };
+            let found = within
+                .iter()
+                .find(|x| x.scalar.as_deref() == Some(subject))
+                .map(|x| x.value)
+                .or_else(|| {
+                    within
+                        .iter()
+                        .find(|x| key_of(x).as_deref() == Some(subject))
+                        .and_then(|x| x.key)
+                })
+                .or_else(|| {
+                    within
+                        .iter()
+                        .find(|x| x.scalar.as_deref().is_some_and(|s| s.contains(subject)))
+                        .map(|x| x.value)
+                });
+            if let Some(mark) = found {
+                return Some(self.location(mark));
+            }
+        }
+
+        let mut path = path;
+        loop {
+            if let Some(x) = self.entries.iter().find(|x| x.path == path) {
+                return Some(self.location(x.key.unwrap_or(x.value)));
+            }
+            if path.is_empty() {
+                return None;
+            }
+            path = &path[..path.len() - 1];
+        }
+    }
+}
+
+/// Copies the entries of `src` under `from` to `to`, replacing what `dst`
+/// recorded under `to` before.
+pub fn splice(dst: &mut Vec<Entry>, src: &[Entry], from: &[Segment], to: &[Segment]) {
+    dst.retain(|x| !x.path.starts_with(to));
+    for entry in src {
+        if entry.path.starts_with(from) {
+            let mut path = to.to_vec();
+            path.extend_from_slice(&entry.path[from.len()..]);
+            dst.push(Entry {
+                path,
+                ..entry.clone()
+            });
+        }
+    }
+}
+
+/// A comment, or an empty line when `text` is None, of a source file.
+/// `inline` comments follow other content on their line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trivia {
+    pub line: usize,
+    pub col: usize,
+    pub text: Option<String>,
+    pub inline: bool,
+}
+
+/// Returns the comments and empty lines of a YAML source, which the parser
+/// drops. Lines inside quoted and block scalars are content.
+pub fn trivia(text: &str) -> Vec<Trivia> {
+    let mut ret = Vec::new();
+    let mut quote: Option<char> = None;
+    let mut block: Option<usize> = None;
+
+    for (i, line) in text.lines().enumerate() {
+        let indent = line.len() - line.trim_start().len();
+        if let Some(x) = block {
+            if line.trim().is_empty() || indent > x {
+                continue;
+            }
+            block = None;
+        }
+        if quote.is_none() && line.trim().is_empty() {
+            ret.push(Trivia {
+                line: i + 1,
+                col: 1,
+                text: None,
+                inline: false,
+            });
+            continue;
+        }
+
+        let chars: Vec<char> = line.chars().collect();
+        let mut comment = None;
+        let mut j = 0;
+        while j < chars.len() {
+            let c = chars[j];
+            match quote {
+                Some('\'') if c == '\'' => {
+                    if chars.get(j + 1) == Some(&'\'') {
+                        j += 1;
+                    } else {
+                        quote = None;
+                    }
+                }
+                Some('"') if c == '\\' => j += 1,
+                Some('"') if c == '"' => quote = None,
+                Some(_) => {}
+                None => {
+                    let before = chars[..j].iter().rev().find(|x| !x.is_whitespace());
+                    let starts = matches!(
+                        before,
+                        None | Some(':')
+                            | Some('-')
+                            | Some('[')
+                            | Some('{')
+                            | Some(',')
+                            | Some('?')
+                    );
+                    if (c == '\'' || c == '"') && starts {
+                        quote = Some(c);
+                    } else if c == '#' && (j == 0 || chars[j - 1].is_whitespace()) {
+                        comment = Some(j);
+                        break;
+                    }
+                }
+            }
+            j += 1;
+        }
+
+        let content: String = chars[..comment.unwrap_or(chars.len())].iter().collect();
+        let content = content.trim_end();
+        if let Some(j) = comment {
+            ret.push(Trivia {
+                line: i + 1,
+                col: j + 1,
+                text: Some(chars[j..].iter().collect::<String>().trim_end().to_string()),
+                inline: !content.trim().is_empty(),
+            });
+        }
+        // `|` and `>` with optional chomping and indentation indicators.
+        let indicator =
+            content.trim_end_matches(|x: char| x == '+' || x == '-' || x.is_ascii_digit());
+        if quote.is_none()
+            && (indicator.ends_with(" |")
+                || indicator.ends_with(" >")
+                || indicator == "|"
+                || indicator == ">")
+        {
+            block = Some(indent);
+        }
+    }
+
+    ret
+}
+
+enum Frame {
+    Sequence(usize),
+    // The key waiting for its value, or None while reading a key, and the
+    // entry of the mapping itself.
+    Mapping(Option<(String, Mark)>, Option<usize>),
+    // A mapping or sequence used as a key; its content is not recorded.
+    Key(Mark),
+}
+
+struct Recorder {
+    file: usize,
+    entries: Vec<Entry>,
+    path: Vec<Segment>,
+    frames: Vec<Frame>,
+}
+
+impl Recorder {
+    fn mark(&self, marker: Marker) -> Mark {
+        Mark {
+            file: self.file,
+            line: marker.line(),
+            col: marker.col() + 1,
+        }
+    }
+
+    // Returns the segment and key position of a new node, or None when the
+    // node is a mapping key.
+    fn enter(&mut self, mark: Mark, scalar: Option<&str>) -> Option<(Segment, Option<Mark>)> {
+        match self.frames.last_mut() {
+            Some(Frame::Sequence(i)) => {
+                *i += 1;
+                Some((Segment::Index(*i - 1), None))
+            }
+            Some(Frame::Mapping(pending, entry)) => match pending.take() {
+                Some((key, key_mark)) => Some((Segment::Key(key), Some(key_mark))),
+                None => {
+                    *pending = Some((scalar.unwrap_or("").to_string(), mark));
+                    // Block mappings are reported after their first key.
+                    if let Some(x) = entry.take().and_then(|i| self.entries.get_mut(i)) {
+                        if (mark.line, mark.col) < (x.value.line, x.value.col) {
+                            x.value = mark;
+                        }
+                    }
+                    None
+                }
+            },
+            Some(Frame::Key(_)) => None,
+            None => Some((Segment::Key(String::new()), None)),
+        }
+    }
+
+    fn record(
+        &mut self,
+        segment: &Segment,
+        key: Option<Mark>,
+        value: Mark,
+        scalar: Option<String>,
+    ) {
+        let mut path = self.path.clone();
+        if !self.frames.is_empty() {
+            path.push(segment.clone());
+        }
+        self.entries.push(Entry {
+            path,
+            key,
+            value,
+            scalar,
+        });
+    }
+
+    fn start(&mut self, mark: Mark, mut frame: Frame) {
+        match self.enter(mark, None) {
+            Some((segment, key)) => {
+                self.record(&segment, key, mark, None);
+                if !self.frames.is_empty() {
+                    self.path.push(segment);
+                }
+                if let Frame::Mapping(_, entry) = &mut frame {
+                    *entry = Some(self.entries.len() - 1);
+                }
+                self.frames.push(frame);
+            }
+            None => self.frames.push(Frame::Key(mark)),
+        }
+    }
+
+    fn end(&mut self) {
+        if let Some(Frame::Key(mark)) = self.frames.pop() {
+            if let Some(Frame::Mapping(pending, _)) = self.frames.last_mut() {
+                *pending = Some((String::new(), mark));
+            }
+            return;
+        }
+        if !self.frames.is_empty() {
+            self.path.pop();
+        }
+    }
+}
+
+impl MarkedEventReceiver for Recorder {
+    fn on_event(&mut self, ev: Event, marker: Marker) {
+        let mark = self.mark(marker);
+        match ev {
+            Event::SequenceStart(_) => self.start(mark, Frame::Sequence(0)),
+            Event::MappingStart(_) => self.start(mark, Frame::Mapping(None, None)),
+            Event::SequenceEnd | Event::MappingEnd => self.end(),
+            Event::Scalar(v, _, _, _) => {
+                if let Some((segment, key)) = self.enter(mark, Some(&v)) {
+                    self.record(&segment, key, mark, Some(v));
+                }
+            }
+            Event::Alias(_) => {
+                if let Some((segment, key)) = self.enter(mark, None) {
+                    self.record(&segment, key, mark, None);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{splice, trivia, Location, Segment, SourceMap, Trivia};
+
+    fn key(x: &str) -> Segment {
+        Segment::Key(x.to_string())
+    }
+
+    const SRC: &str = "---
+width: 400
+layers:
+  - circle:
+      x: 10
+      color: '#AABBCG'
+  - text: {x: 1, text: hello}
+";
+
+    fn map() -> SourceMap {
+        let mut map = SourceMap::new();
+        map.entries = map.add_file("main.yml", SRC);
+        map
+    }
+
+    #[test]
+    fn record_positions() {
+        let subject = map();
+        let width = subject
+            .entries
+            .iter()
+            .find(|x| x.path == vec![key("width")])
+            .unwrap();
+        assert_eq!((width.key.unwrap().line, width.key.unwrap().col), (2, 1));
+        assert_eq!((width.value.line, width.value.col), (2, 8));
+        assert_eq!(width.scalar.as_deref(), Some("400"));
+
+        let text = subject
+            .entries
+            .iter()
+            .find(|x| x.path == vec![key("layers"), Segment::Index(1), key("text"), key("text")])
+            .unwrap();
+        assert_eq!((text.value.line, text.value.col), (7, 24));
+    }
+
+    #[test]
+    fn locate_subject() {
+        let subject = map();
+        let path = vec![key("layers"), Segment::Index(0)];
+        let location = subject.locate(&path, Some("#AABBCG")).unwrap();
+        assert_eq!((location.line, location.col), (6, 14));
+        let location = subject.locate(&path, Some("x")).unwrap();
+        assert_eq!((location.line, location.col), (5, 7));
+    }
+
+    #[test]
+    fn locate_node() {
+        let subject = map();
+        let path = vec![key("layers"), Segment::Index(1)];
+        let location = subject.locate(&path, None).unwrap();
+        assert_eq!((location.line, location.col), (7, 5));
+        let path = vec![key("layers"), Segment::Index(5)];
+        let location = subject.locate(&path, None).unwrap();
+        assert_eq!((location.line, location.col), (3, 1));
+    }
+
+    #[test]
+    fn splice_entries() {
+        let mut subject = SourceMap::new();
+        let entries = subject.add_file("part.yml", "---\n- circle: {x: 1}\n");
+        splice(
+            &mut subject.entries,
+            &entries,
+            &[Segment::Index(0)],
+            &[key("layers"), Segment::Index(3)],
+        );
+        let location = subject
+            .locate(&[key("layers"), Segment::Index(3)], Some("x"))
+            .unwrap();
+        assert_eq!(location.file, "part.yml");
+        assert_eq!((location.line, location.col), (2, 12));
+    }
+
+    #[test]
+    fn render() {
+        let subject = Location::new("main.yml", SRC, 6, 14);
+        assert_eq!(
+            subject.render("invalid color format '#AABBCG'"),
+            "main.yml:6:14: invalid color format '#AABBCG'
+  |
+6 |       color: '#AABBCG'
+  |              ^"
+        );
+    }
+
+    #[test]
+    fn comments_and_empty_lines() {
+        let src = "---
+# head
+width: 400 # wide
+color: '#fff' # 'quoted #'
+text: \"a # b\"
+
+layers:
+  - # first
+    text: |
+      # content
+
+      more
+    x: don't # c
+";
+        let comment = |line, col, text: &str, inline| Trivia {
+            line,
+            col,
+            text: Some(text.to_string()),
+            inline,
+        };
+        assert_eq!(
+            trivia(src),
+            vec![
+                comment(2, 1, "# head", false),
+                comment(3, 12, "# wide", true),
+                comment(4, 15, "# 'quoted #'", true),
+                Trivia {
+                    line: 6,
+                    col: 1,
+                    text: None,
+                    inline: false
+                },
+                comment(8, 5, "# first", true),
+                comment(13, 14, "# c", true),
+            ]
+        );
+    }
+}
@@ -1,4 +1,6 @@
 use crate::node::Arc;
+use crate::node::ArcClose;
+use crate::node::ArcDirection;
 use crate::node::Color;
 use crate::node::Scale;
 use crate::node::Stroke;
@@ -15,7 +17,9 @@ impl Default for Arc {
             stroke: Stroke::default(),
             start: 0.0,
             end: 360.0,
-            close: true,
+            close: ArcClose::Pie,
+            direction: ArcDirection::Clockwise,
+            inner_radius: 0.0,
         }
     }
 }
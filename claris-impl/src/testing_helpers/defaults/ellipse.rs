@@ -0,0 +1,18 @@
+use crate::node::Color;
+use crate::node::Ellipse;
+use crate::node::Stroke;
+
+impl Default for Ellipse {
+    fn default() -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            rx: 1.0,
+            ry: 1.0,
+            rotation: 0.0,
+            fill: false,
+            color: Color::default(),
+            stroke: Stroke::default(),
+        }
+    }
+}
@@ -0,0 +1,11 @@
+use crate::node::Group;
+
+impl Default for Group {
+    fn default() -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            layers: Vec::new(),
+        }
+    }
+}
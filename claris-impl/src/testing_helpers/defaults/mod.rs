@@ -2,9 +2,13 @@ mod arc;
 mod circle;
 mod color;
 mod curve;
+mod ellipse;
+mod group;
 mod line;
 mod point;
 mod polygon;
 mod rectangle;
+mod regular_polygon;
+mod star;
 mod text;
 mod triangle;
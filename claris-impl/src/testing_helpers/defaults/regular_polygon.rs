@@ -0,0 +1,20 @@
+use crate::node::Color;
+use crate::node::RegularPolygon;
+use crate::node::Scale;
+use crate::node::Stroke;
+
+impl Default for RegularPolygon {
+    fn default() -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            radius: 1.0,
+            sides: 6,
+            rotation: 0.0,
+            fill: false,
+            color: Color::default(),
+            stroke: Stroke::default(),
+            scale: Scale::default(),
+        }
+    }
+}
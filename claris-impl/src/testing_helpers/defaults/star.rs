@@ -0,0 +1,21 @@
+use crate::node::Color;
+use crate::node::Scale;
+use crate::node::Star;
+use crate::node::Stroke;
+
+impl Default for Star {
+    fn default() -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            points: 5,
+            outer_radius: 2.0,
+            inner_radius: 1.0,
+            rotation: 0.0,
+            fill: false,
+            color: Color::default(),
+            stroke: Stroke::default(),
+            scale: Scale::default(),
+        }
+    }
+}
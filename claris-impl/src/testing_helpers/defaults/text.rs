@@ -1,6 +1,7 @@
 use crate::node::Color;
 use crate::node::Scale;
 use crate::node::Text;
+use crate::node::TextDirection;
 use cairo::FontSlant;
 use cairo::FontWeight;
 
@@ -16,6 +17,17 @@ impl Default for Text {
             slant: FontSlant::Normal,
             size: 14.0,
             text: "Hello World!".to_string(),
+            underline: false,
+            strikethrough: false,
+            spans: Vec::new(),
+            width: None,
+            height: None,
+            line_height: 1.2,
+            direction: TextDirection::Horizontal,
+            fit: None,
+            outline: None,
+            background: None,
+            path: None,
         }
     }
 }
@@ -1,5 +1,5 @@
 use crate::Context;
-use cairo::{FontSlant, FontWeight, LineCap};
+use cairo::{FontSlant, FontWeight, LineCap, TextExtents};
 
 #[derive(Default)]
 pub struct ContextImpl {
@@ -7,20 +7,26 @@ pub struct ContextImpl {
     pub save_received: i32,
     pub restore_received: i32,
     pub fill_received: i32,
+    pub stroke_preserve_received: i32,
     pub stroke_received: i32,
     pub set_source_rgba_received: i32,
     pub rectangle_received: i32,
     pub translate_received: i32,
     pub scale_received: i32,
+    pub rotate_received: i32,
     pub set_line_width_received: i32,
     pub set_line_cap_received: i32,
     pub arc_received: i32,
+    pub arc_negative_received: i32,
     pub curve_to_received: i32,
     pub line_to_received: i32,
     pub close_path_received: i32,
     pub select_font_face_received: i32,
     pub set_font_size_received: i32,
     pub show_text_received: i32,
+    pub text_path_received: i32,
+    pub text_extents_received: i32,
+    pub font_size: f64,
 }
 
 impl ContextImpl {
@@ -46,6 +52,10 @@ impl Context for ContextImpl {
         self.fill_received += 1;
     }
 
+    fn stroke_preserve(&mut self) {
+        self.stroke_preserve_received += 1;
+    }
+
     fn stroke(&mut self) {
         self.stroke_received += 1;
     }
@@ -66,6 +76,10 @@ impl Context for ContextImpl {
         self.scale_received += 1;
     }
 
+    fn rotate(&mut self, _angle: f64) {
+        self.rotate_received += 1;
+    }
+
     fn set_line_width(&mut self, _width: f64) {
         self.set_line_width_received += 1;
     }
@@ -78,6 +92,10 @@ impl Context for ContextImpl {
         self.arc_received += 1;
     }
 
+    fn arc_negative(&mut self, _xc: f64, _yc: f64, _radius: f64, _angle1: f64, _angle2: f64) {
+        self.arc_negative_received += 1;
+    }
+
     fn curve_to(&mut self, _x1: f64, _y1: f64, _x2: f64, _y2: f64, _x3: f64, _y3: f64) {
         self.curve_to_received += 1;
     }
@@ -94,11 +112,30 @@ impl Context for ContextImpl {
         self.select_font_face_received += 1;
     }
 
-    fn set_font_size(&mut self, _size: f64) {
+    fn set_font_size(&mut self, size: f64) {
         self.set_font_size_received += 1;
+        self.font_size = size;
     }
 
     fn show_text(&mut self, _text: &str) {
         self.show_text_received += 1;
     }
+
+    fn text_path(&mut self, _text: &str) {
+        self.text_path_received += 1;
+    }
+
+    // Every glyph is treated as a box half as wide as the font size.
+    fn text_extents(&mut self, text: &str) -> TextExtents {
+        self.text_extents_received += 1;
+        let width = text.chars().count() as f64 * self.font_size / 2.0;
+        TextExtents {
+            x_bearing: 0.0,
+            y_bearing: -self.font_size,
+            width,
+            height: self.font_size,
+            x_advance: width,
+            y_advance: 0.0,
+        }
+    }
 }
@@ -0,0 +1,388 @@
+use lsp_server::{
+    Connection, ErrorCode, ExtractError, Message, Notification, Request, RequestId, Response,
+};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, LogMessage,
+    Notification as NotificationTrait, PublishDiagnostics,
+};
+use lsp_types::request::{
+    ColorPresentationRequest, Completion, DocumentColor, HoverRequest, Request as RequestTrait,
+};
+use lsp_types::{
+    Color, ColorInformation, ColorPresentation, ColorProviderCapability, CompletionItem,
+    CompletionItemKind, CompletionOptions, CompletionResponse, Diagnostic, DiagnosticSeverity,
+    Documentation, Hover, HoverContents, HoverProviderCapability, LogMessageParams, MarkupContent,
+    MarkupKind, MessageType, Position, PublishDiagnosticsParams, Range, ServerCapabilities,
+    TextDocumentSyncCapability, TextDocumentSyncKind, Url,
+};
+use std::collections::HashMap;
+use std::error::Error;
+
+use claris_impl::{OpenDocument, Severity, Span, SuggestionKind};
+
+type Result<T> = std::result::Result<T, Box<dyn Error + Sync + Send>>;
+
+/// Serves the Language Server Protocol over stdio until the client exits.
+pub fn run() -> Result<()> {
+    let (connection, io_threads) = Connection::stdio();
+
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec![" ".to_string(), ":".to_string()]),
+            ..CompletionOptions::default()
+        }),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        color_provider: Some(ColorProviderCapability::Simple(true)),
+        ..ServerCapabilities::default()
+    };
+    connection.initialize(serde_json::to_value(capabilities)?)?;
+    serve(connection)?;
+
+    io_threads.join()?;
+    Ok(())
+}
+
+fn serve(connection: Connection) -> Result<()> {
+    let mut documents: HashMap<Url, String> = HashMap::new();
+
+    for message in &connection.receiver {
+        match message {
+            Message::Request(req) => {
+                if connection.handle_shutdown(&req)? {
+                    return Ok(());
+                }
+                let response = respond(&documents, req);
+                connection.sender.send(Message::Response(response))?;
+            }
+            Message::Notification(not) => match update(&mut documents, not) {
+                Ok(Some(uri)) => {
+                    let diagnostics = match documents.get(&uri) {
+                        Some(text) => diagnostics(&uri, text),
+                        None => Vec::new(),
+                    };
+                    let params = PublishDiagnosticsParams {
+                        uri,
+                        diagnostics,
+                        version: None,
+                    };
+                    send::<PublishDiagnostics>(&connection, params)?;
+                }
+                Ok(None) => {}
+                Err(message) => {
+                    let params = LogMessageParams {
+                        typ: MessageType::ERROR,
+                        message,
+                    };
+                    send::<LogMessage>(&connection, params)?;
+                }
+            },
+            Message::Response(_) => {}
+        }
+    }
+    Ok(())
+}
+
+fn send<N: NotificationTrait>(connection: &Connection, params: N::Params) -> Result<()> {
+    let not = Notification::new(N::METHOD.to_string(), params);
+    connection.sender.send(Message::Notification(not))?;
+    Ok(())
+}
+
+// Applies a document notification and returns the document whose
+// diagnostics changed, or why its params are invalid.
+fn update(
+    documents: &mut HashMap<Url, String>,
+    not: Notification,
+) -> std::result::Result<Option<Url>, String> {
+    let not = match notification::<DidOpenTextDocument>(not)? {
+        Ok(params) => {
+            let uri = params.text_document.uri;
+            documents.insert(uri.clone(), params.text_document.text);
+            return Ok(Some(uri));
+        }
+        Err(x) => x,
+    };
+    let not = match notification::<DidChangeTextDocument>(not)? {
+        Ok(params) => {
+            let uri = params.text_document.uri;
+            if let Some(change) = params.content_changes.into_iter().last() {
+                documents.insert(uri.clone(), change.text);
+            }
+            return Ok(Some(uri));
+        }
+        Err(x) => x,
+    };
+    match notification::<DidCloseTextDocument>(not)? {
+        Ok(params) => {
+            let uri = params.text_document.uri;
+            documents.remove(&uri);
+            Ok(Some(uri))
+        }
+        Err(_) => Ok(None),
+    }
+}
+
+// Answers a request, or rejects it when its params are invalid.
+fn respond(documents: &HashMap<Url, String>, req: Request) -> Response {
+    let id = req.id.clone();
+    match dispatch(documents, req) {
+        Ok(x) => x,
+        Err(message) => Response::new_err(id, ErrorCode::InvalidParams as i32, message),
+    }
+}
+
+fn dispatch(
+    documents: &HashMap<Url, String>,
+    req: Request,
+) -> std::result::Result<Response, String> {
+    let req = match request::<Completion>(req)? {
+        Ok((id, params)) => {
+            let position = params.text_document_position;
+            let items = open(documents, &position.text_document.uri).map(|doc| {
+                let (line, col) = from_position(documents, &position);
+                doc.completions(line, col)
+                    .into_iter()
+                    .map(|x| CompletionItem {
+                        label: x.label,
+                        kind: Some(match x.kind {
+                            SuggestionKind::Layer => CompletionItemKind::CLASS,
+                            SuggestionKind::Option => CompletionItemKind::PROPERTY,
+                            SuggestionKind::Value => CompletionItemKind::VALUE,
+                        }),
+                        detail: Some(x.detail),
+                        documentation: Some(Documentation::MarkupContent(markdown(
+                            x.documentation,
+                        ))),
+                        ..CompletionItem::default()
+                    })
+                    .collect::<Vec<CompletionItem>>()
+            });
+            return Ok(Response::new_ok(id, items.map(CompletionResponse::Array)));
+        }
+        Err(x) => x,
+    };
+    let req = match request::<HoverRequest>(req)? {
+        Ok((id, params)) => {
+            let position = params.text_document_position_params;
+            let hover = open(documents, &position.text_document.uri).and_then(|doc| {
+                let (line, col) = from_position(documents, &position);
+                doc.hover(line, col)
+            });
+            let hover = hover.map(|x| Hover {
+                contents: HoverContents::Markup(markdown(x)),
+                range: None,
+            });
+            return Ok(Response::new_ok(id, hover));
+        }
+        Err(x) => x,
+    };
+    let req = match request::<DocumentColor>(req)? {
+        Ok((id, params)) => {
+            let uri = params.text_document.uri;
+            let colors = match (open(documents, &uri), documents.get(&uri)) {
+                (Some(doc), Some(text)) => doc
+                    .swatches()
+                    .into_iter()
+                    .map(|x| ColorInformation {
+                        range: range(text, x.span),
+                        color: Color {
+                            red: f32::from(x.r) / 255.0,
+                            green: f32::from(x.g) / 255.0,
+                            blue: f32::from(x.b) / 255.0,
+                            alpha: x.a,
+                        },
+                    })
+                    .collect(),
+                _ => Vec::new(),
+            };
+            return Ok(Response::new_ok(id, colors));
+        }
+        Err(x) => x,
+    };
+    let req = match request::<ColorPresentationRequest>(req)? {
+        Ok((id, params)) => return Ok(Response::new_ok(id, vec![presentation(params.color)])),
+        Err(x) => x,
+    };
+
+    Ok(Response::new_err(
+        req.id,
+        ErrorCode::MethodNotFound as i32,
+        format!("{} is not supported.", req.method),
+    ))
+}
+
+fn request<R: RequestTrait>(
+    req: Request,
+) -> std::result::Result<std::result::Result<(RequestId, R::Params), Request>, String> {
+    match req.extract(R::METHOD) {
+        Ok(x) => Ok(Ok(x)),
+        Err(ExtractError::MethodMismatch(req)) => Ok(Err(req)),
+        Err(ExtractError::JsonError { method, error }) => Err(format!("{}: {}", method, error)),
+    }
+}
+
+fn notification<N: NotificationTrait>(
+    not: Notification,
+) -> std::result::Result<std::result::Result<N::Params, Notification>, String> {
+    match not.extract(N::METHOD) {
+        Ok(x) => Ok(Ok(x)),
+        Err(ExtractError::MethodMismatch(not)) => Ok(Err(not)),
+        Err(ExtractError::JsonError { method, error }) => Err(format!("{}: {}", method, error)),
+    }
+}
+
+fn open(documents: &HashMap<Url, String>, uri: &Url) -> Option<OpenDocument> {
+    documents.get(uri).map(|x| document(uri, x))
+}
+
+// Documents are read at their file path, so includes and images resolve
+// relative to them.
+fn document(uri: &Url, text: &str) -> OpenDocument {
+    let path = match uri.to_file_path() {
+        Ok(x) => x.to_string_lossy().to_string(),
+        Err(_) => uri.path().to_string(),
+    };
+    OpenDocument::new(&path, text)
+}
+
+fn diagnostics(uri: &Url, text: &str) -> Vec<Diagnostic> {
+    document(uri, text)
+        .problems()
+        .into_iter()
+        .map(|x| Diagnostic {
+            range: range(text, x.span),
+            severity: Some(match x.severity {
+                Severity::Error => DiagnosticSeverity::ERROR,
+                Severity::Warning => DiagnosticSeverity::WARNING,
+            }),
+            source: Some("claris".to_string()),
+            message: x.message,
+            ..Diagnostic::default()
+        })
+        .collect()
+}
+
+fn markdown(value: String) -> MarkupContent {
+    MarkupContent {
+        kind: MarkupKind::Markdown,
+        value,
+    }
+}
+
+// Writes a color as `#rrggbb`, or with `rgba()` when it is translucent.
+fn presentation(color: Color) -> ColorPresentation {
+    let byte = |x: f32| (x.clamp(0.0, 1.0) * 255.0).round() as u8;
+    let (r, g, b) = (byte(color.red), byte(color.green), byte(color.blue));
+    let label = if color.alpha < 1.0 {
+        format!(
+            "rgba({}, {}, {}, {})",
+            r,
+            g,
+            b,
+            (color.alpha * 100.0).round() / 100.0
+        )
+    } else {
+        format!("#{:02x}{:02x}{:02x}", r, g, b)
+    };
+    ColorPresentation {
+        label,
+        text_edit: None,
+        additional_text_edits: None,
+    }
+}
+
+// LSP positions count lines from 0 and columns in UTF-16 code units, while
+// documents count both from 1 in characters.
+fn from_position(
+    documents: &HashMap<Url, String>,
+    position: &lsp_types::TextDocumentPositionParams,
+) -> (usize, usize) {
+    let Position { line, character } = position.position;
+    let text = documents
+        .get(&position.text_document.uri)
+        .and_then(|x| x.lines().nth(line as usize))
+        .unwrap_or("");
+    let mut units = 0;
+    let mut col = 1;
+    for c in text.chars() {
+        if units >= character as usize {
+            break;
+        }
+        units += c.len_utf16();
+        col += 1;
+    }
+    (line as usize + 1, col)
+}
+
+fn range(text: &str, span: Span) -> Range {
+    let line = text.lines().nth(span.line.saturating_sub(1)).unwrap_or("");
+    let units = |col: usize| -> u32 {
+        line.chars()
+            .take(col.saturating_sub(1))
+            .map(char::len_utf16)
+            .sum::<usize>() as u32
+    };
+    let line_number = span.line.saturating_sub(1) as u32;
+    Range {
+        start: Position::new(line_number, units(span.col)),
+        end: Position::new(line_number, units(span.end)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{presentation, range, respond, update};
+    use claris_impl::Span;
+    use lsp_server::{ErrorCode, Notification, Request, RequestId};
+    use lsp_types::{Color, Position};
+    use std::collections::HashMap;
+
+    #[test]
+    fn utf16_range() {
+        let subject = range(
+            "a: 1\ntext: '😀 é'\n",
+            Span {
+                line: 2,
+                col: 7,
+                end: 12,
+            },
+        );
+        assert_eq!(subject.start, Position::new(1, 6));
+        assert_eq!(subject.end, Position::new(1, 12));
+    }
+
+    #[test]
+    fn color_presentation() {
+        let color = |alpha| Color {
+            red: 0.2,
+            green: 0.4,
+            blue: 0.6,
+            alpha,
+        };
+        assert_eq!(presentation(color(1.0)).label, "#336699");
+        assert_eq!(presentation(color(0.5)).label, "rgba(51, 102, 153, 0.5)");
+    }
+
+    #[test]
+    fn invalid_params() {
+        let mut documents = HashMap::new();
+        let req = Request::new(
+            RequestId::from(1),
+            "textDocument/hover".to_string(),
+            serde_json::json!({"position": 1}),
+        );
+        let subject = respond(&documents, req);
+        assert_eq!(subject.error.unwrap().code, ErrorCode::InvalidParams as i32);
+
+        let not = Notification::new(
+            "textDocument/didOpen".to_string(),
+            serde_json::json!({"textDocument": 1}),
+        );
+        assert!(update(&mut documents, not)
+            .unwrap_err()
+            .starts_with("textDocument/didOpen: "));
+        let not = Notification::new("initialized".to_string(), serde_json::json!({}));
+        assert_eq!(update(&mut documents, not), Ok(None));
+    }
+}
@@ -2,19 +2,36 @@ use getopts::Options;
 use log::debug;
 use simple_logger;
 use std::path::{Path, PathBuf};
-use std::{env, process};
+use std::{env, fs, process};
 
-use claris_impl::Compiler;
+use claris_impl::{Compiler, InputFormat, Scope, Value};
+
+mod lsp;
+mod watch;
 
 #[derive(Debug)]
 struct Args {
     inputs: Vec<PathBuf>,
     output_dir: Option<PathBuf>,
     force: bool,
+    data: Option<PathBuf>,
+    name_field: Option<String>,
+    defines: Vec<(String, String)>,
+    vars_file: Option<PathBuf>,
+    theme: Option<String>,
+    all_themes: bool,
+    check: bool,
+    strict: bool,
+    input_format: Option<InputFormat>,
+    fmt: bool,
+    watch: bool,
 }
 
 fn print_usage(program: &str, opts: &Options) {
-    let brief = format!("Usage: {} [options] FILE", program);
+    let brief = format!(
+        "Usage: {0} [options] FILE\n       {0} fmt [--check] FILE\n       {0} schema\n       {0} lsp",
+        program
+    );
     print!("{}", opts.usage(&brief));
     process::exit(0);
 }
@@ -31,6 +48,58 @@ fn parse_args() -> Args {
         "[directory]",
     );
     opts.optflag("f", "", "Overwrite output file.");
+    opts.optopt(
+        "",
+        "data",
+        "Render once per row of a CSV or JSON array file.",
+        "[file]",
+    );
+    opts.optopt(
+        "",
+        "name",
+        "Name each output of --data after this field. default: Numbered input file name.",
+        "[field]",
+    );
+    opts.optmulti(
+        "D",
+        "",
+        "Set a variable. Takes precedence over --vars and the file's vars.",
+        "[key=value]",
+    );
+    opts.optopt(
+        "",
+        "vars",
+        "Read variables from a yaml, json or toml file.",
+        "[file]",
+    );
+    opts.optopt(
+        "",
+        "theme",
+        "Render with a theme as FILE.[theme].png.",
+        "[theme]",
+    );
+    opts.optflag("", "all-themes", "Render every theme as FILE.[theme].png.");
+    opts.optflag(
+        "",
+        "check",
+        "Print every error and warning without rendering. With fmt, list unformatted files without rewriting them.",
+    );
+    opts.optflag(
+        "",
+        "strict",
+        "Reject unknown options, invalid option values and invalid colors.",
+    );
+    opts.optopt(
+        "",
+        "input-format",
+        "Read input files as yaml, json or toml. default: By file extension.",
+        "[format]",
+    );
+    opts.optflag(
+        "",
+        "watch",
+        "Keep running and render inputs again whenever they or the files they include change. Implies -f.",
+    );
     opts.optflag("h", "help", "Print usage");
 
     let matches = opts
@@ -45,11 +114,32 @@ fn parse_args() -> Args {
         print_usage(&program, &opts);
     }
 
+    if matches.free == ["schema"] {
+        println!("{}", Compiler::schema());
+        process::exit(0);
+    }
+
+    if matches.free == ["lsp"] {
+        // The log would interleave with the messages on stdout.
+        log::set_max_level(log::LevelFilter::Off);
+        if let Err(e) = lsp::run() {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+        process::exit(0);
+    }
+
     debug!("{:?}", matches.free);
 
+    let fmt = matches.free[0] == "fmt";
+    if fmt && matches.free.len() == 1 {
+        print_usage(&program, &opts);
+    }
+
     let inputs = matches
         .free
         .iter()
+        .skip(if fmt { 1 } else { 0 })
         .map(|i| -> PathBuf { PathBuf::new().join(i) })
         .collect::<Vec<PathBuf>>();
 
@@ -57,20 +147,50 @@ fn parse_args() -> Args {
         .opt_str("d")
         .map(|d| -> PathBuf { PathBuf::new().join(d) });
 
+    let defines = matches
+        .opt_strs("D")
+        .iter()
+        .map(|d| -> (String, String) {
+            match d.find('=') {
+                Some(i) if i > 0 => (d[..i].to_string(), d[i + 1..].to_string()),
+                _ => {
+                    println!("{} is not key=value.", d);
+                    process::exit(1);
+                }
+            }
+        })
+        .collect::<Vec<(String, String)>>();
+
+    let input_format = matches.opt_str("input-format").map(|x| -> InputFormat {
+        InputFormat::parse(&x).unwrap_or_else(|| {
+            println!("{} is not yaml, json or toml.", x);
+            process::exit(1);
+        })
+    });
+
     Args {
         inputs,
         output_dir,
         force: matches.opt_present("f"),
+        data: matches
+            .opt_str("data")
+            .map(|d| -> PathBuf { PathBuf::new().join(d) }),
+        name_field: matches.opt_str("name"),
+        defines,
+        vars_file: matches
+            .opt_str("vars")
+            .map(|d| -> PathBuf { PathBuf::new().join(d) }),
+        theme: matches.opt_str("theme"),
+        all_themes: matches.opt_present("all-themes"),
+        check: matches.opt_present("check"),
+        strict: matches.opt_present("strict"),
+        input_format,
+        fmt,
+        watch: matches.opt_present("watch"),
     }
 }
 
-fn exec_once(input: &Path, output_dir: Option<PathBuf>, force: bool) {
-    debug!(
-        "input: {}, output_dir: {:?}",
-        input.to_string_lossy(),
-        output_dir
-    );
-
+fn validate_input(input: &Path) {
     if !input.exists() {
         println!("{} is not exists", input.to_str().unwrap());
         process::exit(1);
@@ -80,47 +200,90 @@ fn exec_once(input: &Path, output_dir: Option<PathBuf>, force: bool) {
         println!("{} is not file.", input.to_str().unwrap());
         process::exit(1);
     }
+}
 
-    match input.extension() {
+fn output_directory(input: &Path, output_dir: Option<PathBuf>) -> PathBuf {
+    match output_dir {
         Some(x) => {
-            if x != "yml" {
-                println!("{} is not yaml file.", input.to_str().unwrap());
+            if !x.is_dir() {
+                println!("{} is not directory.", x.to_str().unwrap());
                 process::exit(1);
             }
+
+            x
         }
-        _ => {
-            println!("{} is not yaml file.", input.to_str().unwrap());
-            process::exit(1);
+        None => {
+            if input.is_absolute() {
+                input.parent().unwrap().to_path_buf()
+            } else {
+                env::current_dir().unwrap()
+            }
+        }
+    }
+}
+
+fn build_scope(args: &Args) -> Result<Scope, String> {
+    let mut scope = match &args.vars_file {
+        Some(x) => {
+            Compiler::load_vars(x.to_str().unwrap().to_string()).map_err(|e| e.to_string())?
         }
+        None => Scope::new(),
+    };
+    for (key, value) in &args.defines {
+        scope.set(key, Value::parse(value));
+    }
+    scope.set_strict(args.strict);
+    Ok(scope)
+}
+
+// Every theme of the input with --all-themes; without themes the input is
+// rendered once as usual.
+fn themes(input: &Path, args: &Args) -> Option<Vec<Option<String>>> {
+    if !args.all_themes {
+        return Some(vec![args.theme.clone()]);
     }
 
+    validate_input(input);
+    match Compiler::themes(input.to_str().unwrap().to_string(), args.input_format) {
+        Ok(x) if x.is_empty() => Some(vec![None]),
+        Ok(x) => Some(x.into_iter().map(Some).collect()),
+        Err(e) => {
+            println!("{}", e);
+            None
+        }
+    }
+}
+
+fn exec_once(
+    input: &Path,
+    output_dir: Option<PathBuf>,
+    force: bool,
+    format: Option<InputFormat>,
+    scope: &Scope,
+) {
+    debug!(
+        "input: {}, output_dir: {:?}",
+        input.to_string_lossy(),
+        output_dir
+    );
+
+    validate_input(input);
+
+    let extension = match scope.theme() {
+        Some(theme) => format!("{}.png", theme),
+        None => "png".to_string(),
+    };
     let output_filename = input
         .to_path_buf()
         .file_name()
         .map(|f| -> PathBuf {
             let mut r = PathBuf::new().join(f);
-            r.set_extension("png");
+            r.set_extension(&extension);
             r
         })
         .unwrap();
 
-    let output_path: PathBuf = match output_dir {
-        Some(x) => {
-            if !x.is_dir() {
-                println!("{} is not directory.", x.to_str().unwrap());
-                process::exit(1);
-            }
-
-            x.join(output_filename)
-        }
-        None => {
-            if input.is_absolute() {
-                input.parent().unwrap().to_path_buf().join(output_filename)
-            } else {
-                env::current_dir().unwrap().join(output_filename)
-            }
-        }
-    };
+    let output_path: PathBuf = output_directory(input, output_dir).join(output_filename);
 
     debug!(
         "input: {}, output: {}",
@@ -133,15 +296,146 @@ fn exec_once(input: &Path, output_dir: Option<PathBuf>, force: bool) {
         process::exit(1);
     }
 
-    match Compiler::compile_to_png(
+    match Compiler::compile_to_png_with_scope(
         input.to_str().unwrap().to_string(),
         output_path.to_str().unwrap().to_string(),
+        format,
+        scope,
     ) {
         Ok(_) => {}
         Err(e) => println!("{}", e),
     }
 }
 
+// Returns false when the input has errors.
+fn exec_check(input: &Path, format: Option<InputFormat>, scope: &Scope) -> bool {
+    debug!("input: {}", input.to_string_lossy());
+
+    validate_input(input);
+
+    match Compiler::validate(input.to_str().unwrap().to_string(), format, scope) {
+        Ok(warnings) => {
+            for warning in warnings {
+                println!("{}", warning);
+            }
+            true
+        }
+        Err(e) => {
+            println!("{}", e);
+            false
+        }
+    }
+}
+
+// Rewrites `input` in its canonical layout, or with `check` only reports
+// whether it is in it.
+fn exec_fmt(input: &Path, check: bool) -> bool {
+    debug!("input: {}", input.to_string_lossy());
+
+    validate_input(input);
+
+    let path = input.to_str().unwrap();
+    let formatted = match Compiler::format(path.to_string()) {
+        Ok(x) => x,
+        Err(e) => {
+            println!("{}", e);
+            return false;
+        }
+    };
+    if fs::read_to_string(input).ok().as_deref() == Some(formatted.as_str()) {
+        return true;
+    }
+    if check {
+        println!("{} is not formatted.", path);
+        return false;
+    }
+    match fs::write(input, formatted) {
+        Ok(_) => true,
+        Err(_) => {
+            println!("{} is not writable.", path);
+            false
+        }
+    }
+}
+
+fn exec_batch(
+    input: &Path,
+    data: &Path,
+    name_field: Option<String>,
+    output_dir: Option<PathBuf>,
+    force: bool,
+    format: Option<InputFormat>,
+    scope: &Scope,
+) {
+    debug!(
+        "input: {}, data: {}, output_dir: {:?}",
+        input.to_string_lossy(),
+        data.to_string_lossy(),
+        output_dir
+    );
+
+    validate_input(input);
+
+    if !data.is_file() {
+        println!("{} is not file.", data.to_str().unwrap());
+        process::exit(1);
+    }
+
+    let output_dir = output_directory(input, output_dir);
+
+    match Compiler::compile_batch_to_png(
+        input.to_str().unwrap().to_string(),
+        data.to_str().unwrap().to_string(),
+        output_dir.to_str().unwrap().to_string(),
+        name_field,
+        force,
+        format,
+        scope,
+    ) {
+        Ok(outputs) => debug!("outputs: {:?}", outputs),
+        Err(e) => println!("{}", e),
+    }
+}
+
+// Renders or checks every theme of `input`. Returns false when a check
+// finds errors or the themes cannot be read.
+fn exec(input: &Path, args: &Args, scope: &Scope) -> bool {
+    let themes = match themes(input, args) {
+        Some(x) => x,
+        None => return false,
+    };
+
+    let mut ok = true;
+    for theme in themes {
+        debug!("--------------------begin--------------------");
+        let mut scope = scope.clone();
+        if let Some(theme) = theme {
+            scope.set_theme(&theme);
+        }
+        match &args.data {
+            _ if args.check => ok &= exec_check(input, args.input_format, &scope),
+            Some(data) => exec_batch(
+                input,
+                data.as_path(),
+                args.name_field.clone(),
+                args.output_dir.clone(),
+                args.force || args.watch,
+                args.input_format,
+                &scope,
+            ),
+            None => exec_once(
+                input,
+                args.output_dir.clone(),
+                args.force || args.watch,
+                args.input_format,
+                &scope,
+            ),
+        }
+        debug!("---------------------end---------------------");
+    }
+    ok
+}
+
 fn init() {
     simple_logger::init().unwrap();
 }
@@ -152,9 +446,31 @@ fn main() {
     let args = parse_args();
     debug!("{:?}", args);
 
-    for input in args.inputs {
-        debug!("--------------------begin--------------------");
-        exec_once(input.as_path(), args.output_dir.clone(), args.force);
-        debug!("---------------------end---------------------");
+    let scope = build_scope(&args).unwrap_or_else(|e| {
+        println!("{}", e);
+        process::exit(1);
+    });
+
+    let mut failed = false;
+    if args.fmt {
+        for input in &args.inputs {
+            failed |= !exec_fmt(input.as_path(), args.check);
+        }
+        process::exit(if failed { 1 } else { 0 });
+    }
+
+    for input in &args.inputs {
+        failed |= !exec(input.as_path(), &args, &scope);
+    }
+
+    if args.watch {
+        if let Err(e) = watch::run(&args, scope) {
+            println!("{}", e);
+            process::exit(1);
+        }
+    }
+
+    if failed {
+        process::exit(1);
     }
 }
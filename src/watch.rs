@@ -0,0 +1,121 @@
+use log::debug;
+use notify::event::EventKind;
+use notify::{Event, RecursiveMode, Watcher};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use claris_impl::{Compiler, Scope};
+
+use crate::{build_scope, exec, Args};
+
+// Changes this soon after the first one are handled with it, as editors
+// often save a file in several steps.
+const SETTLE: Duration = Duration::from_millis(100);
+
+/// Renders the inputs again whenever one of them, a file they include or
+/// import, or the `--data` or `--vars` file changes, until interrupted. A
+/// failed render leaves the previous output in place.
+pub fn run(args: &Args, scope: Scope) -> Result<(), notify::Error> {
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    // Directories are watched rather than files, so that files replaced on
+    // save keep being watched.
+    let mut directories: HashSet<PathBuf> = HashSet::new();
+    let mut watch = |files: &[PathBuf]| -> Result<(), notify::Error> {
+        for dir in files.iter().filter_map(|x| x.parent()) {
+            if directories.insert(dir.to_path_buf()) {
+                watcher.watch(dir, RecursiveMode::NonRecursive)?;
+            }
+        }
+        Ok(())
+    };
+
+    let vars = args.vars_file.as_deref().and_then(canonical);
+    watch(vars.as_slice())?;
+    let mut inputs: Vec<(PathBuf, Vec<PathBuf>)> = Vec::new();
+    for input in &args.inputs {
+        let files = dependencies(input, args, &[]);
+        watch(&files)?;
+        inputs.push((input.clone(), files));
+    }
+
+    let mut scope = scope;
+    println!("Watching for changes. Press Ctrl-C to stop.");
+    while let Ok(event) = rx.recv() {
+        let mut changed = HashSet::new();
+        collect(event, &mut changed);
+        let until = Instant::now() + SETTLE;
+        while let Ok(event) = rx.recv_timeout(until.saturating_duration_since(Instant::now())) {
+            collect(event, &mut changed);
+        }
+        debug!("changed: {:?}", changed);
+
+        let all = vars.as_ref().is_some_and(|x| changed.contains(x));
+        if all {
+            match build_scope(args) {
+                Ok(x) => scope = x,
+                Err(e) => {
+                    println!("{}", e);
+                    continue;
+                }
+            }
+        }
+
+        for (input, files) in inputs.iter_mut() {
+            if !all && !files.iter().any(|x| changed.contains(x)) {
+                continue;
+            }
+            // Removed while being saved; its creation is reported next.
+            if !input.is_file() {
+                continue;
+            }
+            println!("Rendering {} again.", input.to_str().unwrap());
+            exec(input, args, &scope);
+            *files = dependencies(input, args, files);
+            watch(files)?;
+        }
+    }
+    Ok(())
+}
+
+fn collect(event: Result<Event, notify::Error>, changed: &mut HashSet<PathBuf>) {
+    match event {
+        Ok(x) => {
+            if !matches!(x.kind, EventKind::Access(_)) {
+                changed.extend(x.paths);
+            }
+        }
+        Err(e) => println!("{}", e),
+    }
+}
+
+// The files whose changes call for rendering `input` again. When it cannot
+// be loaded, the files it had before stay watched.
+fn dependencies(input: &Path, args: &Args, previous: &[PathBuf]) -> Vec<PathBuf> {
+    let mut ret: Vec<PathBuf> =
+        match Compiler::dependencies(input.to_str().unwrap().to_string(), args.input_format) {
+            Ok(x) => x.iter().filter_map(|x| canonical(Path::new(x))).collect(),
+            Err(_) => previous.to_vec(),
+        };
+    for x in [Some(input), args.data.as_deref()].iter().flatten() {
+        if let Some(x) = canonical(x) {
+            if !ret.contains(&x) {
+                ret.push(x);
+            }
+        }
+    }
+    ret
+}
+
+// Resolves the directory of `path`, as changes are reported under watched
+// directories, while the file itself may be missing for a moment.
+fn canonical(path: &Path) -> Option<PathBuf> {
+    let dir = match path.parent() {
+        Some(x) if !x.as_os_str().is_empty() => x,
+        _ => Path::new("."),
+    };
+    Some(fs::canonicalize(dir).ok()?.join(path.file_name()?))
+}